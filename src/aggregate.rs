@@ -0,0 +1,172 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use sql_parse::{Expression, IdentifierPart, Select, Span, Spanned};
+
+use crate::{plan::for_each_child, type_function::is_aggregate_function, typer::Typer};
+
+fn is_aggregate(e: &Expression<'_>) -> bool {
+    match e {
+        Expression::Count { .. } | Expression::GroupConcat { .. } => true,
+        Expression::Function(f, _, _) => is_aggregate_function(f),
+        _ => false,
+    }
+}
+
+/// Does the expression contain an aggregate outside subqueries
+pub(crate) fn contains_aggregate(e: &Expression<'_>) -> bool {
+    let mut found = is_aggregate(e);
+    if !found {
+        for_each_child(e, &mut |c| found = found || contains_aggregate(c));
+    }
+    found
+}
+
+/// Walk an expression without descending into subqueries, collecting the
+/// aggregates and the column references outside aggregates. Nested aggregates
+/// are reported as errors.
+fn walk<'a, 'e>(
+    typer: &mut Typer<'a, '_>,
+    e: &'e Expression<'a>,
+    aggregate: Option<&'e Expression<'a>>,
+    aggregates: &mut Vec<&'e Expression<'a>>,
+    columns: &mut Vec<&'e Expression<'a>>,
+) {
+    let aggregate = if is_aggregate(e) {
+        if let Some(outer) = aggregate {
+            typer
+                .err("Aggregate functions cannot be nested", e)
+                .frag("Inside this aggregate", outer);
+        } else {
+            aggregates.push(e);
+        }
+        Some(e)
+    } else {
+        aggregate
+    };
+    if let Expression::Identifier(parts) = e {
+        if aggregate.is_none() && !matches!(parts.last(), Some(IdentifierPart::Star(_))) {
+            columns.push(e);
+        }
+    }
+    for_each_child(e, &mut |c| walk(typer, c, aggregate, aggregates, columns));
+}
+
+/// Does the column reference c name the same column as the grouped expression g
+fn same_column(c: &[IdentifierPart<'_>], g: &[IdentifierPart<'_>]) -> bool {
+    match (c, g) {
+        ([.., IdentifierPart::Name(c)], [IdentifierPart::Name(g)])
+        | ([IdentifierPart::Name(c)], [.., IdentifierPart::Name(g)]) => c == g,
+        (
+            [IdentifierPart::Name(ct), IdentifierPart::Name(c)],
+            [IdentifierPart::Name(gt), IdentifierPart::Name(g)],
+        ) => ct == gt && c == g,
+        _ => false,
+    }
+}
+
+/// Check the use of aggregate functions in a select
+///
+/// Aggregates may not be nested or used in WHERE. When ONLY_FULL_GROUP_BY
+/// checking is enabled, columns selected or used in HAVING outside aggregates
+/// must be grouped by, if the select groups or aggregates.
+pub(crate) fn check_aggregates<'a>(typer: &mut Typer<'a, '_>, select: &Select<'a>) {
+    if let Some((where_, _)) = &select.where_ {
+        let mut aggregates = Vec::new();
+        walk(typer, where_, None, &mut aggregates, &mut Vec::new());
+        for a in aggregates {
+            typer.err("Aggregate functions are not allowed in WHERE", a);
+        }
+    }
+
+    let mut aggregates = Vec::new();
+    let mut select_columns = Vec::new();
+    for e in &select.select_exprs {
+        let mut columns = Vec::new();
+        walk(typer, &e.expr, None, &mut aggregates, &mut columns);
+        select_columns.push((e, columns));
+    }
+    let mut having_columns = Vec::new();
+    if let Some((having, _)) = &select.having {
+        walk(typer, having, None, &mut aggregates, &mut having_columns);
+    }
+
+    if !typer.options.only_full_group_by || (aggregates.is_empty() && select.group_by.is_none()) {
+        return;
+    }
+
+    let group_by: &[Expression<'a>] = match &select.group_by {
+        Some((_, group_by)) => group_by,
+        None => &[],
+    };
+    let grouped_segments: Vec<&str> = group_by
+        .iter()
+        .map(|g| typer.issues.segment(g.span()))
+        .collect();
+
+    for (e, columns) in select_columns {
+        // The whole expression or its alias may be grouped by
+        let segment = typer.issues.segment(e.expr.span());
+        if grouped_segments.contains(&segment) {
+            continue;
+        }
+        if let Some(as_) = &e.as_ {
+            if group_by.iter().any(|g| {
+                matches!(g, Expression::Identifier(parts)
+                    if matches!(parts.as_slice(), [IdentifierPart::Name(n)] if n == as_))
+            }) {
+                continue;
+            }
+        }
+        check_grouped(typer, select, group_by, &columns);
+    }
+
+    // Aliases of the select list may be used in HAVING
+    let having_columns: Vec<_> = having_columns
+        .into_iter()
+        .filter(|c| {
+            !matches!(c, Expression::Identifier(parts)
+                if matches!(parts.as_slice(), [IdentifierPart::Name(n)]
+                    if select.select_exprs.iter().any(|e| e.as_.as_ref() == Some(n))))
+        })
+        .collect();
+    check_grouped(typer, select, group_by, &having_columns);
+}
+
+/// Report the column references that are not grouped by
+fn check_grouped<'a>(
+    typer: &mut Typer<'a, '_>,
+    select: &Select<'a>,
+    group_by: &[Expression<'a>],
+    columns: &[&Expression<'a>],
+) {
+    for c in columns {
+        let Expression::Identifier(parts) = c else {
+            continue;
+        };
+        let grouped = group_by.iter().any(|g| match g {
+            Expression::Identifier(g) => same_column(parts, g),
+            _ => false,
+        });
+        if !grouped {
+            let span: Span = c.span();
+            let mut issue = typer.err(
+                "Column must be grouped by or used in an aggregate function",
+                &span,
+            );
+            if let Some((span, _)) = &select.group_by {
+                issue.frag("Grouped here", span);
+            }
+        }
+    }
+}
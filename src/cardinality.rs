@@ -0,0 +1,131 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use sql_parse::{BinaryOperator, Expression, IdentifierPart, Select, Statement, TableReference};
+
+use crate::{
+    aggregate::contains_aggregate,
+    plan::{collect_identifiers, split_and},
+    schema::{find_table, Schema, Schemas},
+    TypeOptions,
+};
+
+/// Does the identifier refer to a column of the table named name
+fn is_local(
+    schema: &Schema<'_>,
+    name: &str,
+    parts: &[IdentifierPart<'_>],
+    options: &TypeOptions,
+) -> bool {
+    match parts {
+        [IdentifierPart::Name(t), IdentifierPart::Name(_)] => options.same_name(t.value, name),
+        [IdentifierPart::Name(c)] => schema.find_column(c.value, options).is_some(),
+        _ => false,
+    }
+}
+
+/// Find the column of the table named name compared to a value not
+/// depending on the table
+fn bound_column<'a>(
+    schema: &Schema<'a>,
+    name: &str,
+    column: &Expression<'a>,
+    value: &Expression<'a>,
+    options: &TypeOptions,
+) -> Option<&'a str> {
+    let Expression::Identifier(parts) = column else {
+        return None;
+    };
+    if !is_local(schema, name, parts, options) {
+        return None;
+    }
+    let mut identifiers = Vec::new();
+    if !collect_identifiers(value, &mut identifiers)
+        || identifiers
+            .iter()
+            .any(|p| is_local(schema, name, p, options))
+    {
+        return None;
+    }
+    match parts.last() {
+        Some(IdentifierPart::Name(c)) => Some(c.value),
+        _ => None,
+    }
+}
+
+/// Does the WHERE clause fix every column of a unique key of the only table
+fn unique_key_bound(schemas: &Schemas<'_>, select: &Select<'_>, options: &TypeOptions) -> bool {
+    let Some(
+        [TableReference::Table {
+            identifier, as_, ..
+        }],
+    ) = select.table_references.as_deref()
+    else {
+        return false;
+    };
+    if !identifier.prefix.is_empty() {
+        return false;
+    }
+    let table = identifier.identifier.value;
+    let Some((_, schema)) = find_table(&schemas.schemas, table, options) else {
+        return false;
+    };
+    let name = as_.as_ref().map_or(table, |v| v.value);
+    let Some((where_, _)) = &select.where_ else {
+        return false;
+    };
+    let mut conjuncts = Vec::new();
+    split_and(where_, &mut conjuncts);
+    let mut bound = Vec::new();
+    for c in conjuncts {
+        if let Expression::Binary {
+            op: BinaryOperator::Eq | BinaryOperator::NullSafeEq,
+            lhs,
+            rhs,
+            ..
+        } = c
+        {
+            bound.extend(bound_column(schema, name, lhs, rhs, options));
+            bound.extend(bound_column(schema, name, rhs, lhs, options));
+        }
+    }
+    schema.unique_keys.iter().any(|key| {
+        key.iter()
+            .all(|c| bound.iter().any(|b| options.same_name(b, c.value)))
+    })
+}
+
+/// Can the statement be shown to yield at most one row
+pub(crate) fn at_most_one_row(
+    schemas: &Schemas<'_>,
+    statement: &Statement<'_>,
+    options: &TypeOptions,
+) -> bool {
+    let Statement::Select(select) = statement else {
+        return false;
+    };
+    if let Some((_, _, Expression::Integer((count, _)))) = &select.limit {
+        if *count <= 1 {
+            return true;
+        }
+    }
+    if select.group_by.is_none()
+        && select
+            .select_exprs
+            .iter()
+            .any(|e| contains_aggregate(&e.expr))
+    {
+        return true;
+    }
+    select.table_references.is_none() || unique_key_bound(schemas, select, options)
+}
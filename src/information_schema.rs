@@ -0,0 +1,139 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Schemas from the information_schema of a running server
+//!
+//! Instead of maintaining a schema file next to the database, the columns of
+//! the tables can be read from the server with any client, using
+//!
+//! ```sql
+//! SELECT `TABLE_NAME`, `COLUMN_NAME`, `COLUMN_TYPE`, `IS_NULLABLE`, `COLUMN_DEFAULT`,
+//!     `EXTRA`, `GENERATION_EXPRESSION`, `COLUMN_COMMENT`
+//! FROM `information_schema`.`COLUMNS`
+//! WHERE `TABLE_SCHEMA` = DATABASE()
+//! ORDER BY `TABLE_NAME`, `ORDINAL_POSITION`
+//! ```
+//!
+//! and rendered as CREATE TABLE statements to be given to [crate::schema::parse_schemas].
+//!
+//! ```
+//! use sql_type::{
+//!     information_schema::{create_tables, InformationSchemaColumn},
+//!     schema::parse_schemas, type_statement, Issues, SQLDialect, StatementType, TypeOptions,
+//! };
+//! let column = |column_name, column_type, is_nullable, extra| InformationSchemaColumn {
+//!     table_name: "users",
+//!     column_name,
+//!     column_type,
+//!     is_nullable,
+//!     column_default: None,
+//!     extra,
+//!     generation_expression: None,
+//!     column_comment: "",
+//! };
+//! let rows = [
+//!     column("id", "int(10) unsigned", false, "auto_increment"),
+//!     column("name", "varchar(100)", true, ""),
+//! ];
+//! let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+//! let schema_src = create_tables(&SQLDialect::MariaDB, &rows);
+//! let mut issues = Issues::new(&schema_src);
+//! let schemas = parse_schemas(&schema_src, &mut issues, &options);
+//! assert!(issues.is_ok());
+//!
+//! let src = "SELECT `id`, `name` FROM `users`";
+//! let mut issues = Issues::new(src);
+//! let StatementType::Select { columns, .. } = type_statement(&schemas, src, &mut issues, &options)
+//! else {
+//!     panic!("Expected select")
+//! };
+//! assert_eq!(columns[0].type_.to_string(), "u32 not null");
+//! assert_eq!(columns[1].type_.to_string(), "string");
+//! ```
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use sql_parse::SQLDialect;
+
+use crate::quote::{quote_identifier, quote_string};
+
+/// A row of information_schema.COLUMNS
+#[derive(Clone, Debug)]
+pub struct InformationSchemaColumn<'a> {
+    pub table_name: &'a str,
+    pub column_name: &'a str,
+    /// The full type of the column, for instance `int(10) unsigned`
+    pub column_type: &'a str,
+    /// True if IS_NULLABLE is YES
+    pub is_nullable: bool,
+    pub column_default: Option<&'a str>,
+    /// For instance `auto_increment` or `VIRTUAL GENERATED`
+    pub extra: &'a str,
+    pub generation_expression: Option<&'a str>,
+    pub column_comment: &'a str,
+}
+
+/// Render the columns as one CREATE TABLE statement per table. Columns are
+/// given in the order of the rows, and tables in the order of their names
+pub fn create_tables(dialect: &SQLDialect, columns: &[InformationSchemaColumn<'_>]) -> String {
+    let mut tables: BTreeMap<&str, Vec<&InformationSchemaColumn<'_>>> = BTreeMap::new();
+    for c in columns {
+        tables.entry(c.table_name).or_default().push(c);
+    }
+    let mut ans = String::new();
+    for (table, columns) in tables {
+        ans.push_str("CREATE TABLE ");
+        ans.push_str(&quote_identifier(dialect, table));
+        ans.push_str(" (");
+        for (i, c) in columns.into_iter().enumerate() {
+            if i != 0 {
+                ans.push(',');
+            }
+            ans.push_str("\n    ");
+            ans.push_str(&quote_identifier(dialect, c.column_name));
+            ans.push(' ');
+            ans.push_str(c.column_type);
+            let extra = c.extra.to_ascii_lowercase();
+            if let Some(e) = c.generation_expression.filter(|e| !e.is_empty()) {
+                ans.push_str(" GENERATED ALWAYS AS (");
+                ans.push_str(e);
+                ans.push(')');
+                ans.push_str(
+                    if extra.contains("stored") || extra.contains("persistent") {
+                        " STORED"
+                    } else {
+                        " VIRTUAL"
+                    },
+                );
+            } else {
+                if !c.is_nullable {
+                    ans.push_str(" NOT NULL");
+                }
+                // MariaDB gives the default as an expression, with NULL for no default,
+                // while MySQL gives the value. Quoted values are converted to the type
+                // of the column either way
+                if let Some(d) = c.column_default.filter(|d| *d != "NULL") {
+                    ans.push_str(" DEFAULT ");
+                    ans.push_str(&quote_string(dialect, d));
+                }
+            }
+            if extra.contains("auto_increment") {
+                ans.push_str(" AUTO_INCREMENT");
+            }
+            if !c.column_comment.is_empty() {
+                ans.push_str(" COMMENT ");
+                ans.push_str(&quote_string(dialect, c.column_comment));
+            }
+        }
+        ans.push_str("\n);\n");
+    }
+    ans
+}
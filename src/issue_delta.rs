@@ -0,0 +1,97 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Difference between the issues of two typing runs
+//!
+//! Issues are matched by level, message and the sql segment they point to
+//! with whitespace normalized, so issues are still matched when the
+//! statement has been edited elsewhere and spans have moved.
+//!
+//! ```
+//! use sql_type::{issue_delta::diff_issues, schema::parse_schemas, type_statement, Issues, SQLDialect, TypeOptions};
+//! let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+//! let schema_src = "CREATE TABLE `t` (`a` int NOT NULL);";
+//! let mut issues = Issues::new(schema_src);
+//! let schemas = parse_schemas(schema_src, &mut issues, &options);
+//!
+//! let before_src = "SELECT `b` FROM `t`";
+//! let mut before = Issues::new(before_src);
+//! type_statement(&schemas, before_src, &mut before, &options);
+//!
+//! let after_src = "SELECT `a`,\n    `b`, `c` FROM `t`";
+//! let mut after = Issues::new(after_src);
+//! type_statement(&schemas, after_src, &mut after, &options);
+//!
+//! let delta = diff_issues(before.get(), after.get());
+//! assert_eq!(delta.unchanged.len(), 1);
+//! assert_eq!(delta.added.len(), 1);
+//! assert_eq!(delta.added[0].sql_segment, "`c`");
+//! assert!(delta.removed.is_empty());
+//! ```
+
+use alloc::vec::Vec;
+use sql_parse::Issue;
+
+/// Issues added, removed and unchanged between a run before and a run after
+/// a change
+#[derive(Debug, Clone)]
+pub struct IssueDelta<'b, 'a> {
+    /// Issues of the run after the change that were not in the run before
+    pub added: Vec<&'a Issue<'a>>,
+    /// Issues of the run before the change that are no longer there
+    pub removed: Vec<&'b Issue<'b>>,
+    /// Issues of the run after the change that were also in the run before
+    pub unchanged: Vec<&'a Issue<'a>>,
+}
+
+/// Are the two segments equal when ignoring differences in whitespace
+fn same_segment(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+fn same_issue(a: &Issue<'_>, b: &Issue<'_>) -> bool {
+    a.level == b.level && a.message == b.message && same_segment(a.sql_segment, b.sql_segment)
+}
+
+/// Compute the issues added, removed and unchanged going from before to after
+///
+/// Identical issues are matched pairwise in order, so an issue reported
+/// twice before and three times after counts as one added issue
+pub fn diff_issues<'b, 'a>(before: &'b [Issue<'b>], after: &'a [Issue<'a>]) -> IssueDelta<'b, 'a> {
+    let mut matched = alloc::vec![false; before.len()];
+    let mut added = Vec::new();
+    let mut unchanged = Vec::new();
+    for issue in after {
+        let found = before
+            .iter()
+            .zip(matched.iter_mut())
+            .find(|(b, m)| !**m && same_issue(b, issue));
+        match found {
+            Some((_, m)) => {
+                *m = true;
+                unchanged.push(issue);
+            }
+            None => added.push(issue),
+        }
+    }
+    let removed = before
+        .iter()
+        .zip(matched)
+        .filter(|(_, m)| !m)
+        .map(|(b, _)| b)
+        .collect();
+    IssueDelta {
+        added,
+        removed,
+        unchanged,
+    }
+}
@@ -0,0 +1,87 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Classification of issues by severity
+//!
+//! Issues do not carry a separate code, the message identifies the kind of
+//! issue, so counts are given per level and message.
+//!
+//! ```
+//! use sql_type::{issue_summary::IssueSeverity, schema::parse_schemas, type_statement, Issues, Level, SQLDialect, TypeOptions};
+//! let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+//! let schema_src = "CREATE TABLE `t` (`a` int NOT NULL);";
+//! let mut issues = Issues::new(schema_src);
+//! let schemas = parse_schemas(schema_src, &mut issues, &options);
+//!
+//! let src = "SELECT `b`, `c` FROM `t`";
+//! let mut issues = Issues::new(src);
+//! type_statement(&schemas, src, &mut issues, &options);
+//! assert!(issues.has_errors());
+//! assert_eq!(issues.errors().count(), 2);
+//! assert_eq!(issues.warnings().count(), 0);
+//! assert_eq!(issues.counts()[&(Level::Error, "Unknown identifier")], 2);
+//! ```
+
+use alloc::collections::BTreeMap;
+use sql_parse::{Issue, Issues, Level};
+
+/// Severity based access to a list of issues
+pub trait IssueSeverity {
+    /// All issues in the order they were reported
+    fn all(&self) -> &[Issue<'_>];
+
+    /// The issues of the given level in the order they were reported
+    fn with_level(&self, level: Level) -> impl Iterator<Item = &Issue<'_>> {
+        self.all().iter().filter(move |i| i.level == level)
+    }
+
+    /// The errors in the order they were reported
+    fn errors(&self) -> impl Iterator<Item = &Issue<'_>> {
+        self.with_level(Level::Error)
+    }
+
+    /// The warnings in the order they were reported
+    fn warnings(&self) -> impl Iterator<Item = &Issue<'_>> {
+        self.with_level(Level::Warning)
+    }
+
+    /// Is there at least one error
+    fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Is there at least one warning
+    fn has_warnings(&self) -> bool {
+        self.warnings().next().is_some()
+    }
+
+    /// Number of issues by level and message
+    fn counts(&self) -> BTreeMap<(Level, &str), usize> {
+        let mut ans = BTreeMap::new();
+        for issue in self.all() {
+            *ans.entry((issue.level, &*issue.message)).or_default() += 1;
+        }
+        ans
+    }
+}
+
+impl IssueSeverity for [Issue<'_>] {
+    fn all(&self) -> &[Issue<'_>] {
+        self
+    }
+}
+
+impl IssueSeverity for Issues<'_> {
+    fn all(&self) -> &[Issue<'_>] {
+        self.get()
+    }
+}
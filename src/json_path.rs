@@ -0,0 +1,131 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::iter::Peekable;
+use core::str::Chars;
+
+fn skip_ws(chars: &mut Peekable<Chars<'_>>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn number(chars: &mut Peekable<Chars<'_>>) -> bool {
+    let mut any = false;
+    while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        chars.next();
+        any = true;
+    }
+    any
+}
+
+fn keyword(chars: &mut Peekable<Chars<'_>>, word: &str) -> bool {
+    let mut c2 = chars.clone();
+    for w in word.chars() {
+        if c2.next() != Some(w) {
+            return false;
+        }
+    }
+    *chars = c2;
+    true
+}
+
+/// Parse an array index: a number, `last` or `last - number`
+fn array_index(chars: &mut Peekable<Chars<'_>>) -> Result<(), &'static str> {
+    if keyword(chars, "last") {
+        skip_ws(chars);
+        if chars.peek() == Some(&'-') {
+            chars.next();
+            skip_ws(chars);
+            if !number(chars) {
+                return Err("Expected number after 'last -' in JSON path");
+            }
+        }
+        Ok(())
+    } else if number(chars) {
+        Ok(())
+    } else {
+        Err("Expected array index in JSON path")
+    }
+}
+
+/// Validate a MySQL/MariaDB JSON path such as `$.a[0].b`
+pub(crate) fn validate_json_path(path: &str) -> Result<(), &'static str> {
+    let mut chars = path.chars().peekable();
+    skip_ws(&mut chars);
+    if chars.next() != Some('$') {
+        return Err("JSON path must start with '$'");
+    }
+    let mut after_wildcard = false;
+    loop {
+        skip_ws(&mut chars);
+        match chars.next() {
+            None => {
+                if after_wildcard {
+                    return Err("JSON path cannot end with '**'");
+                }
+                return Ok(());
+            }
+            Some('.') => {
+                skip_ws(&mut chars);
+                match chars.next() {
+                    Some('*') => (),
+                    Some('"') => loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => {
+                                chars.next();
+                            }
+                            Some(_) => (),
+                            None => return Err("Unterminated key in JSON path"),
+                        }
+                    },
+                    Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {
+                        while chars
+                            .peek()
+                            .is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+                        {
+                            chars.next();
+                        }
+                    }
+                    _ => return Err("Expected key after '.' in JSON path"),
+                }
+                after_wildcard = false;
+            }
+            Some('[') => {
+                skip_ws(&mut chars);
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                } else {
+                    array_index(&mut chars)?;
+                    skip_ws(&mut chars);
+                    if keyword(&mut chars, "to") {
+                        skip_ws(&mut chars);
+                        array_index(&mut chars)?;
+                    }
+                }
+                skip_ws(&mut chars);
+                if chars.next() != Some(']') {
+                    return Err("Expected ']' in JSON path");
+                }
+                after_wildcard = false;
+            }
+            Some('*') => {
+                if chars.next() != Some('*') || after_wildcard {
+                    return Err("Unexpected '*' in JSON path");
+                }
+                after_wildcard = true;
+            }
+            Some(_) => return Err("Unexpected character in JSON path"),
+        }
+    }
+}
@@ -49,11 +49,19 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{borrow::Cow, collections::BTreeMap, sync::Arc, vec::Vec};
 use schema::Schemas;
-use sql_parse::{parse_statement, ParseOptions};
+use sql_parse::{parse_statement, parse_statements, ParseOptions, Span};
 pub use sql_parse::{Fragment, Issue, Issues, Level};
 
+mod aggregate;
+mod cardinality;
+mod json_path;
+mod optimizer_hints;
+mod plan;
+#[cfg(feature = "serde")]
+mod serialize;
+mod shard;
 mod type_;
 mod type_binary_expression;
 mod type_delete;
@@ -62,12 +70,24 @@ mod type_function;
 mod type_insert_replace;
 mod type_reference;
 mod type_select;
+mod type_set;
 mod type_statement;
 mod type_update;
 mod typer;
 
+pub mod information_schema;
+pub mod issue_delta;
+pub mod issue_summary;
+pub mod quote;
 pub mod schema;
-pub use type_::{BaseType, FullType, Type};
+pub mod schema_builder;
+pub mod schema_diff;
+pub mod scope;
+pub mod testing;
+pub use plan::{JoinKind, PlanNode};
+pub use scope::{ExpressionType, TypeScope};
+pub use shard::{ShardAccess, ShardKeyBinding};
+pub use type_::{BaseType, CustomType, CustomTypeRef, FullType, SizeClass, Type};
 pub use type_insert_replace::AutoIncrementId;
 pub use type_select::SelectTypeColumn;
 use typer::Typer;
@@ -80,6 +100,19 @@ pub struct TypeOptions {
     parse_options: ParseOptions,
     warn_unnamed_column_in_select: bool,
     warn_duplicate_column_in_select: bool,
+    validate_optimizer_hints: bool,
+    max_arguments: Option<usize>,
+    warn_union_name_mismatch: bool,
+    only_full_group_by: bool,
+    warn_multi_row_subquery: bool,
+    warn_natural_join: bool,
+    argument_types: Vec<(usize, FullType<'static>)>,
+    custom_types: Vec<CustomTypeRef>,
+    database: Option<alloc::string::String>,
+    ignore_identifier_case: bool,
+    warn_string_length: bool,
+    tinyint_as_integer: bool,
+    warn_uuid_string_comparison: bool,
 }
 
 impl TypeOptions {
@@ -140,6 +173,139 @@ impl TypeOptions {
         }
     }
 
+    /// Should we warn about columns named differently in the selects of a union,
+    /// the names from the first select are always used
+    pub fn warn_union_name_mismatch(self, warn_union_name_mismatch: bool) -> Self {
+        Self {
+            warn_union_name_mismatch,
+            ..self
+        }
+    }
+
+    /// Require columns selected outside aggregate functions to be grouped by,
+    /// like the ONLY_FULL_GROUP_BY sql mode
+    pub fn only_full_group_by(self, only_full_group_by: bool) -> Self {
+        Self {
+            only_full_group_by,
+            ..self
+        }
+    }
+
+    /// Should we warn about scalar subqueries that may return more than one
+    /// row, that is subqueries without aggregation, LIMIT 1 or an equality on
+    /// every column of a unique key
+    pub fn warn_multi_row_subquery(self, warn_multi_row_subquery: bool) -> Self {
+        Self {
+            warn_multi_row_subquery,
+            ..self
+        }
+    }
+
+    /// Should we warn about NATURAL JOIN, whose join condition depends on
+    /// the column names of both sides
+    pub fn warn_natural_join(self, warn_natural_join: bool) -> Self {
+        Self {
+            warn_natural_join,
+            ..self
+        }
+    }
+
+    /// Register a custom type, columns declared with a type of its name
+    /// are given the custom type
+    pub fn custom_type(mut self, custom_type: Arc<dyn CustomType>) -> Self {
+        self.custom_types.push(CustomTypeRef(custom_type));
+        self
+    }
+
+    /// The name of the database described by the tables of the schema not
+    /// created in a named database. Tables qualified with this name are
+    /// looked up as if they were not qualified
+    pub fn database(self, database: &str) -> Self {
+        Self {
+            database: Some(database.into()),
+            ..self
+        }
+    }
+
+    /// Match the names of tables and columns without regard to case, like
+    /// MySQL and MariaDB do for columns and, with lower_case_table_names
+    /// set, for tables
+    pub fn ignore_identifier_case(self, ignore_identifier_case: bool) -> Self {
+        Self {
+            ignore_identifier_case,
+            ..self
+        }
+    }
+
+    /// Should we warn about string literals assigned to or compared with
+    /// string columns that are longer than the declared length of the column
+    pub fn warn_string_length(self, warn_string_length: bool) -> Self {
+        Self {
+            warn_string_length,
+            ..self
+        }
+    }
+
+    /// Should TINYINT(1) and BIT(1) columns be typed as booleans, as many
+    /// ORMs expect, rather than as integers. This is the default
+    pub fn tinyint_as_bool(self, tinyint_as_bool: bool) -> Self {
+        Self {
+            tinyint_as_integer: !tinyint_as_bool,
+            ..self
+        }
+    }
+
+    /// Should we warn about comparing uuid columns with string values other
+    /// than literals, which compare as text and may differ in case or format
+    pub fn warn_uuid_string_comparison(self, warn_uuid_string_comparison: bool) -> Self {
+        Self {
+            warn_uuid_string_comparison,
+            ..self
+        }
+    }
+
+    /// Do the names a and b refer to the same table or column
+    pub(crate) fn same_name(&self, a: &str, b: &str) -> bool {
+        if self.ignore_identifier_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+
+    /// Is name the database given with [TypeOptions::database]
+    pub(crate) fn is_default_database(&self, name: &str) -> bool {
+        self.database.as_deref() == Some(name)
+    }
+
+    /// Should we warn about unknown tables and indices named in optimizer hints
+    pub fn validate_optimizer_hints(self, validate_optimizer_hints: bool) -> Self {
+        Self {
+            validate_optimizer_hints,
+            ..self
+        }
+    }
+
+    /// The maximal number of arguments supported by the protocol used to
+    /// execute statements, for instance 65535 for MySQL. Statements using more
+    /// arguments are errors and large lists of arguments in IN are warned about
+    pub fn max_arguments(self, max_arguments: usize) -> Self {
+        Self {
+            max_arguments: Some(max_arguments),
+            ..self
+        }
+    }
+
+    /// Declare the type of the argument with the given index up front, for
+    /// arguments whose type is known by the caller. The argument is reported
+    /// with this type, and uses of it with another base type are errors.
+    /// Statements that do not use the argument do not report it
+    pub fn argument_type(mut self, index: usize, type_: FullType<'static>) -> Self {
+        self.argument_types.retain(|(i, _)| *i != index);
+        self.argument_types.push((index, type_));
+        self
+    }
+
     /// Parse _LIST_ as special expression and type as a list of items
     pub fn list_hack(self, list_hack: bool) -> Self {
         Self {
@@ -179,6 +345,8 @@ pub enum StatementType<'a> {
     Insert {
         /// The insert happend in a table with a auto increment id row
         yield_autoincrement: AutoIncrementId,
+        /// The type of the auto increment column of the table, if it has one
+        auto_increment_type: Option<Type<'a>>,
         /// The key and type of arguments to the query
         arguments: Vec<(ArgumentKey<'a>, FullType<'a>)>,
         /// If present, the types and names of the columns returned from the insert
@@ -191,15 +359,74 @@ pub enum StatementType<'a> {
     },
     /// The statement is a replace statement
     Replace {
+        /// The replace happend in a table with a auto increment id row
+        yield_autoincrement: AutoIncrementId,
+        /// The type of the auto increment column of the table, if it has one
+        auto_increment_type: Option<Type<'a>>,
         /// The key and type of arguments to the query
         arguments: Vec<(ArgumentKey<'a>, FullType<'a>)>,
         /// If present, the types and names of the columns returned from the replace
         returning: Option<Vec<SelectTypeColumn<'a>>>,
     },
+    /// The statement is a set statement assigning system variables
+    Set {
+        /// The key and type of arguments to the query
+        arguments: Vec<(ArgumentKey<'a>, FullType<'a>)>,
+    },
+    /// The statement is a truncate table statement
+    Truncate,
+    /// The statement creates or drops a temporary table in a script
+    TemporaryTable,
+    /// The statement begins or ends a transaction, it returns no rows and
+    /// takes no arguments
+    Transaction,
     /// The query was not valid, errors are preset in issues
     Invalid,
 }
 
+/// Structured description of a type mismatch reported in issues
+#[derive(Debug, Clone)]
+pub struct TypeMismatch<'a> {
+    /// Span of the reported error
+    pub span: Span,
+    /// The type that was given
+    pub got: Type<'a>,
+    /// The types that would have been accepted
+    pub expected: Vec<Type<'a>>,
+}
+
+/// Additional information collected while typing a statement
+#[derive(Debug, Clone, Default)]
+pub struct StatementInfo<'a> {
+    /// Type mismatches in the order they were reported
+    pub type_mismatches: Vec<TypeMismatch<'a>>,
+    /// Suggested names for arguments, derived from the columns they
+    /// are compared with or assigned to. Names are unique, the column name
+    /// gets a suffix like `_2` when several arguments share a column
+    pub argument_names: Vec<(ArgumentKey<'a>, Cow<'a, str>)>,
+    /// Names of the tables and views in the schemas used by the statement
+    pub tables: Vec<&'a str>,
+    /// Database and name of the tables in other databases used by the statement
+    pub database_tables: Vec<(&'a str, &'a str)>,
+    /// Hash of the definitions of the used tables and views and their indices.
+    /// The statement only needs to be typed again if this changes
+    pub schema_hash: u64,
+    /// Logical plan of the statement if it is a select
+    pub plan: Option<PlanNode<'a>>,
+    /// Accesses to tables with a shard key, given by `sql-type: shard-key`
+    /// column comments
+    pub shard_access: Vec<ShardAccess<'a>>,
+    /// True if the statement is an INSERT, UPDATE or DELETE with IGNORE, so
+    /// errors like duplicate keys only give warnings and rows may be skipped
+    pub ignore: bool,
+    /// True if the statement is a select shown to return at most one row, for
+    /// instance because its WHERE clause fixes every column of a unique key
+    pub at_most_one_row: bool,
+    /// True if the statement uses more arguments than allowed by
+    /// [TypeOptions::max_arguments]
+    pub too_many_arguments: bool,
+}
+
 /// Type an sql statement with respect to a given schema
 pub fn type_statement<'a>(
     schemas: &'a Schemas<'a>,
@@ -207,43 +434,239 @@ pub fn type_statement<'a>(
     issues: &mut Issues<'a>,
     options: &TypeOptions,
 ) -> StatementType<'a> {
-    if let Some(stmt) = parse_statement(statement, issues, &options.parse_options) {
-        let mut typer = Typer {
+    type_statement_with_info(schemas, statement, issues, options).0
+}
+
+/// Type an sql statement with respect to a given schema, also
+/// returning additional information collected while typing
+pub fn type_statement_with_info<'a>(
+    schemas: &'a Schemas<'a>,
+    statement: &'a str,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) -> (StatementType<'a>, StatementInfo<'a>) {
+    let mut info = StatementInfo::default();
+    let t = if let Some(stmt) = parse_statement(statement, issues, &options.parse_options) {
+        type_parsed_statement(
             schemas,
+            &BTreeMap::new(),
+            statement,
+            &stmt,
             issues,
-            reference_types: Vec::new(),
-            arg_types: Default::default(),
             options,
-            with_schemas: Default::default(),
-        };
-        let t = type_statement::type_statement(&mut typer, &stmt);
-        let arguments = typer.arg_types;
-        match t {
-            type_statement::InnerStatementType::Select(s) => StatementType::Select {
-                columns: s.columns,
-                arguments,
-            },
-            type_statement::InnerStatementType::Delete { returning } => StatementType::Delete {
-                arguments,
-                returning: returning.map(|r| r.columns),
-            },
-            type_statement::InnerStatementType::Insert {
-                auto_increment_id,
-                returning,
-            } => StatementType::Insert {
-                yield_autoincrement: auto_increment_id,
-                arguments,
-                returning: returning.map(|r| r.columns),
-            },
-            type_statement::InnerStatementType::Update => StatementType::Update { arguments },
-            type_statement::InnerStatementType::Replace { returning } => StatementType::Replace {
-                arguments,
-                returning: returning.map(|r| r.columns),
-            },
-            type_statement::InnerStatementType::Invalid => StatementType::Invalid,
-        }
+            &mut info,
+        )
     } else {
         StatementType::Invalid
+    };
+    finish_info(schemas, &mut info);
+    (t, info)
+}
+
+/// Type the statements of a script with respect to a given schema, also
+/// returning additional information collected while typing each statement
+///
+/// Tables created by CREATE TEMPORARY TABLE are visible to the following
+/// statements of the script, until dropped by DROP TEMPORARY TABLE.
+///
+/// ```
+/// use sql_type::{schema::parse_schemas, type_statements, Issues, SQLArguments, SQLDialect, StatementType, TypeOptions};
+/// let options = TypeOptions::new()
+///     .dialect(SQLDialect::MariaDB)
+///     .arguments(SQLArguments::QuestionMark);
+/// let schemas = parse_schemas("", &mut Issues::new(""), &options);
+///
+/// let src = "CREATE TEMPORARY TABLE `ids` (`id` int NOT NULL);
+///     INSERT INTO `ids` (`id`) VALUES (?);
+///     SELECT `id` FROM `ids`;";
+/// let mut issues = Issues::new(src);
+/// let statements = type_statements(&schemas, src, &mut issues, &options);
+/// assert!(issues.is_ok());
+/// assert!(matches!(statements[2].0, StatementType::Select { .. }));
+/// ```
+pub fn type_statements<'a>(
+    schemas: &'a Schemas<'a>,
+    src: &'a str,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) -> Vec<(StatementType<'a>, StatementInfo<'a>)> {
+    let mut temporary: BTreeMap<&'a str, schema::Schema<'a>> = BTreeMap::new();
+    let mut ans = Vec::new();
+    for stmt in parse_statements(src, issues, &options.parse_options) {
+        let mut info = StatementInfo::default();
+        let t = match stmt {
+            sql_parse::Statement::CreateTable(t)
+                if t.create_options
+                    .iter()
+                    .any(|o| matches!(o, sql_parse::CreateOption::Temporary(_))) =>
+            {
+                let id = typer::unqualified_name(issues, &t.identifier).clone();
+                schema::check_reserved_table_name(issues, &id);
+                let schema =
+                    schema::table_schema(&id, t.create_definitions, &t.options, issues, options);
+                if let Some(old) = temporary.get(id.value) {
+                    if t.if_not_exists.is_none() {
+                        issues
+                            .err("Table already defined", &id)
+                            .frag("Defined here", &old.identifier_span);
+                    }
+                } else {
+                    temporary.insert(id.value, schema);
+                }
+                StatementType::TemporaryTable
+            }
+            sql_parse::Statement::DropTable(d) if d.temporary.is_some() => {
+                for table in &d.tables {
+                    let id = typer::unqualified_name(issues, table);
+                    if temporary.remove(id.value).is_none() && d.if_exists.is_none() {
+                        issues.err("Unknown temporary table", id);
+                    }
+                }
+                StatementType::TemporaryTable
+            }
+            stmt => {
+                type_parsed_statement(schemas, &temporary, src, &stmt, issues, options, &mut info)
+            }
+        };
+        finish_info(schemas, &mut info);
+        ans.push((t, info));
+    }
+    ans
+}
+
+/// Type a parsed statement of src, the temporary tables are found before the
+/// tables of the schemas
+fn type_parsed_statement<'a>(
+    schemas: &'a Schemas<'a>,
+    temporary: &BTreeMap<&'a str, schema::Schema<'a>>,
+    src: &'a str,
+    stmt: &sql_parse::Statement<'a>,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+    info: &mut StatementInfo<'a>,
+) -> StatementType<'a> {
+    let mut typer = Typer {
+        schemas,
+        issues,
+        reference_types: Vec::new(),
+        arg_types: Vec::new(),
+        options,
+        with_schemas: temporary.iter().map(|(k, v)| (*k, v)).collect(),
+        info,
+        grouped: false,
+    };
+    let t = type_statement::type_statement(&mut typer, stmt);
+    let arguments = typer.arg_types;
+    if matches!(
+        stmt,
+        sql_parse::Statement::Select(_) | sql_parse::Statement::Union(_)
+    ) {
+        info.plan = Some(plan::plan_statement(schemas, stmt, options));
+    }
+    info.ignore = match stmt {
+        sql_parse::Statement::InsertReplace(i) => i
+            .flags
+            .iter()
+            .any(|f| matches!(f, sql_parse::InsertReplaceFlag::Ignore(_))),
+        sql_parse::Statement::Update(u) => u
+            .flags
+            .iter()
+            .any(|f| matches!(f, sql_parse::UpdateFlag::Ignore(_))),
+        sql_parse::Statement::Delete(d) => d
+            .flags
+            .iter()
+            .any(|f| matches!(f, sql_parse::DeleteFlag::Ignore(_))),
+        _ => false,
+    };
+    info.at_most_one_row = cardinality::at_most_one_row(schemas, stmt, options);
+    shard::check_shard_keys(schemas, stmt, options, issues, &mut info.shard_access);
+    if options.validate_optimizer_hints {
+        optimizer_hints::check_optimizer_hints(schemas, stmt, src, options, issues);
+    }
+    match t {
+        type_statement::InnerStatementType::Select(s) => StatementType::Select {
+            columns: s.columns,
+            arguments,
+        },
+        type_statement::InnerStatementType::Delete { returning } => StatementType::Delete {
+            arguments,
+            returning: returning.map(|r| r.columns),
+        },
+        type_statement::InnerStatementType::Insert {
+            auto_increment_id,
+            auto_increment_type,
+            returning,
+        } => StatementType::Insert {
+            yield_autoincrement: auto_increment_id,
+            auto_increment_type,
+            arguments,
+            returning: returning.map(|r| r.columns),
+        },
+        type_statement::InnerStatementType::Update => StatementType::Update { arguments },
+        type_statement::InnerStatementType::Set => StatementType::Set { arguments },
+        type_statement::InnerStatementType::Transaction => StatementType::Transaction,
+        type_statement::InnerStatementType::Truncate => StatementType::Truncate,
+        type_statement::InnerStatementType::Replace {
+            auto_increment_id,
+            auto_increment_type,
+            returning,
+        } => StatementType::Replace {
+            yield_autoincrement: auto_increment_id,
+            auto_increment_type,
+            arguments,
+            returning: returning.map(|r| r.columns),
+        },
+        type_statement::InnerStatementType::Invalid => StatementType::Invalid,
+    }
+}
+
+/// Deduplicate the tables used by a statement and hash their definitions
+fn finish_info<'a>(schemas: &Schemas<'a>, info: &mut StatementInfo<'a>) {
+    info.tables.sort_unstable();
+    info.tables.dedup();
+    info.database_tables.sort_unstable();
+    info.database_tables.dedup();
+    info.schema_hash = schemas.hash_tables(&info.tables);
+}
+
+/// Type a standalone expression with respect to a given schema, as if it was
+/// selected from the given tables, each named by an optional alias
+///
+/// ```
+/// use sql_type::{schema::parse_schemas, type_expression_str, Issues, SQLDialect, SQLArguments, TypeOptions};
+/// let options = TypeOptions::new()
+///     .dialect(SQLDialect::MariaDB)
+///     .arguments(SQLArguments::QuestionMark);
+/// let schema_src = "CREATE TABLE `orders` (`price` int NOT NULL, `quantity` int NOT NULL);";
+/// let mut issues = Issues::new(schema_src);
+/// let schemas = parse_schemas(schema_src, &mut issues, &options);
+///
+/// let expression = "`price` * `quantity` > ?";
+/// let mut issues = Issues::new(expression);
+/// let t = type_expression_str(&schemas, &[("orders", None)], expression, &mut issues, &options);
+/// assert!(issues.is_ok());
+/// assert_eq!(t.type_.to_string(), "bool");
+/// assert_eq!(t.arguments.len(), 1);
+/// ```
+pub fn type_expression_str<'a>(
+    schemas: &'a Schemas<'a>,
+    tables: &[(&str, Option<&str>)],
+    expression: &'a str,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) -> ExpressionType<'a> {
+    match TypeScope::new(schemas, tables, options) {
+        Ok(scope) => scope.type_expression(expression, issues),
+        Err(table) => {
+            issues.err(
+                alloc::format!("Unknown table or view {}", table),
+                &(0..expression.len()),
+            );
+            ExpressionType {
+                type_: FullType::invalid(),
+                arguments: Vec::new(),
+            }
+        }
     }
 }
 
@@ -261,8 +684,9 @@ mod tests {
     use sql_parse::{Identifier, Issue, Issues, Level, SQLArguments, SQLDialect};
 
     use crate::{
-        schema::parse_schemas, type_statement, ArgumentKey, AutoIncrementId, BaseType, FullType,
-        SelectTypeColumn, StatementType, Type, TypeOptions,
+        schema::parse_schemas, type_statement, type_statement_with_info, ArgumentKey,
+        AutoIncrementId, BaseType, FullType, JoinKind, PlanNode, SelectTypeColumn, SizeClass,
+        StatementType, Type, TypeOptions,
     };
 
     struct N<'a>(Option<&'a str>);
@@ -315,42 +739,7 @@ mod tests {
     }
 
     fn str_to_type(t: &str) -> FullType<'static> {
-        let (t, not_null) = if let Some(t) = t.strip_suffix('!') {
-            (t, true)
-        } else {
-            (t, false)
-        };
-        let (t, list_hack) = if let Some(v) = t.strip_suffix("[]") {
-            (v, true)
-        } else {
-            (t, false)
-        };
-        let t = match t {
-            "b" => BaseType::Bool.into(),
-            "u8" => Type::U8,
-            "u16" => Type::U16,
-            "u32" => Type::U32,
-            "u64" => Type::U64,
-            "i8" => Type::I8,
-            "i16" => Type::I16,
-            "i32" => Type::I32,
-            "i64" => Type::I64,
-            "f32" => Type::F32,
-            "f64" => Type::F64,
-            "i" => BaseType::Integer.into(),
-            "f" => BaseType::Float.into(),
-            "str" => BaseType::String.into(),
-            "bytes" => BaseType::Bytes.into(),
-            "dt" => BaseType::DateTime.into(),
-            "json" => Type::JSON,
-            "any" => BaseType::Any.into(),
-            _ => panic!("Unknown type {}", t),
-        };
-        let mut t = FullType::new(t, not_null);
-        if list_hack {
-            t.list_hack = true;
-        }
-        t
+        crate::testing::parse_type(t).unwrap_or_else(|| panic!("Unknown type {}", t))
     }
 
     fn check_arguments(
@@ -496,13 +885,48 @@ mod tests {
         CREATE TABLE `t4` (
             `id` int(11) NOT NULL AUTO_INCREMENT,
             `dt` datetime NOT NULL);
+
+        CREATE FUNCTION `add_one`(`a` int) RETURNS int DETERMINISTIC RETURN `a` + 1;
+
+        CREATE TABLE `t5` (
+            `id` int(11) NOT NULL,
+            `email` text COMMENT 'sql-type: semantic=email, not-null-in-practice',
+            `note` text COMMENT 'Free text');
+
+        CREATE TABLE `t6` (
+            `tenant` int(11) NOT NULL COMMENT 'sql-type: shard-key',
+            `id` int(11) NOT NULL,
+            `v` int(11));
+
+        CREATE UNIQUE INDEX `t6_key` ON `t6` (`tenant`, `id`);
+
+        CREATE TABLE `t7` (
+            `id` int(11) NOT NULL PRIMARY KEY,
+            `created` datetime(6) NOT NULL,
+            `updated` datetime(3) NOT NULL,
+            `seen` timestamp(6));
+
+        CREATE TABLE `db2`.`events` (
+            `id` int(11) NOT NULL,
+            `t1_id` int(11) NOT NULL,
+            `kind` varchar(10));
+
+        CREATE TABLE `db2`.`t1` (
+            `id` int(11) NOT NULL,
+            `x` int(11) NOT NULL);
         ";
 
         let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
         let mut issues = Issues::new(schema_src);
-        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut schema = parse_schemas(schema_src, &mut issues, &options);
         let mut errors = 0;
         check_no_errors("schema", schema_src, issues.get(), &mut errors);
+        schema.add_function(
+            "udf_concat",
+            alloc::vec![Type::I32, BaseType::String.into()],
+            FullType::new(BaseType::String, true),
+            true,
+        );
 
         let options = TypeOptions::new()
             .dialect(SQLDialect::MariaDB)
@@ -577,6 +1001,7 @@ mod tests {
                 arguments,
                 yield_autoincrement,
                 returning,
+                ..
             } = q
             {
                 check_arguments(
@@ -624,6 +1049,7 @@ mod tests {
                 arguments,
                 yield_autoincrement,
                 returning,
+                ..
             } = q
             {
                 check_arguments(name, &arguments, "i32!,i32!", &mut errors);
@@ -651,6 +1077,7 @@ mod tests {
                 arguments,
                 yield_autoincrement,
                 returning,
+                ..
             } = q
             {
                 check_arguments(name, &arguments, "i32!", &mut errors);
@@ -707,6 +1134,7 @@ mod tests {
             if let StatementType::Replace {
                 arguments,
                 returning,
+                ..
             } = q
             {
                 check_arguments(name, &arguments, "i32!,i32!", &mut errors);
@@ -821,6 +1249,7 @@ mod tests {
                 arguments,
                 yield_autoincrement,
                 returning,
+                ..
             } = q
             {
                 check_arguments(
@@ -859,6 +1288,7 @@ mod tests {
             if let StatementType::Replace {
                 arguments,
                 returning,
+                ..
             } = q
             {
                 check_arguments(name, &arguments, "i32!,i32!", &mut errors);
@@ -1083,74 +1513,43 @@ mod tests {
             }
         }
 
-        if errors != 0 {
-            panic!("{} errors in test", errors);
+        {
+            let name = "q28";
+            let src = "SELECT ROW_NUMBER() OVER (ORDER BY `id`) AS `rn`, LAG(`cu8`, 1, 0) OVER (ORDER BY `id`) AS `prev`,
+                LEAD(`id`) OVER (ORDER BY `id`) AS `next` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "", &mut errors);
+                check_columns(name, &columns, "rn:u64!,prev:u8!,next:i32", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
         }
-    }
-
-    #[test]
-    fn postgresql() {
-        let schema_src = "
-        BEGIN;
-
-        DO $$ BEGIN
-            CREATE TYPE my_enum AS ENUM (
-            'V1',
-            'V2',
-            'V3'
-        );
-        EXCEPTION
-            WHEN duplicate_object THEN null;
-        END $$;
-
-        CREATE TABLE IF NOT EXISTS t1 (
-            id bigint NOT NULL PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
-            path text NOT NULL UNIQUE,
-            v my_enum NOT NULL,
-            time timestamptz NOT NULL DEFAULT now(),
-            old_id bigint,
-            CONSTRAINT t1__old
-            FOREIGN KEY(old_id) 
-            REFERENCES t1(id)
-            ON DELETE SET NULL
-        );
-
-        CREATE TABLE IF NOT EXISTS t2 (
-            id bigint NOT NULL PRIMARY KEY
-        );
-
-        DROP INDEX IF EXISTS t2_index;
-
-        CREATE INDEX t2_index2 ON t2 (id);
-
-        COMMIT;
-        ";
-
-        let options = TypeOptions::new().dialect(SQLDialect::PostgreSQL);
-        let mut issues = Issues::new(schema_src);
-        let schema = parse_schemas(schema_src, &mut issues, &options);
-        let mut errors = 0;
-        check_no_errors("schema", schema_src, issues.get(), &mut errors);
 
-        let options = TypeOptions::new()
-            .dialect(SQLDialect::PostgreSQL)
-            .arguments(SQLArguments::Dollar);
+        {
+            let name = "q29";
+            let src = "SELECT ROW_NUMBER() AS `rn` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
 
         {
-            let name = "q1";
-            let src =
-                "INSERT INTO t2 (id) SELECT id FROM t1 WHERE path=$1 ON CONFLICT (id) DO NOTHING RETURNING id";
-            let mut issues = Issues::new(src);
+            let name = "q30";
+            let src = "SELECT COALESCE(`ci16`, `ci32`, 0) AS `a`, COALESCE(`ci8`, NULL) AS `b`,
+                COALESCE(`cbytes`, ?) AS `c` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
             let q = type_statement(&schema, src, &mut issues, &options);
             check_no_errors(name, src, issues.get(), &mut errors);
-            if let StatementType::Insert {
-                arguments,
-                returning,
-                ..
-            } = q
-            {
-                check_arguments(name, &arguments, "str", &mut errors);
-                check_columns(name, &returning.expect("Returning"), "id:i64!", &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "bytes", &mut errors);
+                check_columns(name, &columns, "a:i!,b:i8,c:bytes", &mut errors);
             } else {
                 println!("{} should be select", name);
                 errors += 1;
@@ -1158,23 +1557,2787 @@ mod tests {
         }
 
         {
-            let name = "q2";
-            let src =
-                "WITH hat AS (DELETE FROM t1 WHERE old_id=42 RETURNING id) INSERT INTO t2 (id) SELECT id FROM hat";
-            let mut issues = Issues::new(src);
-            let q = type_statement(&schema, src, &mut issues, &options);
-            check_no_errors(name, src, issues.get(), &mut errors);
+            let name = "q31";
+            let src = "SELECT COALESCE(`ctext`, 42) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
 
-            if let StatementType::Insert { arguments, .. } = q {
-                check_arguments(name, &arguments, "", &mut errors);
-            } else {
-                println!("{} should be select {q:?}", name);
+        {
+            let name = "q32";
+            let src = "SELECT `id` FROM `t1` WHERE `ctext` = 42 AND LENGTH(`ci32`) > 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            let mismatches: Vec<_> = info
+                .type_mismatches
+                .iter()
+                .map(|m| (m.got.base(), m.expected.iter().map(|t| t.base()).collect()))
+                .collect();
+            let expected: Vec<(BaseType, Vec<BaseType>)> = vec![
+                (BaseType::Integer, vec![BaseType::String]),
+                (BaseType::Integer, vec![BaseType::Bytes, BaseType::String]),
+            ];
+            if mismatches != expected {
+                println!("{} got mismatches {:?}", name, mismatches);
                 errors += 1;
             }
         }
 
-        if errors != 0 {
-            panic!("{} errors in test", errors);
+        {
+            let name = "q33";
+            let src = "SELECT `id` FROM `t1` WHERE ? = `cu8` AND `t1`.`ctext` IN (?, ?, ?) LIMIT ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let expected = vec![
+                (ArgumentKey::Index(0), "cu8"),
+                (ArgumentKey::Index(1), "ctext"),
+                (ArgumentKey::Index(2), "ctext_2"),
+                (ArgumentKey::Index(3), "ctext_3"),
+                (ArgumentKey::Index(4), "limit"),
+            ];
+            let got: Vec<_> = info
+                .argument_names
+                .iter()
+                .map(|(k, v)| (k.clone(), &**v))
+                .collect();
+            if got != expected {
+                println!("{} got argument names {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q34";
+            let src = "INSERT INTO `t2` (`t1_id`) VALUES (?) ON DUPLICATE KEY UPDATE `id` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let expected = vec![
+                (ArgumentKey::Index(0), "t1_id"),
+                (ArgumentKey::Index(1), "id"),
+            ];
+            let got: Vec<_> = info
+                .argument_names
+                .iter()
+                .map(|(k, v)| (k.clone(), &**v))
+                .collect();
+            if got != expected {
+                println!("{} got argument names {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q35";
+            let src = "SELECT IFNULL(`ci32`, 0) AS `a`, IFNULL(`ci8`, `ci16`) AS `b`,
+                NULLIF(`cu8`, ?) AS `c`, IF(`ci32` > 0, `ctext`, 'x') AS `d` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "i", &mut errors);
+                check_columns(name, &columns, "a:i!,b:i,c:i,d:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q36";
+            let src = "SELECT NULLIF(`ctext`, 42) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q37";
+            let src = "SELECT DATE(`dt`) AS `a`, TIMESTAMPDIFF(SECOND, `dt`, NOW()) AS `b`,
+                STR_TO_DATE(?, '%Y') AS `c`, ADDDATE(CURDATE(), 3) AS `d`, DAYNAME(`dt`) AS `e`,
+                DATEDIFF(`dt`, '2020-01-01') AS `f`, YEAR(`dt`) AS `g`, CURTIME() AS `h`
+                FROM `t4`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "str", &mut errors);
+                check_columns(
+                    name,
+                    &columns,
+                    "a:date!,b:i64!,c:dt,d:date!,e:str!,f:i64!,g:i!,h:time!",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q38";
+            let src = "SELECT TIMESTAMPDIFF(FORTNIGHT, `dt`, NOW()) AS `a` FROM `t4`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q39";
+            let src = "CREATE TABLE `dual` (`id` int NOT NULL)";
+            let mut issues: Issues<'_> = Issues::new(src);
+            parse_schemas(src, &mut issues, &options);
+            if !issues.get().iter().any(|i| i.level == Level::Warning) {
+                println!("{} should warn", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q40";
+            let src =
+                "SELECT CONCAT(`ctext`, `ci32`) AS `a`, CONCAT_WS(',', `ctext`, `ci32`) AS `b`,
+                UPPER(`ctext`) AS `c`, LPAD(`ctext`, 5) AS `d`, LOCATE('a', `ctext`) AS `e`,
+                HEX(`cu8`) AS `f`, UNHEX(`ctext`) AS `g`, TRIM(`ctext`) AS `h` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(
+                    name,
+                    &columns,
+                    "a:str,b:str!,c:str!,d:str!,e:i!,f:str!,g:bytes,h:str!",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q41";
+            let src = "SELECT LOWER(`cf64`) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q42";
+            let src = "SELECT `t2`.`id` FROM `t2`
+                JOIN (SELECT `id` FROM `t3`) AS `c` ON `c`.`id` = `t2`.`t1_id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if info.tables != ["t2", "t3"] {
+                println!("{} got tables {:?}", name, info.tables);
+                errors += 1;
+            }
+            if info.schema_hash != schema.hash_tables(&["t2", "t3"])
+                || info.schema_hash == schema.hash_tables(&["t2"])
+            {
+                println!("{} got unexpected schema hash", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q43";
+            let src = "SELECT ABS(`ci16`) AS `a`, ROUND(`cf64`, 2) AS `b`, FLOOR(`cu32`) AS `c`,
+                SQRT(`cu8`) AS `d`, POW(`cu8`, 2) AS `e`, SIGN(`ci8`) AS `f`, TRUNCATE(?, 1) AS `g`
+                FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "f", &mut errors);
+                check_columns(
+                    name,
+                    &columns,
+                    "a:i16,b:f64,c:u32!,d:f64,e:f64!,f:i8,g:f",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q44";
+            let src = "SELECT ROUND(`ctext`) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q45";
+            let src = "SELECT JSON_VALUE(`ctext`, '$.a') AS `a`, JSON_OBJECT('id', `id`) AS `b`,
+                JSON_ARRAY(`ci8`, 1) AS `c`, JSON_SET(`ctext`, '$.a', `ci8`) AS `d`,
+                JSON_LENGTH(`ctext`) AS `e`, JSON_UNQUOTE(`ctext`) AS `f`,
+                JSON_EXTRACT(`ctext`, '$.a') AS `g` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(
+                    name,
+                    &columns,
+                    "a:json,b:json!,c:json!,d:json,e:i!,f:str!,g:json",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q46";
+            let src = "SELECT JSON_LENGTH(`cf64`) AS `a`, JSON_OBJECT('a') AS `b` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues
+                .get()
+                .iter()
+                .filter(|i| i.level == Level::Error)
+                .count()
+                != 2
+            {
+                println!("{} should fail twice", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q47";
+            let src = "SELECT JSON_EXTRACT(`ctext`, '$', '$.a', '$[0]', '$[*]', '$.*', '$**.b',
+                '$[last]', '$[last - 1]', '$[0 to 2]', '$.\"key with space\"') AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+        }
+
+        {
+            let name = "q48";
+            let src = "SELECT JSON_EXTRACT(`ctext`, 'a.b', '$.', '$[x]', '$**', '$.a[1') AS `a`
+                FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues
+                .get()
+                .iter()
+                .filter(|i| i.level == Level::Error)
+                .count()
+                != 5
+            {
+                println!("{} should fail five times", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q49";
+            let src = "SELECT /*+ INDEX(`x` `hat2`) NO_INDEX(t1 nope) */ `x`.`id` FROM `t1` AS `x`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if !issues.get().is_empty() {
+                println!("{} should not warn without hint validation", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q50";
+            let src =
+                "SELECT /*+ INDEX(`x` `hat2`, PRIMARY) NO_INDEX(`x` `nope`) BKA(`y`) */ `x`.`id`
+                FROM `t1` AS `x` JOIN `t2` ON `t2`.`t1_id` = `x`.`id`";
+            let options = options.clone().validate_optimizer_hints(true);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let warnings: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+            if warnings
+                != [
+                    "Unknown index in optimizer hint",
+                    "Unknown table in optimizer hint",
+                ]
+            {
+                println!("{} unexpected warnings {:?}", name, warnings);
+                errors += 1;
+            }
+
+            let options = options.ignore_identifier_case(true);
+            let src = "SELECT /*+ INDEX(X hat2) NO_BKA(T2) */ `x`.`id` FROM `t1` AS `x`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if !issues.is_ok() {
+                println!("{} unexpected issues {:?}", name, issues.get());
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q51";
+            let src = "SELECT UDF_CONCAT(`id`, `ctext`, ?, 'x') AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "str", &mut errors);
+                check_columns(name, &columns, "a:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q52";
+            let src = "SELECT udf_concat(`ctext`) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.is_ok() {
+                println!("{} should fail", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q53";
+            let src = "SELECT `id` FROM `t1` WHERE `ci32` = ? AND `cu8` = ? AND `ctext` = ?";
+            let options = options.clone().max_arguments(2);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.get().len() != 1 || issues.get()[0].sql_segment != "?" {
+                println!("{} should fail once on the last argument", name);
+                errors += 1;
+            }
+
+            // Numbered arguments may skip past the maximum
+            let src = "SELECT `id` FROM `t1` WHERE `ci32` = $1 AND `cu8` = $5 AND `cu16` = $6";
+            let options = options.arguments(SQLArguments::Dollar);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.get().len() != 1 || issues.get()[0].sql_segment != "$5" {
+                println!("{} should fail once on the argument past the maximum", name);
+                errors += 1;
+            }
+
+            // Every statement of a script is checked on its own
+            let src = "SELECT `id` FROM `t1` WHERE `ci32` = ? AND `cu8` = ?;
+                SELECT `id` FROM `t1` WHERE `ci32` = ? AND `cu16` = ?;";
+            let options = options.arguments(SQLArguments::QuestionMark);
+            let options = options.max_arguments(1);
+            let mut issues: Issues<'_> = Issues::new(src);
+            let statements = crate::type_statements(&schema, src, &mut issues, &options);
+            if issues.get().len() != 2
+                || statements.iter().any(|(_, info)| !info.too_many_arguments)
+            {
+                println!("{} should fail once for each statement", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q54";
+            let mut src = alloc::string::String::from("SELECT `id` FROM `t1` WHERE `id` IN (?");
+            for _ in 0..1000 {
+                src.push_str(", ?");
+            }
+            src.push(')');
+            let options = options.clone().max_arguments(65535);
+            let mut issues: Issues<'_> = Issues::new(&src);
+            type_statement(&schema, &src, &mut issues, &options);
+            check_no_errors(name, &src, issues.get(), &mut errors);
+            if issues.get().len() != 1 || issues.get()[0].level != Level::Warning {
+                println!("{} should warn about the large IN list", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q55";
+            let src = "SELECT `add_one`(`cu8`) AS `a` FROM `t1` WHERE add_one(`id`) = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "i", &mut errors);
+                check_columns(name, &columns, "a:i32", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q56";
+            let src = "SELECT `add_one`(`ctext`, 2) AS `a` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.get().len() != 2 {
+                println!("{} should fail twice", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q57";
+            let src = "SELECT `email`, `note` FROM `t5`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "email:str!,note:str", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+            let t5 = schema.schemas.get("t5").unwrap();
+            if t5.get_column("email").unwrap().semantic.as_deref() != Some("email")
+                || t5.get_column("note").unwrap().semantic.is_some()
+            {
+                println!("{} wrong semantic tags", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q58";
+            let src = "SELECT `id` AS `a`, `ctext` AS `b` FROM `t1` UNION SELECT `t1_id` AS `x`, 'y' FROM `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if !issues.get().is_empty() {
+                println!("{} should not warn", name);
+                errors += 1;
+            }
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "a:i32!,b:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q59";
+            let src = "SELECT `id` AS `a`, `ctext` AS `b` FROM `t1` UNION SELECT `t1_id` AS `x`, 'y' AS `b` FROM `t2`";
+            let options = options.clone().warn_union_name_mismatch(true);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if issues.get().len() != 1 {
+                println!("{} should warn once", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q60";
+            let src = "SELECT MAX(COUNT(`id`)) AS `a` FROM `t1` WHERE SUM(`cu8`) > 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.get().len() != 2 {
+                println!("{} should fail twice", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q61";
+            let src = "SELECT `ci32` + 1 AS `a`, `ctext` AS `b`, `cu8` AS `c`, COUNT(*) AS `d`
+                FROM `t1` GROUP BY `ci32` + 1, `t1`.`ctext`, `c`";
+            let options = options.clone().only_full_group_by(true);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+        }
+
+        {
+            let name = "q62";
+            let src = "SELECT `id`, `ctext`, MAX(`cu8`) AS `m` FROM `t1` GROUP BY `id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let options = options.clone().only_full_group_by(true);
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            if issues.get().len() != 1 || issues.get()[0].sql_segment != "`ctext`" {
+                println!("{} should fail on ctext", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q63";
+            let src = "SELECT `t1`.`id`, COUNT(*) AS `c` FROM `t1` LEFT JOIN `t2` ON `t2`.`t1_id` = `t1`.`id`
+                WHERE `t1`.`cu8` = 1 AND `t2`.`id` > 3 AND `ci32` = 2
+                GROUP BY `t1`.`id` ORDER BY `t1`.`id` LIMIT 5";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let seg = |spans: &[core::ops::Range<usize>]| {
+                spans.iter().map(|s| &src[s.clone()]).collect::<Vec<_>>()
+            };
+            let ok = match &info.plan {
+                Some(PlanNode::Limit { input, .. }) => match &**input {
+                    PlanNode::Sort { input, .. } => match &**input {
+                        PlanNode::Aggregate {
+                            group_by, input, ..
+                        } if seg(group_by) == ["`t1`.`id`"] => match &**input {
+                            PlanNode::Filter { predicates, input }
+                                if seg(predicates) == ["`t2`.`id` > 3"] =>
+                            {
+                                match &**input {
+                                    PlanNode::Join {
+                                        kind: JoinKind::Left,
+                                        left,
+                                        right,
+                                        condition: Some(_),
+                                    } => {
+                                        matches!(&**left, PlanNode::Scan { table: "t1", predicates, .. }
+                                            if seg(predicates) == ["`t1`.`cu8` = 1", "`ci32` = 2"])
+                                            && matches!(&**right, PlanNode::Scan { table: "t2", predicates, .. }
+                                            if predicates.is_empty())
+                                    }
+                                    _ => false,
+                                }
+                            }
+                            _ => false,
+                        },
+                        _ => false,
+                    },
+                    _ => false,
+                },
+                _ => false,
+            };
+            if !ok {
+                println!("{} unexpected plan {:?}", name, info.plan);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q64";
+            let src = "SELECT DATEDIFF('a', 3) AS `d`, LPAD(`ctext`) AS `p`,
+                date_format /* x */ (3, 'y') AS `f` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    (
+                        "Argument 2 of DATEDIFF should be of type datetime got integer",
+                        "3",
+                    ),
+                    ("Expected between 2 and 3 arguments got 1", "LPAD"),
+                    (
+                        "Argument 1 of DATE_FORMAT should be of type datetime got integer",
+                        "3",
+                    ),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q65";
+            let scope = crate::scope::TypeScope::new(&schema, &[("t1", Some("a"))], &options)
+                .expect("scope");
+            let src = "`a`.`ctext` = ? AND `nope` > 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let t = scope.type_expression(src, &mut issues);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Unknown identifier", "`nope`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            check_arguments(name, &t.arguments, "str", &mut errors);
+
+            // Only a single expression over the tables in scope is accepted
+            for src in [
+                "`a`.`ctext` AS `zzz`",
+                "(SELECT `ctext` FROM `t2` LIMIT 1)",
+                "`ctext` FROM `t2`",
+                "`ctext`, `id`",
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                scope.type_expression(src, &mut issues);
+                if issues.is_ok() {
+                    println!("{} {} should fail", name, src);
+                    errors += 1;
+                }
+            }
+            if crate::scope::TypeScope::new(&schema, &[("missing", None)], &options).is_ok() {
+                println!("{} expected unknown table", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q66";
+            let src = "SELECT MD5(`ctext`) AS `m`, SHA1(`cbytes`) AS `s`, SHA2(?, 256) AS `s2`,
+                CRC32(`ctext`) AS `c`, TO_BASE64(`cbin`) AS `b`, FROM_BASE64(`ctext`) AS `f`,
+                AES_ENCRYPT(`ctext`, 'key') AS `e`, AES_DECRYPT(`cbytes`, 'key') AS `d`
+                FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "str", &mut errors);
+                check_columns(
+                    name,
+                    &columns,
+                    "m:str!,s:str,s2:str,c:u32!,b:str,f:bytes,e:bytes!,d:bytes",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q67";
+            let src = "SELECT MD5(`id`) FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Expected type String or Bytes got i32", "`id`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q68";
+            let src = "SELECT `t1`.`id`, COUNT(`t2`.`id`) AS `c`, COUNT(*) AS `n`,
+                MAX(`t2`.`t1_id`) AS `mx`, SUM(`t1`.`cu8`) AS `s`, AVG(`t2`.`t1_id`) AS `a`,
+                GROUP_CONCAT(`t2`.`id`) AS `g`
+                FROM `t1` LEFT JOIN `t2` ON `t2`.`t1_id` = `t1`.`id` GROUP BY `t1`.`id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "", &mut errors);
+                check_columns(
+                    name,
+                    &columns,
+                    "id:i32!,c:i!,n:i!,mx:i32,s:u8!,a:f64,g:str",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q69";
+            let src =
+                "SELECT MAX(`id`) AS `mx`, COUNT(*) AS `n`, GROUP_CONCAT(`ctext`) AS `g` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "", &mut errors);
+                check_columns(name, &columns, "mx:i32,n:i!,g:str", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q70";
+            let src = "SELECT ST_X(ST_GeomFromText(?)) AS `x`,
+                ST_Distance(POINT(1, 2), POINT(`cf64`, 3)) AS `d`,
+                ST_Contains(ST_GeomFromText('POLYGON((0 0,0 3,3 3,3 0,0 0))'), POINT(1, 2)) AS `c`,
+                ST_AsText(POINT(1, 2)) AS `t`
+                FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "str", &mut errors);
+                check_columns(name, &columns, "x:f64,d:f64,c:b,t:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q71";
+            let src = "SELECT ST_X(`ctext`) FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "Argument 1 of ST_X should be of type geometry got string",
+                    "`ctext`",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q72";
+            let src =
+                "SELECT LAST_INSERT_ID() AS `i`, LAST_INSERT_ID(?) AS `j`, ROW_COUNT() AS `r`,
+                FOUND_ROWS() AS `f`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "FOUND_ROWS() is deprecated, use COUNT(*) instead",
+                    "FOUND_ROWS",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "i", &mut errors);
+                check_columns(name, &columns, "i:u64!,j:u64,r:i64!,f:u64!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q73";
+            let src = "SELECT `v` FROM `t6` WHERE `tenant` = ? AND `id` = 3";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let bindings: Vec<_> = info
+                .shard_access
+                .iter()
+                .map(|a| {
+                    (
+                        a.table,
+                        a.bindings.iter().map(|b| b.column).collect::<Vec<_>>(),
+                    )
+                })
+                .collect();
+            if bindings != [("t6", alloc::vec!["tenant"])] {
+                println!("{} unexpected shard access {:?}", name, bindings);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q74";
+            let src =
+                "SELECT `a`.`v` FROM `t6` AS `a` JOIN `t6` AS `b` ON `b`.`tenant` = `a`.`tenant`
+                WHERE `a`.`tenant` = 1";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if info.shard_access.iter().any(|a| a.bindings.len() != 1)
+                || info.shard_access.len() != 2
+            {
+                println!("{} unexpected shard access {:?}", name, info.shard_access);
+                errors += 1;
+            }
+
+            for (src, expected) in [
+                (
+                    "SELECT `a`.`v` FROM `t6` AS `a` JOIN `t6` AS `b` ON `b`.`id` = `a`.`id`
+                    WHERE `a`.`tenant` = 1",
+                    Some("`t6`"),
+                ),
+                (
+                    "SELECT `id` FROM `t1` WHERE `id` IN (SELECT `id` FROM `t6`)",
+                    Some("`t6`"),
+                ),
+                (
+                    "SELECT `a`.`v` FROM `t6` AS `a` WHERE `a`.`tenant` = 1
+                    AND EXISTS (SELECT `b`.`id` FROM `t6` AS `b` WHERE `b`.`tenant` = `a`.`tenant`)",
+                    None,
+                ),
+                (
+                    "INSERT INTO `t6` (`tenant`, `id`, `v`) SELECT `tenant`, `id` + 1, `v` FROM `t6`",
+                    Some("`t6`"),
+                ),
+                ("SELECT `v` FROM `T6` WHERE `Tenant` = 1", None),
+            ] {
+                let options = options.clone().ignore_identifier_case(true);
+                let mut issues: Issues<'_> = Issues::new(src);
+                type_statement(&schema, src, &mut issues, &options);
+                let got: Vec<_> = issues
+                    .get()
+                    .iter()
+                    .filter(|i| i.message.starts_with("Sharded"))
+                    .map(|i| &src[i.span.clone()])
+                    .collect();
+                let expected: Vec<_> = expected.into_iter().collect();
+                if got != expected {
+                    println!("{} unexpected shard warnings {:?} in {}", name, got, src);
+                    errors += 1;
+                }
+            }
+        }
+
+        {
+            let name = "q75";
+            let options = options
+                .clone()
+                .argument_type(0, FullType::new(Type::U64, true));
+            let src = "SELECT `id` FROM `t1` WHERE `cu64` = ? AND `ctext` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, .. } = q {
+                check_arguments(name, &arguments, "u64!, str", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT `id` FROM `t1` WHERE `ctext` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Argument declared as u64 used as string", "?")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+
+            // Declared arguments that are not used are not reported
+            let options = options.argument_type(3, FullType::new(Type::I8, false));
+            let src = "SELECT `id` FROM `t1` WHERE `cu64` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, .. } = q {
+                check_arguments(name, &arguments, "u64!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q76";
+            let src = "SELECT `t1`.`id`, `e`.`kind` FROM `t1`
+                JOIN `db2`.`events` AS `e` ON `e`.`t1_id` = `t1`.`id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (q, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32!,kind:str", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+            if info.database_tables != [("db2", "events")] || info.tables != ["t1"] {
+                println!("{} unexpected tables {:?}", name, info.database_tables);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q77";
+            let src = "SELECT `db2`.`t1`.`x` FROM `t1` JOIN `db2`.`t1` ON `db2`.`t1`.`id` = `cu8`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "x:i32!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT 1 FROM `t1` JOIN `db2`.`t1` ON TRUE WHERE `t1`.`id` = 1";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Ambiguous reference", "`t1`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+
+            let src = "SELECT `t1`.`id` FROM `t1` JOIN `db2`.`t1` ON TRUE";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Ambiguous reference", "`t1`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q78";
+            let src = "SELECT `cu8` AS `a`, `cu8` AS `b`, `cu32` AS `c`, `ctext` AS `d`, `ci8` AS `e` FROM `t1`
+                UNION SELECT `cu16`, `cf64`, `ci32`, 'x', `cu8` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(
+                    name,
+                    &columns,
+                    "a:u16!,b:f64,c:i64,d:str!,e:i16",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q79";
+            let src = "SELECT `id`, `cu8` FROM `t1` UNION SELECT `id` FROM `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Different number of columns in union", "UNION")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q80";
+            let src = "SELECT `x`.`a` FROM (SELECT `id` AS `a`, `cu8` AS `b` FROM `t1`) AS `x`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "a:i32!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT `a` FROM (SELECT `id` AS `a` FROM `t1`)";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "Every derived table must have its own alias",
+                    "SELECT `id` AS `a` FROM `t1`",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q81";
+            let src = "SELECT `created`, `seen` FROM `t7` WHERE `created` > ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                let got: Vec<_> = columns.iter().map(|c| c.type_.precision).collect();
+                if got != [Some(6), Some(6)] {
+                    println!("{} unexpected precision {:?}", name, got);
+                    errors += 1;
+                }
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT `id` FROM `t7` WHERE `created` < `updated`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "Comparison of temporal values with different precision",
+                    "<",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q82";
+            let options = options.clone().warn_multi_row_subquery(true);
+            let src = "SELECT `t2`.`id`,
+                (SELECT `created` FROM `t7` WHERE `t7`.`id` = `t2`.`t1_id`) AS `a`,
+                (SELECT MAX(`t1`.`id`) FROM `t1`) AS `b`,
+                (SELECT `ctext` FROM `t1` ORDER BY `t1`.`id` LIMIT 1) AS `c`,
+                (SELECT `v` FROM `t6` AS `x` WHERE `x`.`id` = `t2`.`id` AND `tenant` = ?) AS `d`,
+                (SELECT `v` FROM `t6` WHERE `t6`.`id` = `t2`.`id`) AS `e`
+                FROM `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    (
+                        "Scalar subquery may return more than one row",
+                        "SELECT `v` FROM `t6` WHERE `t6`.`id` = `t2`.`id`",
+                    ),
+                    (
+                        "Sharded table accessed without constraining its shard key",
+                        "`t6`",
+                    ),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q83";
+            let src = "SELECT `id`, `t1_id`, `t2`.`id` AS `b` FROM `t1` JOIN `t2` USING `id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32!,t1_id:i32!,b:i32!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT * FROM `t2` LEFT JOIN `t2` AS `x` USING `id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32!,t1_id:i32!,t1_id:i32", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT *, `id` FROM `t2` JOIN `t2` AS `x` USING `id` JOIN `t3` USING `id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(
+                    name,
+                    &columns,
+                    "id:i32!,t1_id:i32!,t1_id:i32!,text:str,id:i32!",
+                    &mut errors,
+                );
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT 1 FROM `t1` JOIN `t2` USING `cu8`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "Column in USING not found on both sides of the join",
+                    "`cu8`",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q84";
+            let src = "SELECT * FROM `t2` NATURAL JOIN `t2` AS `x`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32!,t1_id:i32!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let src = "SELECT `id`, `t1_id` FROM `t1` NATURAL LEFT JOIN `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32!,t1_id:i32", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+
+            let options = options.clone().warn_natural_join(true);
+            let src = "SELECT `id` FROM `t1` NATURAL JOIN `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "NATURAL JOIN silently changes when columns are added to either side",
+                    "NATURAL JOIN",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q85";
+            let src = "SELECT `t1`.`id`, `t1`.`ctext`, `t2`.`t1_id` FROM `t1`
+                RIGHT JOIN `t2` ON `t2`.`t1_id` = `t1`.`id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "id:i32,ctext:str,t1_id:i32!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q86";
+            let src = "INSERT INTO `t2` SELECT `id`, `cu8` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+
+            let src = "INSERT INTO `t2` (`id`, `t1_id`) SELECT `id`, 'x' FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| {
+                    (
+                        &*i.message,
+                        i.sql_segment,
+                        i.fragments
+                            .iter()
+                            .map(|f| f.sql_segment)
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect();
+            if got != [("Got type string", "'x'", vec!["`t1_id`"])] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+
+            let src = "INSERT INTO `t2` SELECT `id` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Missing column in select", "`t2`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q87";
+            let src = "INSERT INTO `t2` (`id`, `t1_id`) VALUES (?, ?)
+                ON DUPLICATE KEY UPDATE `t1_id` = 'x', `nope` = 1, `id` = VALUES(`id`) + ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Got type string not null expected i32 not null", "'x'"),
+                    ("Unknown identifier", "`nope`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            if let StatementType::Insert { arguments, .. } = q {
+                check_arguments(name, &arguments, "i32!,i32!,i", &mut errors);
+            } else {
+                println!("{} should be insert", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q88";
+            let src = "DELETE FROM `t1` WHERE `id` = ? RETURNING `id`, `ctext`, `cf64` AS `f`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Delete {
+                arguments,
+                returning,
+            } = q
+            {
+                check_arguments(name, &arguments, "i", &mut errors);
+                if let Some(returning) = returning {
+                    check_columns(name, &returning, "id:i32!,ctext:str!,f:f64", &mut errors);
+                } else {
+                    println!("{} should return columns", name);
+                    errors += 1;
+                }
+            } else {
+                println!("{} should be delete", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q89";
+            let src = "REPLACE INTO `t2` (`t1_id`) SELECT `id` FROM `t1` WHERE `ctext` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Replace {
+                yield_autoincrement,
+                arguments,
+                ..
+            } = q
+            {
+                check_arguments(name, &arguments, "str", &mut errors);
+                if yield_autoincrement != AutoIncrementId::Yes {
+                    println!("{} should yield autoincrement", name);
+                    errors += 1;
+                }
+            } else {
+                println!("{} should be replace", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q90";
+            let src = "INSERT INTO `t7` SET `id` = ?, `created` = NOW(), `updated` = NOW()";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Insert { arguments, .. } = q {
+                check_arguments(name, &arguments, "i32!", &mut errors);
+            } else {
+                println!("{} should be insert", name);
+                errors += 1;
+            }
+            let src = "INSERT INTO `t7` SET `id` = ?, `seen` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Missing value for column created", "`t7`"),
+                    ("Missing value for column updated", "`t7`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q91";
+            let src = "INSERT INTO `t7` (`id`, `updated`) VALUES (?, NOW())";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Missing value for column created", "`t7`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            let src = "INSERT INTO `t6` (`tenant`, `id`) SELECT `id`, `t1_id` FROM `t2`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+        }
+
+        {
+            let name = "q92";
+            let src = "INSERT INTO `t2` (`id`, `t1_id`) VALUES (?, ?), (1, 'x'), (2), (3, 4, 5)";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Got type string", "'x'"),
+                    ("Got 1 values expected 2", "2"),
+                    ("Got 3 values expected 2", "3, 4, 5"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q93";
+            let src = "UPDATE `t1` JOIN `t2` ON `t2`.`t1_id` = `t1`.`id`
+                SET `t1`.`cu8` = `t2`.`id`, `ctext` = ? WHERE `t2`.`id` = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Update { arguments, .. } = q {
+                check_arguments(name, &arguments, "str!,i", &mut errors);
+            } else {
+                println!("{} should be update", name);
+                errors += 1;
+            }
+            let src =
+                "UPDATE `t2` JOIN (SELECT `id` AS `x` FROM `t1`) AS `d` ON `d`.`x` = `t2`.`id`
+                SET `d`.`x` = 1, `x` = 2, `t2`.`t1_id` = 'a'";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Only columns of tables can be updated", "`d`.`x`"),
+                    ("Only columns of tables can be updated", "`x`"),
+                    ("Got type string not null expected i32 not null", "'a'"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            let src = "UPDATE `t1` SET `cu8_plus_one` = 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Generated columns can not be updated", "`cu8_plus_one`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q94";
+            for src in [
+                "DELETE `a`, `t2` FROM `t1` AS `a` JOIN `t2` ON `t2`.`t1_id` = `a`.`id` WHERE `a`.`id` = ?",
+                "DELETE FROM `a` USING `t1` AS `a` JOIN `t2` ON `t2`.`t1_id` = `a`.`id` WHERE `t2`.`id` = ?",
+                "DELETE `db2`.`t1` FROM `db2`.`t1` JOIN `t2` ON `t2`.`t1_id` = `db2`.`t1`.`id` WHERE `x` = ?",
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let q = type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if let StatementType::Delete { arguments, .. } = q {
+                    check_arguments(name, &arguments, "i", &mut errors);
+                } else {
+                    println!("{} should be delete", name);
+                    errors += 1;
+                }
+            }
+            let src = "DELETE `t1`, `d` FROM `t3` JOIN (SELECT `id` FROM `t1`) AS `d` ON `d`.`id` = `t3`.`id`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Unknown table in multi delete", "`t1`"),
+                    ("Only tables can be deleted from", "`d`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q95";
+            let src = "SELECT `id`, `cu8` + 1 AS `x` FROM `t1` ORDER BY 2, `x` DESC, `ctext`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let src = "SELECT `id` FROM `t1` ORDER BY 0, 2, `nope`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Position in ORDER BY is not in the select list", "0"),
+                    ("Position in ORDER BY is not in the select list", "2"),
+                    ("Unknown identifier", "`nope`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            let src = "SELECT `id` FROM `t1` UNION SELECT `id` FROM `t2` ORDER BY 1, 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Position in ORDER BY is not in the select list", "2")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q96";
+            let options = options.clone().only_full_group_by(true);
+            let src = "SELECT `t1_id`, COUNT(*) AS `n` FROM `t2` GROUP BY `t1_id`
+                HAVING `n` > 1 AND `t1_id` > ? AND MAX(`id`) > 2";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            let src = "SELECT `t1_id` FROM `t2` GROUP BY `t1_id` HAVING `id` > 1";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [(
+                    "Column must be grouped by or used in an aggregate function",
+                    "`id`",
+                )]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q97";
+            for src in [
+                "SELECT `id` FROM `t1` LIMIT ?, ?",
+                "SELECT `id` FROM `t1` LIMIT ? OFFSET ?",
+                "SELECT `id` FROM `t1` UNION SELECT `id` FROM `t2` LIMIT ?, ?",
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let q = type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if let StatementType::Select { arguments, .. } = q {
+                    check_arguments(name, &arguments, "u64!,u64!", &mut errors);
+                } else {
+                    println!("{} should be select", name);
+                    errors += 1;
+                }
+            }
+            let src = "SELECT `id` FROM `t1` LIMIT 'a', 1.5";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Expected integer type got string", "'a'"),
+                    ("Expected integer type got float", "1.5"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q98";
+            for src in [
+                "SELECT `id` FROM `t1` WHERE `id` = ? FOR UPDATE",
+                "SELECT `a`.`id` FROM `t1` AS `a` JOIN `t2` ON `t2`.`t1_id` = `a`.`id`
+                    WHERE `a`.`id` = ? FOR SHARE OF `a`, `t2` NOWAIT",
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+            }
+            let src = "SELECT `id` FROM `t1` AS `a` FOR UPDATE OF `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Unknown table in locking clause", "`t1`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q99";
+            let src =
+                "SET autocommit = 0, sql_mode = ?, foreign_key_checks = OFF, wait_timeout = ?";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Set { arguments } = q {
+                check_arguments(name, &arguments, "str!,i!", &mut errors);
+            } else {
+                println!("{} should be set", name);
+                errors += 1;
+            }
+            let src = "SET wait_timeout = 'x', no_such_variable = 1";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Got type string not null expected integer not null", "'x'"),
+                    ("Unknown system variable", "no_such_variable"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q100";
+            for src in ["BEGIN", "START TRANSACTION", "COMMIT"] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let q = type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if !matches!(q, StatementType::Transaction) {
+                    println!("{} {} should be a transaction statement", name, src);
+                    errors += 1;
+                }
+            }
+        }
+
+        {
+            let name = "q101";
+            for src in ["TRUNCATE TABLE `t1`", "TRUNCATE `db2`.`events`"] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let q = type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if !matches!(q, StatementType::Truncate) {
+                    println!("{} {} should be a truncate statement", name, src);
+                    errors += 1;
+                }
+            }
+            let src = "TRUNCATE TABLE `nope`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got != [("Unknown table", "`nope`")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q102";
+            let src = "INSERT IGNORE INTO `t7` (`id`, `created`) VALUES (?, NOW())";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (i.level, &*i.message))
+                .collect();
+            if !info.ignore || got != [(Level::Warning, "Missing value for column updated")] {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            for (src, ignore) in [
+                ("UPDATE IGNORE `t1` SET `cu8` = 1", true),
+                ("DELETE IGNORE FROM `t1` WHERE `id` = 1", true),
+                ("DELETE FROM `t1` WHERE `id` = 1", false),
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if info.ignore != ignore {
+                    println!("{} {} should have ignore {}", name, src, ignore);
+                    errors += 1;
+                }
+            }
+        }
+
+        {
+            let name = "q103";
+            let src = "CREATE TEMPORARY TABLE `tmp` (`id` int NOT NULL, `v` varchar(10));
+                INSERT INTO `tmp` (`id`, `v`) SELECT `id`, `ctext` FROM `t1` WHERE `cu8` = ?;
+                SELECT `tmp`.`id`, `v` FROM `tmp` JOIN `t2` ON `t2`.`t1_id` = `tmp`.`id`;
+                DROP TEMPORARY TABLE `tmp`;
+                SELECT `id` FROM `tmp`;";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let statements = crate::type_statements(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Unknown table or view", "`tmp`"),
+                    ("Unknown identifier", "`id`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+            match statements.as_slice() {
+                [(StatementType::TemporaryTable, _), (StatementType::Insert { arguments, .. }, _), (StatementType::Select { columns, .. }, _), (StatementType::TemporaryTable, _), _] =>
+                {
+                    check_arguments(name, arguments, "i", &mut errors);
+                    check_columns(name, columns, "id:i32!,v:str", &mut errors);
+                }
+                _ => {
+                    println!("{} unexpected statements {:?}", name, statements);
+                    errors += 1;
+                }
+            }
+
+            // Every statement of the script gets the checks of type_statement
+            let options = options.clone().validate_optimizer_hints(true);
+            let src = "SELECT /*+ INDEX(`t1` `nope`) */ `id` FROM `t1`;
+                SELECT /*+ NO_BKA(`t9`) */ 1;
+                CREATE TEMPORARY TABLE `dual` (`id` int);";
+            let mut issues: Issues<'_> = Issues::new(src);
+            crate::type_statements(&schema, src, &mut issues, &options);
+            let got: Vec<_> = issues
+                .get()
+                .iter()
+                .map(|i| (&*i.message, i.sql_segment))
+                .collect();
+            if got
+                != [
+                    ("Unknown index in optimizer hint", "`nope`"),
+                    ("Unknown table in optimizer hint", "`t9`"),
+                    ("Table name collides with the DUAL pseudo table", "`dual`"),
+                ]
+            {
+                println!("{} unexpected issues {:?}", name, got);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q104";
+            for (src, column) in [
+                (
+                    "INSERT INTO `t1` SET `cbool` = true, `cu8` = 1, `cu16` = 1, `cu32` = 1, `cu64` = 1, `ctext` = 'a', `status` = 'a'",
+                    "`status`",
+                ),
+                (
+                    "INSERT INTO `t1` (`cbool`, `cu8`, `cu16`, `cu32`, `cu64`, `ctext`, `cu8_plus_one`) VALUES (true, 1, 1, 1, 1, 'a', 2)",
+                    "`cu8_plus_one`",
+                ),
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                type_statement(&schema, src, &mut issues, &options);
+                let got: Vec<_> = issues
+                    .get()
+                    .iter()
+                    .map(|i| (&*i.message, i.sql_segment))
+                    .collect();
+                if got != [("Generated columns can not be inserted into", column)] {
+                    println!("{} unexpected issues {:?}", name, got);
+                    errors += 1;
+                }
+            }
+        }
+
+        {
+            let name = "q105";
+            for (src, expected) in [
+                ("INSERT INTO `t2` (`t1_id`) VALUES (1)", Some(Type::I32)),
+                ("REPLACE INTO `t2` (`t1_id`) VALUES (1)", Some(Type::I32)),
+                (
+                    "INSERT INTO `t7` (`id`, `created`, `updated`) VALUES (1, NOW(), NOW())",
+                    None,
+                ),
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let q = type_statement(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                match q {
+                    StatementType::Insert {
+                        auto_increment_type,
+                        ..
+                    }
+                    | StatementType::Replace {
+                        auto_increment_type,
+                        ..
+                    } if auto_increment_type == expected => (),
+                    _ => {
+                        println!(
+                            "{} {} should have auto increment type {:?}",
+                            name, src, expected
+                        );
+                        errors += 1;
+                    }
+                }
+            }
+        }
+
+        {
+            let name = "q106";
+            for (src, one) in [
+                ("SELECT `created` FROM `t7` WHERE `id` = ?", true),
+                ("SELECT `created` FROM `t7` WHERE `id` > ?", false),
+                ("SELECT COUNT(*) FROM `t1`", true),
+                ("SELECT MAX(`id`) IN (1, 2) AS `m` FROM `t1`", true),
+                ("SELECT COUNT(*) > 0 AS `c` FROM `t1`", true),
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+                check_no_errors(name, src, issues.get(), &mut errors);
+                if info.at_most_one_row != one {
+                    println!("{} {} should have at_most_one_row {}", name, src, one);
+                    errors += 1;
+                }
+            }
+            if !schema.table_indices("t1").any(|i| i == "hat2") {
+                println!("{} t1 should have index hat2", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q107";
+            for (src, expected) in [
+                (
+                    "INSERT INTO `t7` (`id`, `created`, `updated`) VALUES (1, NOW(6), NOW(6))",
+                    Some("NOW(6"),
+                ),
+                (
+                    "UPDATE `t7` SET `updated` = `created` WHERE `id` = 1",
+                    Some("`created`"),
+                ),
+                (
+                    "UPDATE `t7` SET `created` = NOW(6), `updated` = NOW(3) WHERE `id` = 1",
+                    None,
+                ),
+            ] {
+                let mut issues: Issues<'_> = Issues::new(src);
+                type_statement(&schema, src, &mut issues, &options);
+                let got: Vec<_> = issues
+                    .get()
+                    .iter()
+                    .map(|i| (&*i.message, i.sql_segment))
+                    .collect();
+                let expected: Vec<_> = expected
+                    .into_iter()
+                    .map(|s| ("Value with 6 fractional second digits is truncated to 3", s))
+                    .collect();
+                if got != expected {
+                    println!("{} unexpected issues {:?} for {}", name, got, src);
+                    errors += 1;
+                }
+            }
+
+            let src = "SELECT NOW(3) AS `a`, ADDTIME(`created`, '01:00') AS `b` FROM `t7`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                let got: Vec<_> = columns.iter().map(|c| c.type_.precision).collect();
+                if got != [Some(3), Some(6)] {
+                    println!("{} unexpected precision {:?}", name, got);
+                    errors += 1;
+                }
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q108";
+            let src = "SELECT `ctext`, `cbytes`, CONCAT(`cbytes`) AS `c` FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                let got: Vec<_> = columns.iter().map(|c| c.type_.size_class).collect();
+                if got != [None, Some(SizeClass::Regular), None] {
+                    println!("{} unexpected size classes {:?}", name, got);
+                    errors += 1;
+                }
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q109";
+            // Columns only not null in practice may still be left out
+            let src = "INSERT INTO `t5` (`id`) VALUES (?)";
+            let mut issues: Issues<'_> = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+        }
+
+        {
+            let name = "q110";
+            let src = "SELECT CASE WHEN `cu8` = 1 THEN `cu8` ELSE `cu16` END AS `a`,
+                CASE WHEN `cu8` = 1 THEN 1 WHEN `cu8` = 2 THEN 'x' ELSE 2 END AS `b`
+                FROM `t1`";
+            let mut issues: Issues<'_> = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { columns, .. } = q {
+                check_columns(name, &columns, "a:i!,b:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn postgresql() {
+        let schema_src = "
+        BEGIN;
+
+        DO $$ BEGIN
+            CREATE TYPE my_enum AS ENUM (
+            'V1',
+            'V2',
+            'V3'
+        );
+        EXCEPTION
+            WHEN duplicate_object THEN null;
+        END $$;
+
+        CREATE TABLE IF NOT EXISTS t1 (
+            id bigint NOT NULL PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+            path text NOT NULL UNIQUE,
+            v my_enum NOT NULL,
+            time timestamptz NOT NULL DEFAULT now(),
+            old_id bigint,
+            CONSTRAINT t1__old
+            FOREIGN KEY(old_id) 
+            REFERENCES t1(id)
+            ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS t2 (
+            id bigint NOT NULL PRIMARY KEY
+        );
+
+        DROP INDEX IF EXISTS t2_index;
+
+        CREATE INDEX t2_index2 ON t2 (id);
+
+        COMMIT;
+        ";
+
+        let options = TypeOptions::new().dialect(SQLDialect::PostgreSQL);
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        check_no_errors("schema", schema_src, issues.get(), &mut errors);
+
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::PostgreSQL)
+            .arguments(SQLArguments::Dollar);
+
+        {
+            let name = "q1";
+            let src =
+                "INSERT INTO t2 (id) SELECT id FROM t1 WHERE path=$1 ON CONFLICT (id) DO NOTHING RETURNING id";
+            let mut issues = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Insert {
+                arguments,
+                returning,
+                ..
+            } = q
+            {
+                check_arguments(name, &arguments, "str", &mut errors);
+                check_columns(name, &returning.expect("Returning"), "id:i64!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q2";
+            let src =
+                "WITH hat AS (DELETE FROM t1 WHERE old_id=42 RETURNING id) INSERT INTO t2 (id) SELECT id FROM hat";
+            let mut issues = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+
+            if let StatementType::Insert { arguments, .. } = q {
+                check_arguments(name, &arguments, "", &mut errors);
+            } else {
+                println!("{} should be select {q:?}", name);
+                errors += 1;
+            }
+        }
+
+        {
+            let name = "q3";
+            let src = "WITH a AS (SELECT id, path FROM t1 WHERE old_id = $1),
+                b AS (SELECT id, path FROM a WHERE path = $2)
+                SELECT b.id, a.path FROM b JOIN a ON a.id = b.id WHERE b.id > $3";
+            let mut issues = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            check_no_errors(name, src, issues.get(), &mut errors);
+            if let StatementType::Select { arguments, columns } = q {
+                check_arguments(name, &arguments, "i, str, i", &mut errors);
+                check_columns(name, &columns, "id:i64!,path:str!", &mut errors);
+            } else {
+                println!("{} should be select", name);
+                errors += 1;
+            }
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn custom_types() {
+        use crate::CustomType;
+        use alloc::sync::Arc;
+
+        #[derive(Debug)]
+        struct Citext;
+
+        impl CustomType for Citext {
+            fn name(&self) -> &str {
+                "citext"
+            }
+
+            fn base(&self) -> BaseType {
+                BaseType::String
+            }
+        }
+
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::PostgreSQL)
+            .arguments(SQLArguments::Dollar)
+            .custom_type(Arc::new(Citext));
+        let schema_src = "CREATE TABLE users (id bigint NOT NULL, email citext NOT NULL);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        check_no_errors("schema", schema_src, issues.get(), &mut errors);
+
+        let src = "SELECT email FROM users WHERE email = $1 AND email <> 'x'";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("c1", src, issues.get(), &mut errors);
+        if let StatementType::Select { arguments, columns } = q {
+            check_arguments("c1", &arguments, "str", &mut errors);
+            if columns[0].type_.to_string() != "citext not null" {
+                println!("c1 unexpected type {}", columns[0].type_);
+                errors += 1;
+            }
+        } else {
+            println!("c1 should be select");
+            errors += 1;
+        }
+
+        let src = "SELECT id FROM users WHERE email = id";
+        let mut issues = Issues::new(src);
+        type_statement(&schema, src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        if got != ["Type error in comparison"] {
+            println!("c2 unexpected issues {:?}", got);
+            errors += 1;
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn alter_table() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `m` (`id` int NOT NULL, `a` int);
+            ALTER TABLE `m` ADD COLUMN `b` varchar(10) NOT NULL;
+            ALTER TABLE `m` MODIFY `a` int NOT NULL;
+            ALTER TABLE `m` ADD COLUMN `b` int;";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        if got != ["Column already defined"] {
+            println!("a1 unexpected issues {:?}", got);
+            errors += 1;
+        }
+
+        let src = "SELECT * FROM `m`";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("a2", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("a2", &columns, "id:i32!,a:i32!,b:str!", &mut errors);
+        } else {
+            println!("a2 should be select");
+            errors += 1;
+        }
+
+        // Altering a missing table in another database leaves the databases alone
+        let schema_src = "
+            ALTER TABLE `other`.`m` ADD COLUMN `b` int;
+            ALTER TABLE IF EXISTS `other`.`m` ADD COLUMN `b` int;";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        if got != ["Table not found"] || !schema.databases.is_empty() {
+            println!("a3 unexpected issues {:?}", got);
+            errors += 1;
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn views() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE VIEW `v2` AS SELECT `x`, `y` FROM `v1`;
+            CREATE VIEW `v1` AS SELECT `id` AS `x`, `name` AS `y` FROM `base`;
+            CREATE TABLE `base` (`id` int NOT NULL, `name` text);
+            CREATE VIEW `c1` AS SELECT * FROM `c2`;
+            CREATE VIEW `c2` AS SELECT * FROM `c1`;
+            CREATE VIEW `r` AS SELECT `x` FROM `later`;
+            CREATE OR REPLACE VIEW `r` AS SELECT `id` AS `z` FROM `base`;
+            CREATE VIEW `s` AS SELECT `id` FROM `base` WHERE `id` IN (SELECT `x` FROM `later`);
+            CREATE TABLE `later` (`id` int NOT NULL, `x` int);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        if got != ["View depends on itself", "View depends on itself"] {
+            println!("v1 unexpected issues {:?}", got);
+            errors += 1;
+        }
+
+        let src = "SELECT `x`, `y` FROM `v2`";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("v2", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("v2", &columns, "x:i32!,y:str", &mut errors);
+        } else {
+            println!("v2 should be select");
+            errors += 1;
+        }
+
+        // The view replacing a pending view is the one defined
+        let src = "SELECT `z` FROM `r`";
+        let mut issues = Issues::new(src);
+        type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("v3", src, issues.get(), &mut errors);
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn foreign_keys() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `p` (`id` int unsigned NOT NULL, `code` varchar(10) NOT NULL);
+            CREATE TABLE `c` (`id` int NOT NULL, `p_id` int unsigned, `p_code` int);
+            ALTER TABLE `c` ADD FOREIGN KEY (`p_id`) REFERENCES `p` (`id`);
+            ALTER TABLE `c` ADD FOREIGN KEY (`id`) REFERENCES `p` (`id`);
+            ALTER TABLE `c` ADD FOREIGN KEY (`p_code`) REFERENCES `p` (`code`);
+            ALTER TABLE `c` ADD FOREIGN KEY (`p_id`) REFERENCES `q` (`id`);
+            ALTER TABLE `c` ADD FOREIGN KEY (`p_id`) REFERENCES `p` (`pid`);
+            ALTER TABLE `c` ADD FOREIGN KEY (`p_id`, `id`) REFERENCES `p` (`id`);
+            CREATE TABLE `l` (`code` varchar(10) CHARACTER SET latin1 NOT NULL);
+            CREATE TABLE `b` (`code` varchar(10) NOT NULL) COLLATE=utf8mb4_bin;
+            CREATE TABLE `u` (`code` varchar(10) CHARACTER SET utf8mb4 NOT NULL) COLLATE=utf8mb4_general_ci;
+            ALTER TABLE `l` ADD FOREIGN KEY (`code`) REFERENCES `u` (`code`);
+            ALTER TABLE `b` ADD FOREIGN KEY (`code`) REFERENCES `u` (`code`);";
+        let mut issues = Issues::new(schema_src);
+        parse_schemas(schema_src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        assert_eq!(
+            got,
+            [
+                "Column of type i32 references column of type u32",
+                "Column of type i32 references column of type string",
+                "Unknown referenced table",
+                "Unknown referenced column",
+                "Foreign key has 2 columns but references 1",
+                "Column with character set latin1 references column with character set utf8mb4",
+                "Column with collation utf8mb4_bin references column with collation utf8mb4_general_ci",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_constraints() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `k` (
+                `a` int NOT NULL CHECK (`a` > 0),
+                `s` varchar(10) CHECK (`s`),
+                `b` int CHECK (`nope` > 0)
+            );";
+        let mut issues = Issues::new(schema_src);
+        parse_schemas(schema_src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        assert_eq!(got, ["Expected type bool got string", "Unknown identifier"]);
+    }
+
+    #[test]
+    fn column_defaults() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `d` (
+                `a` int NOT NULL DEFAULT 0,
+                `b` int NOT NULL DEFAULT '0',
+                `f` tinyint(1) NOT NULL DEFAULT 0,
+                `c` datetime NOT NULL DEFAULT current_timestamp(),
+                `n` int NOT NULL DEFAULT NULL,
+                `e` datetime DEFAULT 1,
+                `p` int DEFAULT NULL COMMENT 'sql-type: not-null-in-practice',
+                `x` int NOT NULL
+            );";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        assert_eq!(
+            got,
+            [
+                "Column is NOT NULL but defaults to NULL",
+                "Default of type integer for column of type datetime"
+            ]
+        );
+        let d = &schema.schemas["d"];
+        assert!(d.get_column("a").is_some_and(|c| c.default));
+        assert!(d.get_column("x").is_some_and(|c| !c.default));
+    }
+
+    #[test]
+    fn default_database() {
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::MariaDB)
+            .database("app");
+        let schema_src = "
+            CREATE TABLE `app`.`t` (`id` int NOT NULL);
+            CREATE TABLE `other`.`t` (`x` text NOT NULL);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        check_no_errors("schema", schema_src, issues.get(), &mut errors);
+
+        let src = "SELECT `app`.`t`.`id`, `other`.`t`.`x` FROM `app`.`t` JOIN `other`.`t`";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("d1", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("d1", &columns, "id:i32!,x:str!", &mut errors);
+        } else {
+            println!("d1 should be select");
+            errors += 1;
+        }
+
+        for src in [
+            "INSERT INTO `app`.`t` (`id`) VALUES (1)",
+            "UPDATE `app`.`t` SET `id` = 2",
+            "DELETE FROM `app`.`t` WHERE `id` = 1",
+            "TRUNCATE TABLE `app`.`t`",
+        ] {
+            let mut issues = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors("d2", src, issues.get(), &mut errors);
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn identifier_case() {
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::MariaDB)
+            .arguments(SQLArguments::QuestionMark);
+        let schema_src = "CREATE TABLE `Users` (`Id` int NOT NULL PRIMARY KEY, `Name` text);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let mut errors = 0;
+        check_no_errors("schema", schema_src, issues.get(), &mut errors);
+
+        let src = "SELECT `id`, `users`.`name` FROM `users` WHERE `ID` = ?";
+        let mut issues = Issues::new(src);
+        type_statement(&schema, src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        if got.first() != Some(&"Unknown table or view") {
+            println!("i1 unexpected issues {:?}", got);
+            errors += 1;
+        }
+
+        let options = options.ignore_identifier_case(true);
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("i2", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, arguments } = q {
+            check_columns("i2", &columns, "id:i32!,name:str", &mut errors);
+            check_arguments("i2", &arguments, "i", &mut errors);
+        } else {
+            println!("i2 should be select");
+            errors += 1;
+        }
+
+        for src in [
+            "INSERT INTO `USERS` (`id`, `name`) VALUES (?, ?)",
+            "UPDATE `users` SET `NAME` = ? WHERE `users`.`ID` = ?",
+            "DELETE FROM `users` WHERE `id` = ?",
+            "DELETE `USERS` FROM `users` JOIN `Users` AS `u` ON `u`.`id` = `users`.`id`",
+            "SELECT `id` FROM `users` WHERE `id` = ? FOR UPDATE OF `USERS`",
+        ] {
+            let mut issues = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            check_no_errors("i3", src, issues.get(), &mut errors);
+        }
+
+        let src =
+            "SELECT `id`, `u`.`NAME` FROM `users` JOIN `Users` AS `u` USING `ID` WHERE `ID` = ?";
+        let mut issues = Issues::new(src);
+        let (q, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+        check_no_errors("i4", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("i4", &columns, "id:i32!,NAME:str", &mut errors);
+        } else {
+            println!("i4 should be select");
+            errors += 1;
+        }
+        if info.at_most_one_row {
+            println!("i4 should not be at most one row");
+            errors += 1;
+        }
+
+        let src = "SELECT `name` FROM `users` AS `U` WHERE `u`.`ID` = ?";
+        let mut issues = Issues::new(src);
+        let (_, info) = type_statement_with_info(&schema, src, &mut issues, &options);
+        check_no_errors("i5", src, issues.get(), &mut errors);
+        if !info.at_most_one_row {
+            println!("i5 should be at most one row");
+            errors += 1;
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn decimals() {
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::MariaDB)
+            .arguments(SQLArguments::QuestionMark);
+        let schema_src = "
+            CREATE TABLE `o` (
+                `price` numeric(10, 2) NOT NULL,
+                `rate` numeric(5, 4),
+                `qty` int NOT NULL
+            );";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok());
+
+        let src = "SELECT `price` * `qty` AS `a`, `price` + `rate` AS `b`, `price` / 2 AS `c`,
+            SUM(`price`) AS `d`, AVG(`rate`) AS `e`, -`price` AS `f`, `price` + ? AS `g`
+            FROM `o` WHERE `price` > 10";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        assert!(issues.is_ok(), "{:?}", issues.get());
+        let StatementType::Select { columns, .. } = q else {
+            panic!("Expected select")
+        };
+        let got: Vec<_> = columns.iter().map(|c| c.type_.to_string()).collect();
+        assert_eq!(
+            got,
+            [
+                "decimal(20,2) not null",
+                "decimal(13,4)",
+                "decimal(14,6) not null",
+                "decimal(65,2)",
+                "decimal(9,8)",
+                "decimal(10,2) not null",
+                "decimal(10,2)",
+            ]
+        );
+
+        // sql-parse 0.21 only has NUMERIC, columns declared as DECIMAL, DEC or
+        // FIXED are parse errors
+        for type_ in ["decimal(10, 2)", "dec(10, 2)", "fixed(10, 2)"] {
+            let schema_src = alloc::format!("CREATE TABLE `p` (`d` {} NOT NULL);", type_);
+            let mut issues = Issues::new(&schema_src);
+            parse_schemas(&schema_src, &mut issues, &options);
+            assert!(!issues.is_ok(), "{} should not parse", type_);
+        }
+    }
+
+    #[test]
+    fn string_length() {
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::MariaDB)
+            .arguments(SQLArguments::QuestionMark)
+            .warn_string_length(true);
+        let schema_src = "
+            CREATE TABLE `s` (
+                `code` char(2) NOT NULL,
+                `name` varchar(5) NOT NULL,
+                `note` text
+            );";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok());
+        let code = &schema.schemas["s"].get_column("code").unwrap().type_;
+        assert_eq!((code.length, code.fixed_length), (Some(2), true));
+        let note = &schema.schemas["s"].get_column("note").unwrap().type_;
+        assert_eq!((note.length, note.fixed_length), (None, false));
+
+        // The length is not part of the type, but is part of the column
+        let wider_src = schema_src.replace("varchar(5)", "varchar(6)");
+        let mut issues = Issues::new(&wider_src);
+        let wider = parse_schemas(&wider_src, &mut issues, &options);
+        let name = &schema.schemas["s"].get_column("name").unwrap().type_;
+        assert_eq!(name, &wider.schemas["s"].get_column("name").unwrap().type_);
+        assert_ne!(schema.hash_tables(&["s"]), wider.hash_tables(&["s"]));
+        let diff = crate::schema_diff::diff_schemas(&schema, &wider);
+        assert_eq!(diff.tables_changed[0].columns_changed[0].name, "name");
+
+        let src = "INSERT INTO `s` (`code`, `name`, `note`) VALUES ('DK', 'Hans Christian', 'x')";
+        let mut issues = Issues::new(src);
+        type_statement(&schema, src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        assert_eq!(
+            got,
+            ["String of length 14 is longer than the column length 5"]
+        );
+
+        let src = "SELECT `note` FROM `s` WHERE `code` = 'DNK' AND `name` = 'Hans'";
+        let mut issues = Issues::new(src);
+        type_statement(&schema, src, &mut issues, &options);
+        let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+        assert_eq!(
+            got,
+            ["String of length 3 is longer than the column length 2"]
+        );
+
+        // Arguments are given the length of the column they are assigned to
+        let src = "UPDATE `s` SET `name` = ? WHERE `code` = 'DK'";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        assert!(issues.is_ok());
+        let StatementType::Update { arguments } = q else {
+            panic!("Expected update")
+        };
+        assert_eq!(arguments[0].1.length, Some(5));
+    }
+
+    #[test]
+    fn enum_values() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `e` (
+                `state` enum('active', 'closed') NOT NULL,
+                `flags` set('read', 'write', 'admin') NOT NULL
+            );";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok());
+
+        let check = |src: &str| -> Vec<String> {
+            let mut issues = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            issues.get().iter().map(|i| i.message.to_string()).collect()
+        };
+        assert!(
+            check("INSERT INTO `e` (`state`, `flags`) VALUES ('Active', 'read,write')").is_empty()
+        );
+        assert!(
+            check("UPDATE `e` SET `flags` = '' WHERE `state` IN ('active', 'closed')").is_empty()
+        );
+        assert_eq!(
+            check("INSERT INTO `e` (`state`, `flags`) VALUES ('actve', 'read,wrte')"),
+            [
+                "Unknown enum value 'actve', did you mean 'active'",
+                "Unknown set value 'wrte', did you mean 'write'"
+            ]
+        );
+        assert_eq!(
+            check("SELECT `flags` FROM `e` WHERE `state` = 'open'"),
+            ["Unknown enum value 'open'"]
+        );
+    }
+
+    #[test]
+    fn collations() {
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        let schema_src = "
+            CREATE TABLE `c` (
+                `a` varchar(10) NOT NULL,
+                `b` varchar(10) COLLATE utf8mb4_bin NOT NULL,
+                `l` varchar(10) CHARACTER SET latin1 NOT NULL,
+                `n` varchar(10) COLLATE `binary` NOT NULL
+            ) DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok(), "{:?}", issues.get());
+        let c = &schema.schemas["c"];
+        let a = &c.get_column("a").unwrap().type_;
+        assert_eq!(a.charset.as_deref(), Some("utf8mb4"));
+        assert_eq!(a.collation.as_deref(), Some("utf8mb4_general_ci"));
+        let l = &c.get_column("l").unwrap().type_;
+        assert_eq!(l.charset.as_deref(), Some("latin1"));
+        assert_eq!(l.collation, None);
+        let n = &c.get_column("n").unwrap().type_;
+        assert_eq!(n.charset, None);
+        assert_eq!(n.collation.as_deref(), Some("binary"));
+
+        let check = |src: &str| -> Vec<String> {
+            let mut issues = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            issues.get().iter().map(|i| i.message.to_string()).collect()
+        };
+        assert!(check("SELECT `a` FROM `c` WHERE `a` = 'x' AND `a` = `l`").is_empty());
+        assert_eq!(
+            check("SELECT `a` FROM `c` WHERE LOWER(`a`) = `b`"),
+            ["Comparison of strings with different collations"]
+        );
+        assert_eq!(
+            check("SELECT `a` FROM `c` UNION SELECT `b` FROM `c`"),
+            ["Union of strings with different collations"]
+        );
+    }
+
+    #[test]
+    fn bit_and_tinyint() {
+        let schema_src = "
+            CREATE TABLE `b` (
+                `flag` tinyint(1) NOT NULL,
+                `on` bit(1) NOT NULL,
+                `mask` bit(12) NOT NULL,
+                `wide` bit(40)
+            );";
+        let types = |options: &TypeOptions| -> Vec<String> {
+            let mut issues = Issues::new(schema_src);
+            let schema = parse_schemas(schema_src, &mut issues, options);
+            assert!(issues.is_ok());
+            schema.schemas["b"]
+                .columns
+                .iter()
+                .map(|c| c.type_.to_string())
+                .collect()
+        };
+        let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+        assert_eq!(
+            types(&options),
+            ["bool not null", "bool not null", "u16 not null", "u64"]
+        );
+        assert_eq!(
+            types(&options.tinyint_as_bool(false)),
+            ["i8 not null", "u8 not null", "u16 not null", "u64"]
+        );
+    }
+
+    #[test]
+    fn uuids() {
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::PostgreSQL)
+            .arguments(SQLArguments::Dollar)
+            .warn_uuid_string_comparison(true);
+        let schema_src = "CREATE TABLE u (id uuid NOT NULL, name text NOT NULL);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok());
+
+        let check = |src: &str| -> Vec<String> {
+            let mut issues = Issues::new(src);
+            type_statement(&schema, src, &mut issues, &options);
+            issues.get().iter().map(|i| i.message.to_string()).collect()
+        };
+        assert!(check("INSERT INTO u (id, name) VALUES (gen_random_uuid(), 'a')").is_empty());
+        assert!(check(
+            "SELECT name FROM u WHERE id = 'A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11' OR id = $1"
+        )
+        .is_empty());
+        assert_eq!(
+            check("SELECT name FROM u WHERE id = 'a0eebc99'"),
+            ["Invalid uuid"]
+        );
+        assert_eq!(
+            check("SELECT name FROM u WHERE id = name"),
+            ["Comparison of uuid with string"]
+        );
+
+        let src = "SELECT id FROM u";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        let mut errors = 0;
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("u", &columns, "id:uuid!", &mut errors);
+        }
+        assert_eq!(errors, 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_schemas() {
+        use crate::{schema::Schemas, CustomType, CustomTypeRef};
+        use alloc::{format, sync::Arc};
+
+        let options = TypeOptions::new()
+            .dialect(SQLDialect::MariaDB)
+            .arguments(SQLArguments::QuestionMark);
+        let schema_src = "
+            CREATE TABLE `s` (
+                `id` int NOT NULL AUTO_INCREMENT PRIMARY KEY,
+                `a` int,
+                `b` int AS (`a` + 1),
+                `e` enum('x', 'y') NOT NULL);
+            CREATE UNIQUE INDEX `s_a` ON `s` (`a`);
+            CREATE TABLE `other`.`t` (`v` varchar(10));";
+        let mut issues = Issues::new(schema_src);
+        let mut schema = parse_schemas(schema_src, &mut issues, &options);
+        assert!(issues.is_ok());
+        schema.add_function("f", alloc::vec![], FullType::new(Type::U64, true), false);
+
+        let json = serde_json::to_string(&schema).unwrap();
+        let loaded: Schemas<'_> = serde_json::from_str(&json).unwrap();
+        assert_eq!(schema.hash_tables(&["s"]), loaded.hash_tables(&["s"]));
+        for src in [
+            "SELECT *, f() AS `h` FROM `s` USE INDEX (`s_a`) WHERE `id` = ?",
+            "SELECT `v` FROM `other`.`t`",
+            "INSERT INTO `s` (`a`, `b`, `e`) VALUES (?, ?, 'x')",
+        ] {
+            let mut issues = Issues::new(src);
+            let q = type_statement(&schema, src, &mut issues, &options);
+            let mut loaded_issues = Issues::new(src);
+            let l = type_statement(&loaded, src, &mut loaded_issues, &options);
+            assert_eq!(format!("{:?}", q), format!("{:?}", l));
+            assert_eq!(
+                format!("{:?}", issues.get()),
+                format!("{:?}", loaded_issues.get())
+            );
+        }
+
+        #[derive(Debug)]
+        struct Citext;
+
+        impl CustomType for Citext {
+            fn name(&self) -> &str {
+                "citext"
+            }
+
+            fn base(&self) -> BaseType {
+                BaseType::String
+            }
+        }
+
+        let t = FullType::new(Type::Custom(CustomTypeRef(Arc::new(Citext))), true);
+        let json = serde_json::to_string(&t).unwrap();
+        let loaded: FullType<'_> = serde_json::from_str(&json).unwrap();
+        assert_eq!(t, loaded);
+        assert_eq!(loaded.to_string(), "citext not null");
+    }
+
+    #[test]
+    fn dual() {
+        let mut errors = 0;
+        for (dialect, schema_src) in [
+            (
+                SQLDialect::MariaDB,
+                "CREATE TABLE `dual` (`id` int NOT NULL);",
+            ),
+            (
+                SQLDialect::PostgreSQL,
+                "CREATE TABLE \"dual\" (id int NOT NULL);",
+            ),
+        ] {
+            let options = TypeOptions::new().dialect(dialect.clone());
+            let mut issues = Issues::new(schema_src);
+            parse_schemas(schema_src, &mut issues, &options);
+            let got: Vec<_> = issues.get().iter().map(|i| &*i.message).collect();
+            if got != ["Table name collides with the DUAL pseudo table"] {
+                println!("{:?} unexpected issues {:?}", dialect, got);
+                errors += 1;
+            }
+        }
+
+        // The table can still be selected from
+        let options = TypeOptions::new();
+        let schema_src = "CREATE TABLE `dual` (`id` int NOT NULL);";
+        let mut issues = Issues::new(schema_src);
+        let schema = parse_schemas(schema_src, &mut issues, &options);
+        let src = "SELECT `id` FROM `dual`";
+        let mut issues = Issues::new(src);
+        let q = type_statement(&schema, src, &mut issues, &options);
+        check_no_errors("dual", src, issues.get(), &mut errors);
+        if let StatementType::Select { columns, .. } = q {
+            check_columns("dual", &columns, "id:i32!", &mut errors);
+        } else {
+            println!("dual should be select");
+            errors += 1;
+        }
+
+        if errors != 0 {
+            panic!("{} errors in test", errors);
+        }
+    }
+
+    #[test]
+    fn quoting() {
+        use crate::quote::{quote_identifier, quote_string, unquote_identifier, unquote_string};
+        for dialect in [SQLDialect::MariaDB, SQLDialect::PostgreSQL] {
+            for v in ["plain", "a`b\"c", "it's", "back\\slash", "nul\0byte", ""] {
+                let q = quote_identifier(&dialect, v);
+                assert_eq!(unquote_identifier(&dialect, &q).as_deref(), Some(v));
+                let q = quote_string(&dialect, v);
+                assert_eq!(unquote_string(&dialect, &q).as_deref(), Some(v));
+            }
+            assert_eq!(unquote_string(&dialect, "'a'b'"), None);
         }
     }
 }
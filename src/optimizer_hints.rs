@@ -0,0 +1,324 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of optimizer hints given in `/*+ ... */` comments
+//!
+//! The parser treats hints as comments, so they never influence typing.
+//! When enabled the table and index names mentioned in hints are checked
+//! against the tables of the statement and the schemas.
+
+use alloc::vec::Vec;
+use sql_parse::{Identifier, Issues, Span, Spanned, Statement, TableReference};
+
+use crate::{
+    schema::{find_table, IndexKey, Schemas},
+    TypeOptions,
+};
+
+/// Hints taking a table followed by a list of indices on that table
+const INDEX_HINTS: &[&str] = &[
+    "INDEX",
+    "NO_INDEX",
+    "JOIN_INDEX",
+    "NO_JOIN_INDEX",
+    "GROUP_INDEX",
+    "NO_GROUP_INDEX",
+    "ORDER_INDEX",
+    "NO_ORDER_INDEX",
+    "INDEX_MERGE",
+    "NO_INDEX_MERGE",
+    "MRR",
+    "NO_MRR",
+    "NO_ICP",
+    "NO_RANGE_OPTIMIZATION",
+    "SKIP_SCAN",
+    "NO_SKIP_SCAN",
+];
+
+/// Hints taking a list of tables
+const TABLE_HINTS: &[&str] = &[
+    "BKA",
+    "NO_BKA",
+    "BNL",
+    "NO_BNL",
+    "HASH_JOIN",
+    "NO_HASH_JOIN",
+    "MERGE",
+    "NO_MERGE",
+    "DERIVED_CONDITION_PUSHDOWN",
+    "NO_DERIVED_CONDITION_PUSHDOWN",
+    "JOIN_FIXED_ORDER",
+    "JOIN_ORDER",
+    "JOIN_PREFIX",
+    "JOIN_SUFFIX",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Name(&'a str),
+    At,
+    Open,
+    Close,
+    Other,
+}
+
+/// Split the body of a hint comment into tokens with their spans
+fn tokenize(src: &str, start: usize, end: usize) -> Vec<(Token<'_>, Span)> {
+    let bytes = src.as_bytes();
+    let mut ans = Vec::new();
+    let mut i = start;
+    while i < end {
+        let c = bytes[i];
+        match c {
+            b' ' | b'\t' | b'\r' | b'\n' => i += 1,
+            b'(' => {
+                ans.push((Token::Open, i..i + 1));
+                i += 1;
+            }
+            b')' => {
+                ans.push((Token::Close, i..i + 1));
+                i += 1;
+            }
+            b'@' => {
+                ans.push((Token::At, i..i + 1));
+                i += 1;
+            }
+            b'`' | b'"' | b'\'' => {
+                let s = i;
+                i += 1;
+                while i < end && bytes[i] != c {
+                    i += 1;
+                }
+                let v = &src[s + 1..i];
+                i = (i + 1).min(end);
+                if c == b'\'' {
+                    ans.push((Token::Other, s..i));
+                } else {
+                    ans.push((Token::Name(v), s..i));
+                }
+            }
+            c if c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80 => {
+                let s = i;
+                while i < end
+                    && (bytes[i].is_ascii_alphanumeric()
+                        || bytes[i] == b'_'
+                        || bytes[i] == b'$'
+                        || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                ans.push((Token::Name(&src[s..i]), s..i));
+            }
+            _ => {
+                ans.push((Token::Other, i..i + 1));
+                i += 1;
+            }
+        }
+    }
+    ans
+}
+
+/// Find the spans of the bodies of all `/*+ ... */` comments in the part of
+/// src given by span
+fn hint_bodies(src: &str, span: Span) -> Vec<(usize, usize)> {
+    let src = &src[..span.end];
+    let bytes = src.as_bytes();
+    let mut ans = Vec::new();
+    let mut i = span.start;
+    while i < bytes.len() {
+        match bytes[i] {
+            q @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != q {
+                    if bytes[i] == b'\\' && q != b'`' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let start = i + 2;
+                let end = src[start..]
+                    .find("*/")
+                    .map(|v| v + start)
+                    .unwrap_or(bytes.len());
+                if bytes.get(start) == Some(&b'+') {
+                    ans.push((start + 1, end));
+                }
+                i = end + 2;
+            }
+            _ => i += 1,
+        }
+    }
+    ans
+}
+
+/// Collect the names tables can be referred to by in hints, together with
+/// the schema table they name if any
+fn collect_references<'a>(
+    reference: &TableReference<'a>,
+    out: &mut Vec<(&'a str, Option<&'a str>)>,
+) {
+    match reference {
+        TableReference::Table {
+            identifier, as_, ..
+        } => {
+            let table = identifier.identifier.value;
+            out.push((as_.as_ref().map_or(table, |v| v.value), Some(table)));
+        }
+        TableReference::Query { as_, query, .. } => {
+            if let Some(as_) = as_ {
+                out.push((as_.value, None));
+            }
+            collect_statement(query, out);
+        }
+        TableReference::Join { left, right, .. } => {
+            collect_references(left, out);
+            collect_references(right, out);
+        }
+    }
+}
+
+fn collect_statement<'a>(statement: &Statement<'a>, out: &mut Vec<(&'a str, Option<&'a str>)>) {
+    match statement {
+        Statement::Select(s) => {
+            for r in s.table_references.iter().flatten() {
+                collect_references(r, out);
+            }
+        }
+        Statement::Update(u) => {
+            for r in &u.tables {
+                collect_references(r, out);
+            }
+        }
+        Statement::Delete(d) => {
+            for t in &d.tables {
+                out.push((t.identifier.value, Some(t.identifier.value)));
+            }
+            for r in &d.using {
+                collect_references(r, out);
+            }
+        }
+        Statement::InsertReplace(i) => {
+            out.push((i.table.identifier.value, Some(i.table.identifier.value)));
+            if let Some(s) = &i.select {
+                for r in s.table_references.iter().flatten() {
+                    collect_references(r, out);
+                }
+            }
+        }
+        Statement::Union(u) => {
+            collect_statement(&u.left, out);
+            for w in &u.with {
+                collect_statement(&w.union_statement, out);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Warn about tables and indices named in optimizer hints that do not exist
+pub(crate) fn check_optimizer_hints<'a>(
+    schemas: &Schemas<'a>,
+    statement: &Statement<'a>,
+    src: &'a str,
+    options: &TypeOptions,
+    issues: &mut Issues<'a>,
+) {
+    let bodies = hint_bodies(src, statement.span());
+    if bodies.is_empty() {
+        return;
+    }
+    let mut references = Vec::new();
+    collect_statement(statement, &mut references);
+
+    for (start, end) in bodies {
+        let tokens = tokenize(src, start, end);
+        let mut i = 0;
+        while i < tokens.len() {
+            let (Token::Name(name), _) = tokens[i] else {
+                i += 1;
+                continue;
+            };
+            i += 1;
+            if tokens.get(i).map(|v| v.0) != Some(Token::Open) {
+                continue;
+            }
+            i += 1;
+            // Collect the arguments up to the matching close paren
+            let mut args = Vec::new();
+            let mut depth = 1;
+            while i < tokens.len() && depth != 0 {
+                match tokens[i].0 {
+                    Token::Open => depth += 1,
+                    Token::Close => depth -= 1,
+                    Token::At => {
+                        // Skip query block names like @qb1 and t1@qb1
+                        i += 1;
+                    }
+                    Token::Name(v) if depth == 1 => args.push((v, tokens[i].1.clone())),
+                    _ => (),
+                }
+                i += 1;
+            }
+            let upper = name.to_ascii_uppercase();
+            let (tables, indices) = if INDEX_HINTS.contains(&upper.as_str()) {
+                args.split_at(args.len().min(1))
+            } else if TABLE_HINTS.contains(&upper.as_str()) {
+                (&args[..], &[][..])
+            } else {
+                continue;
+            };
+            for (table, span) in tables {
+                let schema_table = match references
+                    .iter()
+                    .find(|(n, _)| options.same_name(n, table))
+                {
+                    Some((_, t)) => *t,
+                    None if find_table(&schemas.schemas, table, options).is_some() => Some(*table),
+                    None => {
+                        issues.warn("Unknown table in optimizer hint", span);
+                        continue;
+                    }
+                };
+                let Some((schema_table, _)) =
+                    schema_table.and_then(|t| find_table(&schemas.schemas, t, options))
+                else {
+                    continue;
+                };
+                for (index, span) in indices {
+                    if index.eq_ignore_ascii_case("PRIMARY") {
+                        continue;
+                    }
+                    let key = IndexKey {
+                        table: Some(schema_table.clone()),
+                        index: Identifier::new(index, span.clone()),
+                    };
+                    if !schemas.indices.contains_key(&key) {
+                        issues.warn("Unknown index in optimizer hint", span);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,445 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{boxed::Box, vec::Vec};
+use sql_parse::{
+    BinaryOperator, Expression, IdentifierPart, JoinSpecification, JoinType, Select, Span, Spanned,
+    Statement, TableReference,
+};
+
+use crate::{
+    aggregate::contains_aggregate,
+    schema::{find_table, Schemas},
+    TypeOptions,
+};
+
+/// Kind of join in a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Inner, cross, straight and natural joins
+    Inner,
+    /// Left outer join
+    Left,
+    /// Right outer join
+    Right,
+}
+
+/// A node in the logical plan of a select
+///
+/// Expressions are given by their span in the statement
+#[derive(Debug, Clone)]
+pub enum PlanNode<'a> {
+    /// Scan of a table or view with the WHERE conjuncts only referring to it
+    Scan {
+        /// Name of the table or view
+        table: &'a str,
+        /// Alias of the table if any
+        alias: Option<&'a str>,
+        /// Conjuncts of the WHERE clause that can be applied while scanning
+        predicates: Vec<Span>,
+    },
+    /// A derived table given by a subquery
+    Derived {
+        /// Alias of the derived table if any
+        alias: Option<&'a str>,
+        /// Plan of the subquery
+        input: Box<PlanNode<'a>>,
+    },
+    /// Join of two inputs
+    Join {
+        kind: JoinKind,
+        left: Box<PlanNode<'a>>,
+        right: Box<PlanNode<'a>>,
+        /// The ON condition if any
+        condition: Option<Span>,
+    },
+    /// A select without any tables
+    Empty,
+    /// Filtering by the WHERE conjuncts that could not be pushed to a scan
+    Filter {
+        predicates: Vec<Span>,
+        input: Box<PlanNode<'a>>,
+    },
+    /// Grouping and aggregation
+    Aggregate {
+        group_by: Vec<Span>,
+        having: Option<Span>,
+        input: Box<PlanNode<'a>>,
+    },
+    /// Ordering of the rows
+    Sort {
+        order_by: Vec<Span>,
+        input: Box<PlanNode<'a>>,
+    },
+    /// Limiting the number of rows
+    Limit {
+        limit: Span,
+        input: Box<PlanNode<'a>>,
+    },
+    /// Union of the rows of the inputs
+    Union { inputs: Vec<PlanNode<'a>> },
+}
+
+struct ScanRef<'a> {
+    name: &'a str,
+    table: &'a str,
+    nullable: bool,
+}
+
+pub(crate) fn split_and<'e, 'a>(e: &'e Expression<'a>, out: &mut Vec<&'e Expression<'a>>) {
+    match e {
+        Expression::Binary {
+            op: BinaryOperator::And,
+            lhs,
+            rhs,
+            ..
+        } => {
+            split_and(lhs, out);
+            split_and(rhs, out);
+        }
+        e => out.push(e),
+    }
+}
+
+/// Call f on each direct subexpression of e, not descending into subqueries
+pub(crate) fn for_each_child<'e, 'a>(
+    e: &'e Expression<'a>,
+    f: &mut impl FnMut(&'e Expression<'a>),
+) {
+    match e {
+        Expression::Binary { lhs, rhs, .. } => {
+            f(lhs);
+            f(rhs);
+        }
+        Expression::Unary { operand, .. } => f(operand),
+        Expression::Function(_, args, _) => args.iter().for_each(f),
+        Expression::WindowFunction {
+            args, window_spec, ..
+        } => {
+            args.iter().for_each(&mut *f);
+            window_spec.order_by.1.iter().for_each(|(e, _)| f(e));
+        }
+        Expression::In { lhs, rhs, .. } => {
+            f(lhs);
+            rhs.iter().for_each(f);
+        }
+        Expression::Is(e, _, _)
+        | Expression::Cast { expr: e, .. }
+        | Expression::Count { expr: e, .. }
+        | Expression::GroupConcat { expr: e, .. } => f(e),
+        Expression::Case {
+            value,
+            whens,
+            else_,
+            ..
+        } => {
+            if let Some(v) = value {
+                f(v);
+            }
+            for w in whens {
+                f(&w.when);
+                f(&w.then);
+            }
+            if let Some((_, e)) = else_ {
+                f(e);
+            }
+        }
+        Expression::Subquery(_)
+        | Expression::Exists(_)
+        | Expression::Identifier(_)
+        | Expression::Null(_)
+        | Expression::Bool(_, _)
+        | Expression::String(_)
+        | Expression::Integer(_)
+        | Expression::ListHack(_)
+        | Expression::Float(_)
+        | Expression::Arg(_)
+        | Expression::Invalid(_)
+        | Expression::Variable { .. } => (),
+    }
+}
+
+/// Collect the column references of an expression, returns false if the
+/// expression contains a subquery
+pub(crate) fn collect_identifiers<'e, 'a>(
+    e: &'e Expression<'a>,
+    out: &mut Vec<&'e [IdentifierPart<'a>]>,
+) -> bool {
+    match e {
+        Expression::Identifier(parts) => {
+            out.push(parts);
+            true
+        }
+        Expression::Subquery(_) | Expression::Exists(_) => false,
+        e => {
+            let mut ok = true;
+            for_each_child(e, &mut |c| ok &= collect_identifiers(c, out));
+            ok
+        }
+    }
+}
+
+/// Collect the subqueries of an expression, without descending into them
+pub(crate) fn collect_subqueries<'e, 'a>(e: &'e Expression<'a>, out: &mut Vec<&'e Statement<'a>>) {
+    match e {
+        Expression::Subquery(s) | Expression::Exists(s) => out.push(s),
+        e => for_each_child(e, &mut |c| collect_subqueries(c, out)),
+    }
+}
+
+/// Collect the subqueries of the select list, join conditions, WHERE and
+/// HAVING of a select. Derived tables in the FROM clause are not included
+pub(crate) fn select_subqueries<'e, 'a>(s: &'e Select<'a>, out: &mut Vec<&'e Statement<'a>>) {
+    fn reference<'e, 'a>(r: &'e TableReference<'a>, out: &mut Vec<&'e Statement<'a>>) {
+        if let TableReference::Join {
+            left,
+            right,
+            specification,
+            ..
+        } = r
+        {
+            reference(left, out);
+            reference(right, out);
+            if let Some(JoinSpecification::On(e, _)) = specification {
+                collect_subqueries(e, out);
+            }
+        }
+    }
+    for e in &s.select_exprs {
+        collect_subqueries(&e.expr, out);
+    }
+    for r in s.table_references.iter().flatten() {
+        reference(r, out);
+    }
+    if let Some((e, _)) = &s.where_ {
+        collect_subqueries(e, out);
+    }
+    if let Some((e, _)) = &s.having {
+        collect_subqueries(e, out);
+    }
+}
+
+fn plan_reference<'a>(
+    schemas: &Schemas<'a>,
+    options: &TypeOptions,
+    reference: &TableReference<'a>,
+    nullable: bool,
+    scans: &mut Vec<ScanRef<'a>>,
+) -> PlanNode<'a> {
+    match reference {
+        TableReference::Table {
+            identifier, as_, ..
+        } => {
+            let table = identifier.identifier.value;
+            let alias = as_.as_ref().map(|v| v.value);
+            scans.push(ScanRef {
+                name: alias.unwrap_or(table),
+                table,
+                nullable,
+            });
+            PlanNode::Scan {
+                table,
+                alias,
+                predicates: Vec::new(),
+            }
+        }
+        TableReference::Query { query, as_, .. } => PlanNode::Derived {
+            alias: as_.as_ref().map(|v| v.value),
+            input: Box::new(plan_statement(schemas, query, options)),
+        },
+        TableReference::Join {
+            join,
+            left,
+            right,
+            specification,
+        } => {
+            let (kind, left_nullable, right_nullable) = match join {
+                JoinType::Left(_) | JoinType::NaturalLeft(_) => (JoinKind::Left, nullable, true),
+                JoinType::Right(_) | JoinType::NaturalRight(_) => (JoinKind::Right, true, nullable),
+                _ => (JoinKind::Inner, nullable, nullable),
+            };
+            let left = plan_reference(schemas, options, left, left_nullable, scans);
+            let right = plan_reference(schemas, options, right, right_nullable, scans);
+            PlanNode::Join {
+                kind,
+                left: Box::new(left),
+                right: Box::new(right),
+                condition: match specification {
+                    Some(JoinSpecification::On(e, _)) => Some(e.span()),
+                    _ => None,
+                },
+            }
+        }
+    }
+}
+
+/// Find the name of the only scan referred to by the predicate if any
+fn single_scan<'a>(
+    schemas: &Schemas<'a>,
+    options: &TypeOptions,
+    scans: &[ScanRef<'a>],
+    predicate: &Expression<'a>,
+) -> Option<&'a str> {
+    let mut identifiers = Vec::new();
+    if !collect_identifiers(predicate, &mut identifiers) {
+        return None;
+    }
+    let mut ans: Option<&'a str> = None;
+    for parts in identifiers {
+        let scan = match parts {
+            [IdentifierPart::Name(t), IdentifierPart::Name(_)] => {
+                scans.iter().find(|s| options.same_name(s.name, t.value))?
+            }
+            [IdentifierPart::Name(c)] => {
+                let mut found = scans.iter().filter(|s| {
+                    find_table(&schemas.schemas, s.table, options)
+                        .is_some_and(|(_, t)| t.find_column(c.value, options).is_some())
+                });
+                let scan = found.next()?;
+                if found.next().is_some() {
+                    return None;
+                }
+                scan
+            }
+            _ => return None,
+        };
+        if scan.nullable || ans.is_some_and(|a| a != scan.name) {
+            return None;
+        }
+        ans = Some(scan.name);
+    }
+    ans
+}
+
+/// Push the predicate to the scan named name
+fn push_predicate(node: &mut PlanNode<'_>, name: &str, predicate: Span) -> Result<(), Span> {
+    match node {
+        PlanNode::Scan {
+            table,
+            alias,
+            predicates,
+        } if alias.unwrap_or(table) == name => {
+            predicates.push(predicate);
+            Ok(())
+        }
+        PlanNode::Join { left, right, .. } => {
+            push_predicate(left, name, predicate).or_else(|p| push_predicate(right, name, p))
+        }
+        _ => Err(predicate),
+    }
+}
+
+fn plan_select<'a>(
+    schemas: &Schemas<'a>,
+    options: &TypeOptions,
+    select: &Select<'a>,
+) -> PlanNode<'a> {
+    let mut scans = Vec::new();
+    let mut node = None;
+    for reference in select.table_references.iter().flatten() {
+        let right = plan_reference(schemas, options, reference, false, &mut scans);
+        node = Some(match node {
+            None => right,
+            Some(left) => PlanNode::Join {
+                kind: JoinKind::Inner,
+                left: Box::new(left),
+                right: Box::new(right),
+                condition: None,
+            },
+        });
+    }
+    let mut node = node.unwrap_or(PlanNode::Empty);
+
+    if let Some((where_, _)) = &select.where_ {
+        let mut conjuncts = Vec::new();
+        split_and(where_, &mut conjuncts);
+        let mut remaining = Vec::new();
+        for c in conjuncts {
+            let pushed = match single_scan(schemas, options, &scans, c) {
+                Some(name) => push_predicate(&mut node, name, c.span()),
+                None => Err(c.span()),
+            };
+            if let Err(span) = pushed {
+                remaining.push(span);
+            }
+        }
+        if !remaining.is_empty() {
+            node = PlanNode::Filter {
+                predicates: remaining,
+                input: Box::new(node),
+            };
+        }
+    }
+
+    let aggregates = select
+        .select_exprs
+        .iter()
+        .any(|e| contains_aggregate(&e.expr));
+    if aggregates || select.group_by.is_some() || select.having.is_some() {
+        node = PlanNode::Aggregate {
+            group_by: select
+                .group_by
+                .iter()
+                .flat_map(|(_, g)| g.iter().map(|e| e.span()))
+                .collect(),
+            having: select.having.as_ref().map(|(e, _)| e.span()),
+            input: Box::new(node),
+        };
+    }
+
+    if let Some((_, order_by)) = &select.order_by {
+        node = PlanNode::Sort {
+            order_by: order_by.iter().map(|(e, _)| e.span()).collect(),
+            input: Box::new(node),
+        };
+    }
+
+    if let Some((span, _, _)) = &select.limit {
+        node = PlanNode::Limit {
+            limit: span.clone(),
+            input: Box::new(node),
+        };
+    }
+    node
+}
+
+/// Build the plan of a select or union
+pub(crate) fn plan_statement<'a>(
+    schemas: &Schemas<'a>,
+    statement: &Statement<'a>,
+    options: &TypeOptions,
+) -> PlanNode<'a> {
+    match statement {
+        Statement::Select(s) => plan_select(schemas, options, s),
+        Statement::Union(u) => {
+            let mut inputs = alloc::vec![plan_statement(schemas, &u.left, options)];
+            for w in &u.with {
+                inputs.push(plan_statement(schemas, &w.union_statement, options));
+            }
+            let mut node = PlanNode::Union { inputs };
+            if let Some((_, order_by)) = &u.order_by {
+                node = PlanNode::Sort {
+                    order_by: order_by.iter().map(|(e, _)| e.span()).collect(),
+                    input: Box::new(node),
+                };
+            }
+            if let Some((span, _, _)) = &u.limit {
+                node = PlanNode::Limit {
+                    limit: span.clone(),
+                    input: Box::new(node),
+                };
+            }
+            node
+        }
+        _ => PlanNode::Empty,
+    }
+}
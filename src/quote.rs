@@ -0,0 +1,138 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quoting of identifiers and string literals for use in generated SQL
+//!
+//! ```
+//! use sql_type::{quote::{quote_identifier, quote_string, unquote_identifier}, SQLDialect};
+//! assert_eq!(quote_identifier(&SQLDialect::MariaDB, "my`table"), "`my``table`");
+//! assert_eq!(quote_identifier(&SQLDialect::PostgreSQL, "my\"table"), "\"my\"\"table\"");
+//! assert_eq!(quote_string(&SQLDialect::MariaDB, "it's a \\"), "'it''s a \\\\'");
+//! assert_eq!(quote_string(&SQLDialect::PostgreSQL, "it's a \\"), "'it''s a \\'");
+//! assert_eq!(
+//!     unquote_identifier(&SQLDialect::MariaDB, "`my``table`").as_deref(),
+//!     Some("my`table")
+//! );
+//! ```
+
+use alloc::{borrow::Cow, string::String};
+use sql_parse::SQLDialect;
+
+fn identifier_quote(dialect: &SQLDialect) -> char {
+    match dialect {
+        SQLDialect::MariaDB => '`',
+        SQLDialect::PostgreSQL => '"',
+    }
+}
+
+/// Quote an identifier so that it can be used in a statement of the given dialect
+pub fn quote_identifier(dialect: &SQLDialect, name: &str) -> String {
+    let q = identifier_quote(dialect);
+    let mut ans = String::with_capacity(name.len() + 2);
+    ans.push(q);
+    for c in name.chars() {
+        if c == q {
+            ans.push(q);
+        }
+        ans.push(c);
+    }
+    ans.push(q);
+    ans
+}
+
+/// Remove quotes from an identifier quoted for the given dialect
+///
+/// Returns None if the identifier is not correctly quoted
+pub fn unquote_identifier(dialect: &SQLDialect, quoted: &str) -> Option<String> {
+    let q = identifier_quote(dialect);
+    let inner = quoted.strip_prefix(q)?.strip_suffix(q)?;
+    let mut ans = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == q && chars.next() != Some(q) {
+            return None;
+        }
+        ans.push(c);
+    }
+    Some(ans)
+}
+
+/// Normalize an unquoted identifier the way the dialect does when
+/// looking it up, PostgreSQL folds unquoted identifiers to lower case
+pub fn normalize_identifier<'a>(dialect: &SQLDialect, name: &'a str) -> Cow<'a, str> {
+    match dialect {
+        SQLDialect::PostgreSQL if name.chars().any(|c| c.is_uppercase()) => {
+            Cow::Owned(name.to_lowercase())
+        }
+        _ => Cow::Borrowed(name),
+    }
+}
+
+/// Quote a string literal so that it can be used in a statement of the given dialect
+///
+/// For MariaDB backslashes are escaped as well, as they start escape sequences
+/// unless NO_BACKSLASH_ESCAPES is set
+pub fn quote_string(dialect: &SQLDialect, value: &str) -> String {
+    let mut ans = String::with_capacity(value.len() + 2);
+    ans.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => ans.push_str("''"),
+            '\\' if dialect.is_maria() => ans.push_str("\\\\"),
+            '\0' if dialect.is_maria() => ans.push_str("\\0"),
+            c => ans.push(c),
+        }
+    }
+    ans.push('\'');
+    ans
+}
+
+/// Remove quotes and escapes from a string literal quoted for the given dialect
+///
+/// Returns None if the literal is not correctly quoted
+pub fn unquote_string(dialect: &SQLDialect, quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('\'')?.strip_suffix('\'')?;
+    if !dialect.is_maria() {
+        let mut ans = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\'' && chars.next() != Some('\'') {
+                return None;
+            }
+            ans.push(c);
+        }
+        return Some(ans);
+    }
+    let mut ans = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                if chars.next() != Some('\'') {
+                    return None;
+                }
+                ans.push('\'');
+            }
+            '\\' => match chars.next()? {
+                '0' => ans.push('\0'),
+                'n' => ans.push('\n'),
+                'r' => ans.push('\r'),
+                't' => ans.push('\t'),
+                'b' => ans.push('\x08'),
+                'Z' => ans.push('\x1a'),
+                c => ans.push(c),
+            },
+            c => ans.push(c),
+        }
+    }
+    Some(ans)
+}
@@ -83,27 +83,48 @@
 //! ```
 
 use crate::{
-    type_::{BaseType, FullType},
+    plan::select_subqueries,
+    type_::{BaseType, FullType, SizeClass},
     type_statement,
     typer::unqualified_name,
     Type, TypeOptions,
 };
-use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
-use sql_parse::{parse_statements, DataType, Expression, Identifier, Issues, Span, Spanned};
+use alloc::{borrow::Cow, collections::BTreeMap, format, sync::Arc, vec::Vec};
+use core::hash::{Hash, Hasher};
+use sql_parse::{
+    parse_statements, CreateView, DataType, Expression, Identifier, IndexCol, Issues, OptSpanned,
+    SString, Span, Spanned, Statement, TableOption, TableReference,
+};
 
 /// A column in a schema
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Column<'a> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier"))]
     pub identifier: Identifier<'a>,
     /// Type of the column
     pub type_: FullType<'a>,
+    /// True if the column is declared NOT NULL. The type of the column is
+    /// also not null for columns annotated as not-null-in-practice
+    pub declared_not_null: bool,
     /// True if the column is auto_increment
     pub auto_increment: bool,
+    /// True if the column has a default value or is generated as an identity
+    pub default: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::generated"))]
     pub as_: Option<alloc::boxed::Box<Expression<'a>>>,
+    /// Semantic tag given by a `sql-type: semantic=...` column comment
+    pub semantic: Option<Cow<'a, str>>,
+    /// True if the column is part of the shard key of the table, given by a
+    /// `sql-type: shard-key` column comment
+    pub shard_key: bool,
 }
 
 /// Schema representing a table or view
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Schema<'a> {
     /// Span of identifier
     pub identifier_span: Span,
@@ -111,6 +132,114 @@ pub struct Schema<'a> {
     pub columns: Vec<Column<'a>>,
     /// True if this is a view instead of a table
     pub view: bool,
+    /// Sets of columns with a primary or unique key
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier_lists"))]
+    pub unique_keys: Vec<Vec<Identifier<'a>>>,
+}
+
+/// Hasher used for hashing schemas, the hash is stable between runs
+struct SchemaHasher(u64);
+
+impl core::hash::Hasher for SchemaHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // FNV-1a
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl<'a> Schemas<'a> {
+    /// Register a user defined function, so that calls to it can be typed
+    ///
+    /// ```
+    /// use sql_type::{
+    ///     schema::parse_schemas, type_statement, BaseType, FullType, Issues, Type, TypeOptions,
+    /// };
+    /// let options = TypeOptions::new();
+    /// let mut issues = Issues::new("");
+    /// let mut schemas = parse_schemas("", &mut issues, &options);
+    /// schemas.add_function(
+    ///     "my_hash",
+    ///     vec![BaseType::String.into()],
+    ///     FullType::new(Type::U64, true),
+    ///     false,
+    /// );
+    ///
+    /// let sql = "SELECT my_hash('a')";
+    /// let mut issues = Issues::new(sql);
+    /// type_statement(&schemas, sql, &mut issues, &options);
+    /// assert!(issues.is_ok());
+    /// ```
+    pub fn add_function(
+        &mut self,
+        name: &'a str,
+        arguments: Vec<Type<'a>>,
+        return_type: FullType<'a>,
+        variadic: bool,
+    ) {
+        self.functions.insert(
+            Identifier::new(name, 0..0),
+            Functions {
+                arguments,
+                return_type,
+                variadic,
+            },
+        );
+    }
+
+    /// Find a function by name ignoring case
+    pub fn get_function(&self, name: &str) -> Option<&Functions<'a>> {
+        self.functions
+            .iter()
+            .find(|(k, _)| k.value.eq_ignore_ascii_case(name))
+            .map(|(_, f)| f)
+    }
+
+    /// Names of the indices on the given table
+    pub fn table_indices<'s>(&'s self, table: &'s str) -> impl Iterator<Item = &'s str> + 's {
+        self.indices
+            .keys()
+            .filter(move |k| k.table.as_ref().is_some_and(|t| t.value == table))
+            .map(|k| k.index.value)
+    }
+
+    /// Compute a hash of the definitions of the given tables and views and
+    /// of their indices
+    pub fn hash_tables(&self, tables: &[&str]) -> u64 {
+        let mut h = SchemaHasher(0xcbf29ce484222325);
+        for table in tables {
+            table.hash(&mut h);
+            if let Some(schema) = self.schemas.get(*table) {
+                schema.view.hash(&mut h);
+                for column in &schema.columns {
+                    column.identifier.value.hash(&mut h);
+                    column.type_.hash_declaration(&mut h);
+                    column.declared_not_null.hash(&mut h);
+                    column.auto_increment.hash(&mut h);
+                    column.default.hash(&mut h);
+                    column.shard_key.hash(&mut h);
+                }
+                for key in &schema.unique_keys {
+                    for column in key {
+                        column.value.hash(&mut h);
+                    }
+                    key.len().hash(&mut h);
+                }
+            }
+            for key in self.indices.keys() {
+                if key.table.as_ref().map(|t| t.value) == Some(*table) {
+                    key.index.value.hash(&mut h);
+                }
+            }
+        }
+        h.finish()
+    }
 }
 
 impl<'a> Schema<'a> {
@@ -124,44 +253,142 @@ impl<'a> Schema<'a> {
             .iter_mut()
             .find(|column| column.identifier.value == identifier)
     }
+
+    /// Find a column by name, ignoring case if the options say so
+    pub(crate) fn find_column(
+        &self,
+        identifier: &str,
+        options: &TypeOptions,
+    ) -> Option<&Column<'a>> {
+        self.columns
+            .iter()
+            .find(|column| options.same_name(column.identifier.value, identifier))
+    }
+}
+
+/// Find a table by name, ignoring case if the options say so
+pub(crate) fn find_table<'s, 'a>(
+    tables: &'s BTreeMap<Identifier<'a>, Schema<'a>>,
+    name: &str,
+    options: &TypeOptions,
+) -> Option<(&'s Identifier<'a>, &'s Schema<'a>)> {
+    tables.get_key_value(name).or_else(|| {
+        tables
+            .iter()
+            .find(|(id, _)| options.same_name(id.value, name))
+    })
 }
 
 /// A procedure
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Procedure {}
 
 /// A function
-#[derive(Debug)]
-pub struct Functions {}
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Functions<'a> {
+    /// Types of the arguments
+    pub arguments: Vec<Type<'a>>,
+    /// Type returned by the function
+    pub return_type: FullType<'a>,
+    /// The last argument may be repeated any number of times
+    pub variadic: bool,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct IndexKey<'a> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialize::optional_identifier")
+    )]
     pub table: Option<Identifier<'a>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier"))]
     pub index: Identifier<'a>,
 }
 
 /// A description of tables, view, procedures and function in a schemas definition file
+///
+/// With the `serde` feature schemas can be serialized, to be loaded again
+/// without parsing the schema definitions. The identifiers of deserialized
+/// schemas borrow from the serialized data, and the expressions of generated
+/// columns are not kept
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "'de: 'a")))]
 pub struct Schemas<'a> {
     /// Map from name to Tables or views
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier_map"))]
     pub schemas: BTreeMap<Identifier<'a>, Schema<'a>>,
+    /// Map from database name to the tables created in that database, by
+    /// `CREATE TABLE database.table`
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::database_map"))]
+    pub databases: BTreeMap<Identifier<'a>, BTreeMap<Identifier<'a>, Schema<'a>>>,
     /// Map from name to procedure
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier_map"))]
     pub procedures: BTreeMap<Identifier<'a>, Procedure>,
     /// Map from name to function
-    pub functions: BTreeMap<Identifier<'a>, Functions>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::identifier_map"))]
+    pub functions: BTreeMap<Identifier<'a>, Functions<'a>>,
     /// Map from (table, index) to location
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::pairs"))]
     pub indices: BTreeMap<IndexKey<'a>, Span>,
 }
 
+/// Annotations given in a column comment on the form
+/// `sql-type: semantic=email, not-null-in-practice, shard-key`
+#[derive(Default)]
+struct ColumnAnnotations<'a> {
+    semantic: Option<Cow<'a, str>>,
+    not_null: bool,
+    shard_key: bool,
+}
+
+fn parse_column_annotations<'a>(
+    comment: &SString<'a>,
+    issues: &mut Issues<'a>,
+) -> ColumnAnnotations<'a> {
+    let mut ans = ColumnAnnotations::default();
+    let Some((_, annotations)) = comment.value.split_once("sql-type:") else {
+        return ans;
+    };
+    for annotation in annotations
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+    {
+        match annotation.split_once('=') {
+            Some(("semantic", v)) if !v.is_empty() => {
+                ans.semantic = Some(Cow::Owned(v.into()));
+            }
+            None if annotation == "not-null-in-practice" => ans.not_null = true,
+            None if annotation == "shard-key" => ans.shard_key = true,
+            _ => {
+                issues.warn(
+                    alloc::format!("Unknown sql-type annotation '{}'", annotation),
+                    comment,
+                );
+            }
+        }
+    }
+    ans
+}
+
 pub(crate) fn parse_column<'a>(
     data_type: DataType<'a>,
     identifier: Identifier<'a>,
-    _issues: &mut Issues<'a>,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
 ) -> Column<'a> {
     let mut not_null = false;
     let mut unsigned = false;
     let mut auto_increment = false;
+    let mut default = false;
     let mut _as = None;
+    let mut annotations = ColumnAnnotations::default();
+    let mut charset = None;
+    let mut collation = None;
     for p in data_type.properties {
         match p {
             sql_parse::DataTypeProperty::Signed(_) => unsigned = false,
@@ -169,13 +396,48 @@ pub(crate) fn parse_column<'a>(
             sql_parse::DataTypeProperty::Null(_) => not_null = false,
             sql_parse::DataTypeProperty::NotNull(_) => not_null = true,
             sql_parse::DataTypeProperty::AutoIncrement(_) => auto_increment = true,
+            sql_parse::DataTypeProperty::Default(_)
+            | sql_parse::DataTypeProperty::GeneratedAlways(_) => default = true,
             sql_parse::DataTypeProperty::As((_, e)) => _as = Some(e),
-            _ => {} // TODO default,
+            // sql-parse reports COLLATE as a character set, so the keyword
+            // before the name tells them apart
+            sql_parse::DataTypeProperty::Charset(c) if after_collate(issues.src, &c) => {
+                collation = Some(Cow::Borrowed(c.value))
+            }
+            sql_parse::DataTypeProperty::Charset(c) => charset = Some(Cow::Borrowed(c.value)),
+            sql_parse::DataTypeProperty::Collate(c) => collation = Some(Cow::Borrowed(c.value)),
+            sql_parse::DataTypeProperty::Comment(c) => {
+                annotations = parse_column_annotations(&c, issues)
+            }
+            _ => {}
         }
     }
+    let precision = match &data_type.type_ {
+        sql_parse::Type::DateTime(w) | sql_parse::Type::Time(w) => w.as_ref(),
+        sql_parse::Type::Timestamp(t) => t.width.as_ref(),
+        _ => None,
+    }
+    .map(|(w, _)| *w as u8);
+    let (length, fixed_length) = match &data_type.type_ {
+        // CHAR without a length holds one character
+        sql_parse::Type::Char(w) => (Some(w.as_ref().map_or(1, |(w, _)| *w)), true),
+        sql_parse::Type::VarChar(w)
+        | sql_parse::Type::TinyText(w)
+        | sql_parse::Type::MediumText(w)
+        | sql_parse::Type::Text(w)
+        | sql_parse::Type::LongText(w) => (w.as_ref().map(|(w, _)| *w), false),
+        _ => (None, false),
+    };
+    let size_class = match &data_type.type_ {
+        sql_parse::Type::TinyText(_) | sql_parse::Type::TinyBlob(_) => Some(SizeClass::Tiny),
+        sql_parse::Type::Text(_) | sql_parse::Type::Blob(_) => Some(SizeClass::Regular),
+        sql_parse::Type::MediumText(_) | sql_parse::Type::MediumBlob(_) => Some(SizeClass::Medium),
+        sql_parse::Type::LongText(_) | sql_parse::Type::LongBlob(_) => Some(SizeClass::Long),
+        _ => None,
+    };
     let type_ = match data_type.type_ {
         sql_parse::Type::TinyInt(v) => {
-            if !unsigned && matches!(v, Some((1, _))) {
+            if !unsigned && matches!(v, Some((1, _))) && !options.tinyint_as_integer {
                 BaseType::Bool.into()
             } else if unsigned {
                 Type::U8
@@ -227,12 +489,38 @@ pub(crate) fn parse_column<'a>(
         sql_parse::Type::Boolean => BaseType::Bool.into(),
         sql_parse::Type::Integer(_) => BaseType::Integer.into(),
         sql_parse::Type::Float8 => BaseType::Float.into(),
-        sql_parse::Type::Numeric(_, _, _) => todo!("Numeric"),
+        sql_parse::Type::Numeric(p, s, _) => {
+            let s = s.min(30);
+            Type::Decimal(p.clamp(s, 65) as u8, s as u8)
+        }
         sql_parse::Type::Timestamptz => BaseType::TimeStamp.into(),
         sql_parse::Type::Json => BaseType::String.into(),
-        sql_parse::Type::Bit(_, _) => BaseType::Bytes.into(),
+        // Bit strings of PostgreSQL may be of any length
+        sql_parse::Type::Bit(_, _) if options.parse_options.get_dialect().is_postgresql() => {
+            BaseType::Bytes.into()
+        }
+        // Bit values are read as the smallest unsigned integer holding them
+        sql_parse::Type::Bit(w, _) => match w {
+            1 if !options.tinyint_as_integer => BaseType::Bool.into(),
+            0..=8 => Type::U8,
+            9..=16 => Type::U16,
+            17..=32 => Type::U32,
+            33..=64 => Type::U64,
+            _ => BaseType::Bytes.into(),
+        },
         sql_parse::Type::Bytea => BaseType::Bytes.into(),
-        sql_parse::Type::Named(_) => BaseType::String.into(), // TODO lookup name??
+        sql_parse::Type::Named(span) => {
+            let name = issues.segment(span);
+            match options
+                .custom_types
+                .iter()
+                .find(|c| c.0.name().eq_ignore_ascii_case(name))
+            {
+                Some(c) => Type::Custom(c.clone()),
+                None if name.eq_ignore_ascii_case("uuid") => Type::Uuid,
+                None => BaseType::String.into(),
+            }
+        }
         sql_parse::Type::Inet4 => BaseType::String.into(),
         sql_parse::Type::Inet6 => BaseType::String.into(),
     };
@@ -241,11 +529,583 @@ pub(crate) fn parse_column<'a>(
         identifier,
         type_: FullType {
             t: type_,
-            not_null,
+            not_null: not_null || annotations.not_null,
             list_hack: false,
+            precision,
+            length: length.map(|l| l.min(u32::MAX as usize) as u32),
+            fixed_length,
+            charset,
+            collation,
+            size_class,
         },
+        declared_not_null: not_null,
         auto_increment,
+        default,
         as_: _as,
+        semantic: annotations.semantic,
+        shard_key: annotations.shard_key,
+    }
+}
+
+/// Is the column defined as a primary or unique key by itself
+fn is_unique(data_type: &DataType<'_>) -> bool {
+    data_type.properties.iter().any(|p| {
+        matches!(
+            p,
+            sql_parse::DataTypeProperty::PrimaryKey(_)
+                | sql_parse::DataTypeProperty::Unique(_)
+                | sql_parse::DataTypeProperty::UniqueKey(_)
+        )
+    })
+}
+
+/// Build the schema of a table from the definitions of its columns
+pub(crate) fn table_schema<'a>(
+    id: &Identifier<'a>,
+    create_definitions: Vec<sql_parse::CreateDefinition<'a>>,
+    table_options: &[TableOption<'a>],
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) -> Schema<'a> {
+    let mut schema = Schema {
+        view: false,
+        identifier_span: id.span.clone(),
+        columns: Default::default(),
+        unique_keys: Default::default(),
+    };
+    let mut checks = Vec::new();
+    let mut defaults = Vec::new();
+    for d in create_definitions {
+        match d {
+            sql_parse::CreateDefinition::ColumnDefinition {
+                identifier,
+                data_type,
+            } => {
+                let unique = is_unique(&data_type);
+                for p in &data_type.properties {
+                    match p {
+                        sql_parse::DataTypeProperty::Check((_, e)) => checks.push(e.clone()),
+                        sql_parse::DataTypeProperty::Default(e) => {
+                            defaults.push((identifier.clone(), e.clone()))
+                        }
+                        _ => (),
+                    }
+                }
+                let column = parse_column(data_type, identifier.clone(), issues, options);
+                if let Some(oc) = schema.get_column(column.identifier.value) {
+                    issues
+                        .err("Column already defined", &identifier)
+                        .frag("Defined here", &oc.identifier);
+                } else {
+                    if unique {
+                        schema.unique_keys.push(alloc::vec![identifier]);
+                    }
+                    schema.columns.push(column);
+                }
+            }
+            sql_parse::CreateDefinition::ConstraintDefinition { .. } => {}
+        }
+    }
+    apply_table_collation(&mut schema, table_options);
+    type_column_expressions(id, &schema, &checks, &defaults, issues, options);
+    schema
+}
+
+/// Give the string columns declared without a character set or collation
+/// the defaults of the table
+fn apply_table_collation<'a>(schema: &mut Schema<'a>, table_options: &[TableOption<'a>]) {
+    let mut charset = None;
+    let mut collation = None;
+    for o in table_options {
+        match o {
+            TableOption::CharSet { value, .. } | TableOption::DefaultCharSet { value, .. } => {
+                charset = Some(value.value)
+            }
+            TableOption::Collate { value, .. } | TableOption::DefaultCollate { value, .. } => {
+                collation = Some(value.value)
+            }
+            _ => (),
+        }
+    }
+    if charset.is_none() && collation.is_none() {
+        return;
+    }
+    for c in &mut schema.columns {
+        let t = &mut c.type_;
+        if t.base() != BaseType::String || t.collation.is_some() {
+            continue;
+        }
+        match &t.charset {
+            None => {
+                t.charset = charset.map(Cow::Borrowed);
+                t.collation = collation.map(Cow::Borrowed);
+            }
+            // The collation of the table only applies to its character set
+            Some(c) => {
+                if let Some(collation) = collation.filter(|v| {
+                    v.split('_')
+                        .next()
+                        .is_some_and(|v| v.eq_ignore_ascii_case(c))
+                }) {
+                    t.collation = Some(Cow::Borrowed(collation));
+                }
+            }
+        }
+    }
+}
+
+/// Type the CHECK constraints and the DEFAULT values of the columns of a table
+/// against its columns
+fn type_column_expressions<'a>(
+    id: &Identifier<'a>,
+    schema: &Schema<'a>,
+    checks: &[alloc::boxed::Box<Expression<'a>>],
+    defaults: &[(Identifier<'a>, alloc::boxed::Box<Expression<'a>>)],
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) {
+    if checks.is_empty() && defaults.is_empty() {
+        return;
+    }
+    let dummy_schemas = Schemas::default();
+    let mut typer = crate::typer::Typer {
+        schemas: &dummy_schemas,
+        issues,
+        reference_types: alloc::vec![crate::typer::ReferenceType {
+            name: Some(id.clone()),
+            database: None,
+            span: schema.identifier_span.clone(),
+            columns: schema
+                .columns
+                .iter()
+                .map(|c| (c.identifier.clone(), c.type_.clone()))
+                .collect(),
+            using: Vec::new(),
+        }],
+        arg_types: Default::default(),
+        options,
+        with_schemas: Default::default(),
+        info: &mut Default::default(),
+        grouped: false,
+    };
+    for e in checks {
+        let t = crate::type_expression::type_expression(
+            &mut typer,
+            e,
+            crate::type_expression::ExpressionFlags::default(),
+            BaseType::Bool,
+        );
+        typer.ensure_base(e, &t, BaseType::Bool);
+    }
+    for (identifier, e) in defaults {
+        let Some(column) = schema.get_column(identifier.value) else {
+            continue;
+        };
+        let t = crate::type_expression::type_expression(
+            &mut typer,
+            e,
+            crate::type_expression::ExpressionFlags::default(),
+            column.type_.base(),
+        );
+        if t.t == Type::Null {
+            if column.declared_not_null {
+                typer.err("Column is NOT NULL but defaults to NULL", &**e);
+            }
+            continue;
+        }
+        // Quoted defaults are converted to the type of the column, boolean
+        // columns are commonly given 0 or 1 and timestamps the current time
+        let time = [BaseType::DateTime, BaseType::TimeStamp];
+        if matches!(**e, Expression::String(_))
+            || (column.type_.base() == BaseType::Bool && t.base() == BaseType::Integer)
+            || (time.contains(&column.type_.base()) && time.contains(&t.base()))
+        {
+            continue;
+        }
+        if typer.matched_type(&t.t, &column.type_.t).is_none() {
+            typer
+                .err(
+                    format!(
+                        "Default of type {} for column of type {}",
+                        t.t, column.type_.t
+                    ),
+                    &**e,
+                )
+                .frag("Column defined here", identifier);
+        }
+    }
+}
+
+/// Is the possibly quoted identifier in src directly preceded by the COLLATE
+/// keyword
+fn after_collate(src: &str, identifier: &Identifier<'_>) -> bool {
+    let before = src[..identifier.span.start]
+        .trim_end_matches(['`', '"'])
+        .trim_end()
+        .as_bytes();
+    before.len() >= 7 && before[before.len() - 7..].eq_ignore_ascii_case(b"collate")
+}
+
+/// Warn about tables and views named like the DUAL pseudo table, in every
+/// dialect as such names are not portable
+pub(crate) fn check_reserved_table_name(issues: &mut Issues<'_>, id: &Identifier<'_>) {
+    if id.value.eq_ignore_ascii_case("dual") {
+        issues.warn("Table name collides with the DUAL pseudo table", id);
+    }
+}
+
+/// The tables and views named in the FROM clauses of a statement and its
+/// subqueries
+fn view_dependencies<'a>(statement: &Statement<'a>, out: &mut Vec<&'a str>) {
+    fn reference<'a>(r: &TableReference<'a>, out: &mut Vec<&'a str>) {
+        match r {
+            TableReference::Table { identifier, .. } => {
+                if identifier.prefix.is_empty() {
+                    out.push(identifier.identifier.value);
+                }
+            }
+            TableReference::Query { query, .. } => view_dependencies(query, out),
+            TableReference::Join { left, right, .. } => {
+                reference(left, out);
+                reference(right, out);
+            }
+        }
+    }
+    match statement {
+        Statement::Select(s) => {
+            for r in s.table_references.iter().flatten() {
+                reference(r, out);
+            }
+            let mut subqueries = Vec::new();
+            select_subqueries(s, &mut subqueries);
+            for q in subqueries {
+                view_dependencies(q, out);
+            }
+        }
+        Statement::Union(u) => {
+            view_dependencies(&u.left, out);
+            for w in &u.with {
+                view_dependencies(&w.union_statement, out);
+            }
+        }
+        Statement::WithQuery(w) => {
+            for b in &w.with_blocks {
+                view_dependencies(&b.statement, out);
+            }
+            view_dependencies(&w.statement, out);
+        }
+        _ => (),
+    }
+}
+
+/// Type the select of a view and add the view to the schemas
+fn add_view<'a>(
+    schemas: &mut Schemas<'a>,
+    v: CreateView<'a>,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) {
+    let mut replace = false;
+    let mut schema = Schema {
+        view: true,
+        identifier_span: v.name.span(),
+        columns: Default::default(),
+        unique_keys: Default::default(),
+    };
+    for o in v.create_options {
+        match o {
+            sql_parse::CreateOption::OrReplace(_) => {
+                replace = true;
+            }
+            sql_parse::CreateOption::Temporary(s) => {
+                issues.err("Not supported", &s);
+            }
+            sql_parse::CreateOption::Unique(s) => {
+                issues.err("Not supported", &s);
+            }
+            sql_parse::CreateOption::Algorithm(_, _) => {}
+            sql_parse::CreateOption::Definer { .. } => {}
+            sql_parse::CreateOption::SqlSecurityDefiner(_, _) => {}
+            sql_parse::CreateOption::SqlSecurityUser(_, _) => {}
+        }
+    }
+
+    {
+        let mut typer: crate::typer::Typer<'a, '_> = crate::typer::Typer {
+            schemas,
+            issues,
+            reference_types: Vec::new(),
+            arg_types: Default::default(),
+            options,
+            with_schemas: Default::default(),
+            info: &mut Default::default(),
+            grouped: false,
+        };
+
+        let t = type_statement::type_statement(&mut typer, &v.select);
+        let s = if let type_statement::InnerStatementType::Select(s) = t {
+            s
+        } else {
+            issues.err("Not supported", &v.select.span());
+            return;
+        };
+
+        for column in s.columns {
+            //let column: crate::SelectTypeColumn<'a> = column;
+            let name = column.name.unwrap();
+
+            schema.columns.push(Column {
+                identifier: name,
+                type_: column.type_,
+                declared_not_null: false,
+                auto_increment: false,
+                default: false,
+                as_: None,
+                semantic: None,
+                shard_key: false,
+            });
+        }
+    }
+
+    let id = unqualified_name(issues, &v.name);
+    check_reserved_table_name(issues, id);
+    match schemas.schemas.entry(id.clone()) {
+        alloc::collections::btree_map::Entry::Occupied(mut e) => {
+            if replace {
+                e.insert(schema);
+            } else if v.if_not_exists.is_none() {
+                issues
+                    .err("View already defined", &v.name)
+                    .frag("Defined here", &e.get().identifier_span);
+            }
+        }
+        alloc::collections::btree_map::Entry::Vacant(e) => {
+            e.insert(schema);
+        }
+    }
+}
+
+/// Add the views that were defined before the views they depend on, once
+/// their dependencies are. Views depending on themselves through other views
+/// are reported and left out
+fn add_pending_views<'a>(
+    schemas: &mut Schemas<'a>,
+    mut pending: Vec<(Vec<&'a str>, CreateView<'a>)>,
+    issues: &mut Issues<'a>,
+    options: &TypeOptions,
+) {
+    /// Is the view named target reached from the pending view idx
+    fn reaches(
+        pending: &[(Vec<&str>, CreateView<'_>)],
+        idx: usize,
+        target: &str,
+        visited: &mut Vec<usize>,
+    ) -> bool {
+        if visited.contains(&idx) {
+            return false;
+        }
+        visited.push(idx);
+        pending[idx].0.iter().any(|d| {
+            *d == target
+                || pending
+                    .iter()
+                    .position(|(_, v)| v.name.identifier.value == *d)
+                    .is_some_and(|i| reaches(pending, i, target, visited))
+        })
+    }
+
+    let is_ready = |pending: &[(Vec<&'a str>, CreateView<'a>)], deps: &[&'a str]| {
+        deps.iter()
+            .all(|d| !pending.iter().any(|(_, v)| v.name.identifier.value == *d))
+    };
+    loop {
+        while let Some(idx) = pending.iter().position(|(d, _)| is_ready(&pending, d)) {
+            let (_, v) = pending.remove(idx);
+            add_view(schemas, v, issues, options);
+        }
+        let cyclic: Vec<usize> = (0..pending.len())
+            .filter(|i| {
+                reaches(
+                    &pending,
+                    *i,
+                    pending[*i].1.name.identifier.value,
+                    &mut Vec::new(),
+                )
+            })
+            .collect();
+        if cyclic.is_empty() {
+            break;
+        }
+        for idx in cyclic.into_iter().rev() {
+            let (_, v) = pending.remove(idx);
+            issues.err("View depends on itself", &v.name);
+        }
+    }
+}
+
+/// A foreign key added to a table
+struct ForeignKey<'a> {
+    database: Option<Identifier<'a>>,
+    table: Identifier<'a>,
+    cols: Vec<IndexCol<'a>>,
+    references_table: Identifier<'a>,
+    references_cols: Vec<Identifier<'a>>,
+}
+
+/// Check that the columns of foreign keys exist and have the types of the
+/// columns they reference. Tables are looked up in the final schema, as
+/// constraints are commonly added after all tables are created
+fn check_foreign_keys<'a>(
+    schemas: &Schemas<'a>,
+    foreign_keys: &[ForeignKey<'a>],
+    issues: &mut Issues<'a>,
+) {
+    for fk in foreign_keys {
+        let tables = match &fk.database {
+            Some(database) => match schemas.databases.get(database.value) {
+                Some(tables) => tables,
+                None => continue,
+            },
+            None => &schemas.schemas,
+        };
+        let Some(table) = tables.get(fk.table.value) else {
+            continue;
+        };
+        let Some(references) = tables.get(fk.references_table.value) else {
+            issues.err("Unknown referenced table", &fk.references_table);
+            continue;
+        };
+        if references.view {
+            issues
+                .err("Foreign keys can not reference views", &fk.references_table)
+                .frag("View defined here", &references.identifier_span);
+            continue;
+        }
+        if fk.cols.len() != fk.references_cols.len() {
+            issues.err(
+                format!(
+                    "Foreign key has {} columns but references {}",
+                    fk.cols.len(),
+                    fk.references_cols.len()
+                ),
+                &fk.references_cols
+                    .opt_span()
+                    .unwrap_or(fk.references_table.span()),
+            );
+            continue;
+        }
+        for (col, reference) in fk.cols.iter().zip(&fk.references_cols) {
+            let Some(c) = table.get_column(col.name.value) else {
+                issues.err("Unknown column", &col.name);
+                continue;
+            };
+            let Some(r) = references.get_column(reference.value) else {
+                issues.err("Unknown referenced column", reference);
+                continue;
+            };
+            if c.type_.t != r.type_.t {
+                issues
+                    .err(
+                        format!(
+                            "Column of type {} references column of type {}",
+                            c.type_.t, r.type_.t
+                        ),
+                        &col.name,
+                    )
+                    .frag("Referenced column", &r.identifier);
+            } else if let Some((a, b)) = differing(&c.type_.charset, &r.type_.charset) {
+                issues
+                    .err(
+                        format!(
+                            "Column with character set {} references column with character set {}",
+                            a, b
+                        ),
+                        &col.name,
+                    )
+                    .frag("Referenced column", &r.identifier);
+            } else if let Some((a, b)) = differing(&c.type_.collation, &r.type_.collation) {
+                issues
+                    .err(
+                        format!(
+                            "Column with collation {} references column with collation {}",
+                            a, b
+                        ),
+                        &col.name,
+                    )
+                    .frag("Referenced column", &r.identifier);
+            }
+        }
+    }
+}
+
+/// The two character sets or collations if both are known and they differ
+fn differing<'c>(
+    a: &'c Option<Cow<'_, str>>,
+    b: &'c Option<Cow<'_, str>>,
+) -> Option<(&'c str, &'c str)> {
+    match (a, b) {
+        (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b) => Some((a, b)),
+        _ => None,
+    }
+}
+
+/// Several schema sources, such as the files of a migrations directory,
+/// joined so they can be parsed as one schema
+///
+/// Each source must end its last statement with a semicolon. Issues are
+/// reported with spans into the joined source, [SchemaSources::locate] finds
+/// the source and the span within it.
+///
+/// ```
+/// use sql_type::{schema::{parse_schemas, SchemaSources}, Issues, SQLDialect, TypeOptions};
+/// let sources = SchemaSources::new([
+///     ("002_posts.sql", "CREATE TABLE `posts` (`id` int NOT NULL);"),
+///     ("001_users.sql", "CREATE TABLE `users` (`id` int NOT NULL);"),
+///     ("003_users.sql", "CREATE TABLE `users` (`id` int NOT NULL);"),
+/// ]);
+/// let mut issues = Issues::new(sources.src());
+/// let schemas = parse_schemas(
+///     sources.src(),
+///     &mut issues,
+///     &TypeOptions::new().dialect(SQLDialect::MariaDB),
+/// );
+/// assert_eq!(schemas.schemas.len(), 2);
+/// let issue = &issues.get()[0];
+/// assert_eq!(&issue.message, "Table already defined");
+/// assert_eq!(sources.locate(&issue.span), Some(("003_users.sql", 13..20)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SchemaSources<'n> {
+    src: alloc::string::String,
+    sources: Vec<(&'n str, Span)>,
+}
+
+impl<'n> SchemaSources<'n> {
+    /// Join the sources given as names and contents, ordered by name
+    pub fn new<'s>(sources: impl IntoIterator<Item = (&'n str, &'s str)>) -> Self {
+        let mut sources: Vec<_> = sources.into_iter().collect();
+        sources.sort_by_key(|(name, _)| *name);
+        let mut ans = Self::default();
+        for (name, src) in sources {
+            let start = ans.src.len();
+            ans.src.push_str(src);
+            ans.sources.push((name, start..ans.src.len()));
+            ans.src.push('\n');
+        }
+        ans
+    }
+
+    /// The joined source to parse
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    /// The name of the source containing the span, and the span within it
+    pub fn locate(&self, span: &Span) -> Option<(&'n str, Span)> {
+        self.sources
+            .iter()
+            .find(|(_, s)| s.start <= span.start && span.end <= s.end)
+            .map(|(name, s)| (*name, span.start - s.start..span.end - s.start))
     }
 }
 
@@ -274,23 +1134,30 @@ pub fn parse_schemas<'a>(
 
     let mut schemas = Schemas {
         schemas: Default::default(),
+        databases: Default::default(),
         procedures: Default::default(),
         functions: Default::default(),
         indices: Default::default(),
     };
+    let mut pending_views: Vec<(Vec<&'a str>, CreateView<'a>)> = Vec::new();
+    let mut foreign_keys = Vec::new();
 
     for statement in statements {
         match statement {
             sql_parse::Statement::CreateTable(t) => {
                 let mut replace = false;
 
-                let id = unqualified_name(issues, &t.identifier);
-
-                let mut schema = Schema {
-                    view: false,
-                    identifier_span: id.span.clone(),
-                    columns: Default::default(),
+                let database = match t.identifier.prefix.as_slice() {
+                    [(database, _)] if !options.is_default_database(database.value) => {
+                        Some(database.clone())
+                    }
+                    _ => None,
                 };
+                let id = match t.identifier.prefix.as_slice() {
+                    [_] => &t.identifier.identifier,
+                    _ => unqualified_name(issues, &t.identifier),
+                };
+                check_reserved_table_name(issues, id);
 
                 for o in t.create_options {
                     match o {
@@ -310,25 +1177,12 @@ pub fn parse_schemas<'a>(
                     }
                 }
                 // TODO: do we care about table options
-                for d in t.create_definitions {
-                    match d {
-                        sql_parse::CreateDefinition::ColumnDefinition {
-                            identifier,
-                            data_type,
-                        } => {
-                            let column = parse_column(data_type, identifier.clone(), issues);
-                            if let Some(oc) = schema.get_column(column.identifier.value) {
-                                issues
-                                    .err("Column already defined", &identifier)
-                                    .frag("Defined here", &oc.identifier);
-                            } else {
-                                schema.columns.push(column);
-                            }
-                        }
-                        sql_parse::CreateDefinition::ConstraintDefinition { .. } => {}
-                    }
-                }
-                match schemas.schemas.entry(id.clone()) {
+                let schema = table_schema(id, t.create_definitions, &t.options, issues, options);
+                let tables = match database {
+                    Some(database) => schemas.databases.entry(database).or_default(),
+                    None => &mut schemas.schemas,
+                };
+                match tables.entry(id.clone()) {
                     alloc::collections::btree_map::Entry::Occupied(mut e) => {
                         if replace {
                             e.insert(schema);
@@ -344,78 +1198,36 @@ pub fn parse_schemas<'a>(
                 }
             }
             sql_parse::Statement::CreateView(v) => {
-                let mut replace = false;
-                let mut schema = Schema {
-                    view: true,
-                    identifier_span: v.name.span(),
-                    columns: Default::default(),
-                };
-                for o in v.create_options {
-                    match o {
-                        sql_parse::CreateOption::OrReplace(_) => {
-                            replace = true;
-                        }
-                        sql_parse::CreateOption::Temporary(s) => {
-                            issues.err("Not supported", &s);
-                        }
-                        sql_parse::CreateOption::Unique(s) => {
-                            issues.err("Not supported", &s);
-                        }
-                        sql_parse::CreateOption::Algorithm(_, _) => {}
-                        sql_parse::CreateOption::Definer { .. } => {}
-                        sql_parse::CreateOption::SqlSecurityDefiner(_, _) => {}
-                        sql_parse::CreateOption::SqlSecurityUser(_, _) => {}
-                    }
-                }
-
+                // A view defined again while pending replaces the pending view
+                if let Some(p) = pending_views
+                    .iter()
+                    .position(|(_, p)| p.name.identifier.value == v.name.identifier.value)
                 {
-                    let mut typer: crate::typer::Typer<'a, '_> = crate::typer::Typer {
-                        schemas: &schemas,
-                        issues,
-                        reference_types: Vec::new(),
-                        arg_types: Default::default(),
-                        options,
-                        with_schemas: Default::default(),
-                    };
-
-                    let t = type_statement::type_statement(&mut typer, &v.select);
-                    let s = if let type_statement::InnerStatementType::Select(s) = t {
-                        s
+                    if v.create_options
+                        .iter()
+                        .any(|o| matches!(o, sql_parse::CreateOption::OrReplace(_)))
+                    {
+                        pending_views.remove(p);
                     } else {
-                        issues.err("Not supported", &v.select.span());
-                        continue;
-                    };
-
-                    for column in s.columns {
-                        //let column: crate::SelectTypeColumn<'a> = column;
-                        let name = column.name.unwrap();
-
-                        schema.columns.push(Column {
-                            identifier: name,
-                            type_: column.type_,
-                            auto_increment: false,
-                            as_: None,
-                        });
-                    }
-                }
-
-                match schemas
-                    .schemas
-                    .entry(unqualified_name(issues, &v.name).clone())
-                {
-                    alloc::collections::btree_map::Entry::Occupied(mut e) => {
-                        if replace {
-                            e.insert(schema);
-                        } else if v.if_not_exists.is_none() {
+                        if v.if_not_exists.is_none() {
                             issues
                                 .err("View already defined", &v.name)
-                                .frag("Defined here", &e.get().identifier_span);
+                                .frag("Defined here", &pending_views[p].1.name);
                         }
-                    }
-                    alloc::collections::btree_map::Entry::Vacant(e) => {
-                        e.insert(schema);
+                        continue;
                     }
                 }
+                // Views on views not yet defined are typed once those are
+                let mut dependencies = Vec::new();
+                view_dependencies(&v.select, &mut dependencies);
+                if dependencies
+                    .iter()
+                    .any(|d| !schemas.schemas.contains_key(*d))
+                {
+                    pending_views.push((dependencies, v));
+                } else {
+                    add_view(&mut schemas, v, issues, options);
+                }
             }
             sql_parse::Statement::CreateTrigger(_) => {}
             // sql_parse::Statement::CreateFunction(_) => todo!(),
@@ -485,6 +1297,13 @@ pub fn parse_schemas<'a>(
             sql_parse::Statement::DropTrigger(_) => {}
             sql_parse::Statement::DropView(v) => {
                 for i in v.views {
+                    if let Some(p) = pending_views
+                        .iter()
+                        .position(|(_, p)| p.name.identifier.value == i.identifier.value)
+                    {
+                        pending_views.remove(p);
+                        continue;
+                    }
                     match schemas.schemas.entry(unqualified_name(issues, &i).clone()) {
                         alloc::collections::btree_map::Entry::Occupied(e) => {
                             if !e.get().view {
@@ -505,19 +1324,29 @@ pub fn parse_schemas<'a>(
             }
             sql_parse::Statement::Set(_) => {}
             sql_parse::Statement::AlterTable(a) => {
-                let e = match schemas
-                    .schemas
-                    .entry(unqualified_name(issues, &a.table).clone())
-                {
-                    alloc::collections::btree_map::Entry::Occupied(e) => {
-                        let e = e.into_mut();
+                let database = match a.table.prefix.as_slice() {
+                    [(database, _)] if !options.is_default_database(database.value) => {
+                        Some(database.clone())
+                    }
+                    _ => None,
+                };
+                let id = match a.table.prefix.as_slice() {
+                    [_] => a.table.identifier.clone(),
+                    _ => unqualified_name(issues, &a.table).clone(),
+                };
+                let tables = match &database {
+                    Some(database) => schemas.databases.get_mut(database),
+                    None => Some(&mut schemas.schemas),
+                };
+                let e = match tables.and_then(|tables| tables.get_mut(&id)) {
+                    Some(e) => {
                         if e.view {
                             issues.err("Cannot alter view", &a.table);
                             continue;
                         }
                         e
                     }
-                    alloc::collections::btree_map::Entry::Vacant(_) => {
+                    None => {
                         if a.if_exists.is_none() {
                             issues.err("Table not found", &a.table);
                         }
@@ -527,6 +1356,7 @@ pub fn parse_schemas<'a>(
                 for s in a.alter_specifications {
                     match s {
                         sql_parse::AlterSpecification::AddIndex {
+                            index_type,
                             if_not_exists,
                             name,
                             cols,
@@ -539,6 +1369,13 @@ pub fn parse_schemas<'a>(
                                         .frag("Table defined here", &a.table);
                                 }
                             }
+                            if matches!(
+                                index_type,
+                                sql_parse::IndexType::Primary(_) | sql_parse::IndexType::Unique(_)
+                            ) {
+                                e.unique_keys
+                                    .push(cols.iter().map(|c| c.name.clone()).collect());
+                            }
 
                             if let Some(name) = &name {
                                 let ident = if options.parse_options.get_dialect().is_postgresql() {
@@ -565,7 +1402,18 @@ pub fn parse_schemas<'a>(
                                 }
                             }
                         }
-                        sql_parse::AlterSpecification::AddForeignKey { .. } => {}
+                        sql_parse::AlterSpecification::AddForeignKey {
+                            cols,
+                            references_table,
+                            references_cols,
+                            ..
+                        } => foreign_keys.push(ForeignKey {
+                            database: database.clone(),
+                            table: id.clone(),
+                            cols,
+                            references_table,
+                            references_cols,
+                        }),
                         sql_parse::AlterSpecification::Modify {
                             if_exists,
                             col,
@@ -583,14 +1431,31 @@ pub fn parse_schemas<'a>(
                                     continue;
                                 }
                             };
-                            *c = parse_column(definition, c.identifier.clone(), issues);
+                            let unique = is_unique(&definition);
+                            *c = parse_column(definition, c.identifier.clone(), issues, options);
+                            if unique {
+                                e.unique_keys.push(alloc::vec![col]);
+                            }
                         }
                         sql_parse::AlterSpecification::AddColumn {
+                            if_not_exists_span,
                             identifier,
                             data_type,
                             ..
                         } => {
-                            e.columns.push(parse_column(data_type, identifier, issues));
+                            if let Some(oc) = e.get_column(identifier.value) {
+                                if if_not_exists_span.is_none() {
+                                    issues
+                                        .err("Column already defined", &identifier)
+                                        .frag("Defined here", &oc.identifier);
+                                }
+                                continue;
+                            }
+                            if is_unique(&data_type) {
+                                e.unique_keys.push(alloc::vec![identifier.clone()]);
+                            }
+                            e.columns
+                                .push(parse_column(data_type, identifier, issues, options));
                         }
                         sql_parse::AlterSpecification::OwnerTo { .. } => {}
                     }
@@ -608,7 +1473,7 @@ pub fn parse_schemas<'a>(
             sql_parse::Statement::CreateIndex(ci) => {
                 let t = unqualified_name(issues, &ci.table_name);
 
-                if let Some(table) = schemas.schemas.get(t) {
+                if let Some(table) = schemas.schemas.get_mut(t) {
                     for col in &ci.column_names {
                         if table.get_column(col).is_none() {
                             issues
@@ -616,6 +1481,13 @@ pub fn parse_schemas<'a>(
                                 .frag("Table defined here", &table.identifier_span);
                         }
                     }
+                    if ci
+                        .create_options
+                        .iter()
+                        .any(|o| matches!(o, sql_parse::CreateOption::Unique(_)))
+                    {
+                        table.unique_keys.push(ci.column_names.clone());
+                    }
                     // TODO type where_
                 } else {
                     issues.err("No such table", &ci.table_name);
@@ -652,7 +1524,37 @@ pub fn parse_schemas<'a>(
             }
             sql_parse::Statement::Commit(_) => (),
             sql_parse::Statement::Begin(_) => (),
-            sql_parse::Statement::CreateFunction(_) => (),
+            sql_parse::Statement::CreateFunction(f) => {
+                let replace = f
+                    .create_options
+                    .iter()
+                    .any(|o| matches!(o, sql_parse::CreateOption::OrReplace(_)));
+                let mut arguments = Vec::new();
+                for (_, identifier, data_type) in f.params {
+                    arguments.push(parse_column(data_type, identifier, issues, options).type_.t);
+                }
+                let return_type =
+                    parse_column(f.return_type, f.name.clone(), issues, options).type_;
+                let function = Functions {
+                    arguments,
+                    return_type,
+                    variadic: false,
+                };
+                match schemas.functions.entry(f.name.clone()) {
+                    alloc::collections::btree_map::Entry::Occupied(mut e) => {
+                        if replace {
+                            e.insert(function);
+                        } else if f.if_not_exists.is_none() {
+                            issues
+                                .err("Function already defined", &f.name)
+                                .frag("Defined here", &e.key().span);
+                        }
+                    }
+                    alloc::collections::btree_map::Entry::Vacant(e) => {
+                        e.insert(function);
+                    }
+                }
+            }
             s => {
                 issues.err(
                     alloc::format!("Unsupported statement {:?} in schema definition", s),
@@ -662,6 +1564,9 @@ pub fn parse_schemas<'a>(
         }
     }
 
+    add_pending_views(&mut schemas, pending_views, issues, options);
+    check_foreign_keys(&schemas, &foreign_keys, issues);
+
     let dummy_schemas = Schemas::default();
 
     let mut typer = crate::typer::Typer {
@@ -671,6 +1576,8 @@ pub fn parse_schemas<'a>(
         arg_types: Default::default(),
         options,
         with_schemas: Default::default(),
+        info: &mut Default::default(),
+        grouped: false,
     };
 
     // Compute nullity of generated columns
@@ -685,8 +1592,10 @@ pub fn parse_schemas<'a>(
         }
         typer.reference_types.push(crate::typer::ReferenceType {
             name: Some(name.clone()),
+            database: None,
             span: schema.identifier_span.clone(),
             columns,
+            using: Vec::new(),
         });
         for c in &mut schema.columns {
             if let Some(as_) = &c.as_ {
@@ -0,0 +1,147 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Construction of schemas without SQL text, for schema metadata given in
+//! another form such as ORM models
+//!
+//! ```
+//! use sql_type::{schema_builder::SchemaBuilder, type_statement, BaseType, Issues, SQLDialect, StatementType, Type, TypeOptions};
+//! let schemas = SchemaBuilder::new()
+//!     .table("users")
+//!     .column("id", Type::U64, true)
+//!     .auto_increment()
+//!     .column("name", BaseType::String, false)
+//!     .unique_key(&["id"])
+//!     .build();
+//!
+//! let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+//! let src = "SELECT `id`, `name` FROM `users`";
+//! let mut issues = Issues::new(src);
+//! let StatementType::Select { columns, .. } = type_statement(&schemas, src, &mut issues, &options)
+//! else {
+//!     panic!("Expected select")
+//! };
+//! assert!(issues.is_ok());
+//! assert_eq!(columns[0].type_.to_string(), "u64 not null");
+//! assert_eq!(columns[1].type_.to_string(), "string");
+//! ```
+
+use alloc::vec::Vec;
+use sql_parse::Identifier;
+
+use crate::{
+    schema::{Column, Schema, Schemas},
+    FullType, Type,
+};
+
+/// Builder of [Schemas], tables are given one at a time followed by their columns
+#[derive(Debug, Default)]
+pub struct SchemaBuilder<'a> {
+    schemas: Schemas<'a>,
+    table: Option<(Identifier<'a>, Schema<'a>)>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    /// Start with an empty schema
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start a table named name, the columns given after this belong to it.
+    /// A table of the same name given earlier is replaced
+    pub fn table(mut self, name: &'a str) -> Self {
+        self.finish_table();
+        self.table = Some((
+            Identifier::new(name, 0..0),
+            Schema {
+                identifier_span: 0..0,
+                columns: Vec::new(),
+                view: false,
+                unique_keys: Vec::new(),
+            },
+        ));
+        self
+    }
+
+    fn current(&mut self) -> &mut Schema<'a> {
+        &mut self
+            .table
+            .as_mut()
+            .expect("Columns must be given after a table")
+            .1
+    }
+
+    fn last_column(&mut self) -> &mut Column<'a> {
+        self.current()
+            .columns
+            .last_mut()
+            .expect("Column properties must be given after a column")
+    }
+
+    /// Add a column to the current table
+    ///
+    /// # Panics
+    /// If no table has been started
+    pub fn column(mut self, name: &'a str, t: impl Into<Type<'a>>, not_null: bool) -> Self {
+        self.current().columns.push(Column {
+            identifier: Identifier::new(name, 0..0),
+            type_: FullType::new(t, not_null),
+            declared_not_null: not_null,
+            auto_increment: false,
+            default: false,
+            as_: None,
+            semantic: None,
+            shard_key: false,
+        });
+        self
+    }
+
+    /// Mark the last column added as auto increment
+    ///
+    /// # Panics
+    /// If no column has been added to the current table
+    pub fn auto_increment(mut self) -> Self {
+        self.last_column().auto_increment = true;
+        self
+    }
+
+    /// Mark the last column added as having a default value
+    ///
+    /// # Panics
+    /// If no column has been added to the current table
+    pub fn with_default(mut self) -> Self {
+        self.last_column().default = true;
+        self
+    }
+
+    /// Add a primary or unique key over the named columns of the current table
+    ///
+    /// # Panics
+    /// If no table has been started
+    pub fn unique_key(mut self, columns: &[&'a str]) -> Self {
+        let key = columns.iter().map(|c| Identifier::new(c, 0..0)).collect();
+        self.current().unique_keys.push(key);
+        self
+    }
+
+    fn finish_table(&mut self) {
+        if let Some((name, schema)) = self.table.take() {
+            self.schemas.schemas.insert(name, schema);
+        }
+    }
+
+    /// The schemas with the tables given
+    pub fn build(mut self) -> Schemas<'a> {
+        self.finish_table();
+        self.schemas
+    }
+}
@@ -0,0 +1,164 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Differences between two schemas
+//!
+//! For instance between the schema the code was written against and the
+//! schema of the database, to detect drift or hint at migrations.
+//!
+//! ```
+//! use sql_type::{schema::parse_schemas, schema_diff::diff_schemas, Issues, SQLDialect, TypeOptions};
+//! let options = TypeOptions::new().dialect(SQLDialect::MariaDB);
+//! let old_src = "
+//!     CREATE TABLE `users` (`id` int NOT NULL, `name` text);
+//!     CREATE TABLE `logs` (`id` int NOT NULL);";
+//! let new_src = "
+//!     CREATE TABLE `users` (`id` int NOT NULL, `name` text NOT NULL, `email` text);
+//!     CREATE INDEX `users_email` ON `users` (`email`);
+//!     CREATE TABLE `posts` (`id` int NOT NULL);";
+//! let mut issues = Issues::new(old_src);
+//! let old = parse_schemas(old_src, &mut issues, &options);
+//! let mut issues = Issues::new(new_src);
+//! let new = parse_schemas(new_src, &mut issues, &options);
+//!
+//! let diff = diff_schemas(&old, &new);
+//! assert_eq!(diff.tables_added, ["posts"]);
+//! assert_eq!(diff.tables_removed, ["logs"]);
+//! assert_eq!(diff.indices_added, [(Some("users"), "users_email")]);
+//! let users = &diff.tables_changed[0];
+//! assert_eq!(users.name, "users");
+//! assert_eq!(users.columns_added, ["email"]);
+//! assert_eq!(users.columns_changed[0].name, "name");
+//! assert!(!users.columns_changed[0].old.not_null && users.columns_changed[0].new.not_null);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    schema::{Schema, Schemas},
+    FullType,
+};
+
+/// A column found in both schemas with a different type or nullability
+#[derive(Debug, Clone)]
+pub struct ColumnChange<'a> {
+    pub name: &'a str,
+    /// The type in the old schema
+    pub old: FullType<'a>,
+    /// The type in the new schema
+    pub new: FullType<'a>,
+}
+
+/// Differences between the columns of a table found in both schemas
+#[derive(Debug, Clone, Default)]
+pub struct TableDiff<'a> {
+    pub name: &'a str,
+    pub columns_added: Vec<&'a str>,
+    pub columns_removed: Vec<&'a str>,
+    pub columns_changed: Vec<ColumnChange<'a>>,
+    /// The table was changed into a view or the view into a table
+    pub view_changed: bool,
+}
+
+/// Differences between two schemas, tables are compared by name and columns
+/// by name within their table
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDiff<'a> {
+    /// Tables and views only in the new schema
+    pub tables_added: Vec<&'a str>,
+    /// Tables and views only in the old schema
+    pub tables_removed: Vec<&'a str>,
+    /// Tables and views in both schemas that differ
+    pub tables_changed: Vec<TableDiff<'a>>,
+    /// Table and name of indices only in the new schema
+    pub indices_added: Vec<(Option<&'a str>, &'a str)>,
+    /// Table and name of indices only in the old schema
+    pub indices_removed: Vec<(Option<&'a str>, &'a str)>,
+}
+
+impl SchemaDiff<'_> {
+    /// Are the schemas the same
+    pub fn is_empty(&self) -> bool {
+        self.tables_added.is_empty()
+            && self.tables_removed.is_empty()
+            && self.tables_changed.is_empty()
+            && self.indices_added.is_empty()
+            && self.indices_removed.is_empty()
+    }
+}
+
+fn diff_table<'a>(name: &'a str, old: &Schema<'a>, new: &Schema<'a>) -> TableDiff<'a> {
+    let mut ans = TableDiff {
+        name,
+        view_changed: old.view != new.view,
+        ..Default::default()
+    };
+    for c in &new.columns {
+        match old.get_column(c.identifier.value) {
+            None => ans.columns_added.push(c.identifier.value),
+            Some(o) if !o.type_.same_declaration(&c.type_) => {
+                ans.columns_changed.push(ColumnChange {
+                    name: c.identifier.value,
+                    old: o.type_.clone(),
+                    new: c.type_.clone(),
+                })
+            }
+            Some(_) => (),
+        }
+    }
+    for c in &old.columns {
+        if new.get_column(c.identifier.value).is_none() {
+            ans.columns_removed.push(c.identifier.value);
+        }
+    }
+    ans
+}
+
+/// Find the tables, columns and indices added, removed or changed from the
+/// old to the new schema. Only tables not created in a named database are
+/// compared
+pub fn diff_schemas<'a>(old: &Schemas<'a>, new: &Schemas<'a>) -> SchemaDiff<'a> {
+    let mut ans = SchemaDiff::default();
+    for (name, schema) in &new.schemas {
+        match old.schemas.get(name) {
+            None => ans.tables_added.push(name.value),
+            Some(o) => {
+                let d = diff_table(name.value, o, schema);
+                if d.view_changed
+                    || !d.columns_added.is_empty()
+                    || !d.columns_removed.is_empty()
+                    || !d.columns_changed.is_empty()
+                {
+                    ans.tables_changed.push(d);
+                }
+            }
+        }
+    }
+    for name in old.schemas.keys() {
+        if !new.schemas.contains_key(name) {
+            ans.tables_removed.push(name.value);
+        }
+    }
+    for key in new.indices.keys() {
+        if !old.indices.contains_key(key) {
+            ans.indices_added
+                .push((key.table.as_ref().map(|t| t.value), key.index.value));
+        }
+    }
+    for key in old.indices.keys() {
+        if !new.indices.contains_key(key) {
+            ans.indices_removed
+                .push((key.table.as_ref().map(|t| t.value), key.index.value));
+        }
+    }
+    ans
+}
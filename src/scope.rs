@@ -0,0 +1,206 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typing of standalone expressions against a fixed set of tables
+//!
+//! ```
+//! use sql_type::{schema::parse_schemas, scope::TypeScope, Issues, SQLDialect, SQLArguments, TypeOptions};
+//! let options = TypeOptions::new()
+//!     .dialect(SQLDialect::MariaDB)
+//!     .arguments(SQLArguments::QuestionMark);
+//! let schema_src = "CREATE TABLE `orders` (`price` double NOT NULL, `quantity` int NOT NULL);";
+//! let mut issues = Issues::new(schema_src);
+//! let schemas = parse_schemas(schema_src, &mut issues, &options);
+//!
+//! let scope = TypeScope::new(&schemas, &[("orders", None)], &options).unwrap();
+//! let expression = "`price` > ? AND `quantity` < 10";
+//! let mut issues = Issues::new(expression);
+//! let t = scope.type_expression(expression, &mut issues);
+//! assert!(issues.is_ok());
+//! assert_eq!(t.type_.to_string(), "bool");
+//! assert_eq!(t.arguments.len(), 1);
+//! ```
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use sql_parse::{parse_statement, Identifier, Issues, Level, Select, Span, Statement};
+
+use crate::{
+    plan::collect_subqueries,
+    schema::{find_table, Schema, Schemas},
+    type_expression::{type_expression, ExpressionFlags},
+    typer::{ReferenceType, Typer},
+    ArgumentKey, BaseType, FullType, StatementInfo, TypeOptions,
+};
+
+/// The expression is parsed as the single column of a select
+const PREFIX: &str = "SELECT ";
+
+/// The type of a standalone expression
+#[derive(Debug, Clone)]
+pub struct ExpressionType<'a> {
+    /// The type of the value of the expression
+    pub type_: FullType<'a>,
+    /// The key and type of arguments to the expression
+    pub arguments: Vec<(ArgumentKey<'a>, FullType<'a>)>,
+}
+
+/// A set of tables in scope, as if given in a FROM clause, that standalone
+/// expressions can be typed against repeatedly
+#[derive(Debug, Clone)]
+pub struct TypeScope<'a> {
+    schemas: &'a Schemas<'a>,
+    options: TypeOptions,
+    /// Alias or name of each table in scope with its schema
+    tables: Vec<(String, &'a Schema<'a>)>,
+}
+
+/// Is the select just a single unnamed expression without subqueries
+fn single_expression(select: &Select<'_>) -> bool {
+    let [e] = select.select_exprs.as_slice() else {
+        return false;
+    };
+    let mut subqueries = Vec::new();
+    collect_subqueries(&e.expr, &mut subqueries);
+    e.as_.is_none()
+        && subqueries.is_empty()
+        && select.flags.is_empty()
+        && select.from_span.is_none()
+        && select.table_references.is_none()
+        && select.where_.is_none()
+        && select.group_by.is_none()
+        && select.having.is_none()
+        && select.window_span.is_none()
+        && select.order_by.is_none()
+        && select.limit.is_none()
+        && select.locking.is_none()
+}
+
+impl<'a> TypeScope<'a> {
+    /// Create a scope of tables given by name and optional alias
+    ///
+    /// If a table is not in the schemas its name is returned as the error
+    pub fn new(
+        schemas: &'a Schemas<'a>,
+        tables: &[(&str, Option<&str>)],
+        options: &TypeOptions,
+    ) -> Result<Self, String> {
+        let mut scope = Vec::new();
+        for (table, alias) in tables {
+            let Some((identifier, schema)) = find_table(&schemas.schemas, table, options) else {
+                return Err(table.to_string());
+            };
+            scope.push((alias.unwrap_or(identifier.value).to_string(), schema));
+        }
+        Ok(Self {
+            schemas,
+            options: options.clone(),
+            tables: scope,
+        })
+    }
+
+    /// Type an expression against the tables in scope
+    ///
+    /// Errors and warnings are added to issues, with spans into expression
+    pub fn type_expression<'b>(
+        &self,
+        expression: &'b str,
+        issues: &mut Issues<'b>,
+    ) -> ExpressionType<'b> {
+        let sql = format!("{}{}", PREFIX, expression);
+        let mut inner = Issues::new(&sql);
+        let statement = parse_statement(&sql, &mut inner, &self.options.parse_options);
+        let expr = match &statement {
+            Some(Statement::Select(s)) if single_expression(s) => Some(&s.select_exprs[0].expr),
+            Some(_) => {
+                inner.err("Expected a single expression", &(PREFIX.len()..sql.len()));
+                None
+            }
+            None => None,
+        };
+
+        // The tables in scope have no span in the expression
+        let reference_types = self
+            .tables
+            .iter()
+            .map(|(name, schema)| ReferenceType {
+                name: Some(Identifier::new(name, 0..0)),
+                database: None,
+                span: 0..0,
+                columns: schema
+                    .columns
+                    .iter()
+                    .map(|c| (c.identifier.clone(), c.type_.clone()))
+                    .collect(),
+                using: Vec::new(),
+            })
+            .collect();
+        let mut info = StatementInfo::default();
+        let mut typer = Typer {
+            schemas: self.schemas,
+            issues: &mut inner,
+            reference_types,
+            arg_types: Vec::new(),
+            options: &self.options,
+            with_schemas: BTreeMap::new(),
+            info: &mut info,
+            grouped: false,
+        };
+        let type_ = match expr {
+            Some(e) => type_expression(&mut typer, e, ExpressionFlags::default(), BaseType::Any)
+                .into_owned(),
+            None => FullType::invalid(),
+        };
+
+        // Named arguments are found by their position in sql
+        let arguments = typer
+            .arg_types
+            .into_iter()
+            .map(|(k, t)| {
+                let k = match k {
+                    ArgumentKey::Index(i) => ArgumentKey::Index(i),
+                    ArgumentKey::Identifier(name) => {
+                        let start = name.as_ptr() as usize - sql.as_ptr() as usize - PREFIX.len();
+                        ArgumentKey::Identifier(&expression[start..start + name.len()])
+                    }
+                };
+                (k, t.into_owned())
+            })
+            .collect();
+
+        // Move the issues back to the expression, fragments outside of it
+        // point at the tables in scope and are left out
+        let len = expression.len();
+        let map = |span: &Span| -> Span {
+            let start = span.start.saturating_sub(PREFIX.len()).min(len);
+            let end = span.end.saturating_sub(PREFIX.len()).min(len);
+            start..end.max(start)
+        };
+        for issue in inner.get() {
+            let span = map(&issue.span);
+            let mut handle = match issue.level {
+                Level::Error => issues.err(issue.message.clone(), &span),
+                Level::Warning => issues.warn(issue.message.clone(), &span),
+            };
+            for fragment in &issue.fragments {
+                if fragment.span.start >= PREFIX.len() {
+                    handle.frag(fragment.message.clone(), &map(&fragment.span));
+                }
+            }
+        }
+        ExpressionType { type_, arguments }
+    }
+}
@@ -0,0 +1,188 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serde support for the parts of the schemas that hold sql-parse values
+//!
+//! Identifiers are serialized as their name and span. Deserialized
+//! identifiers borrow their name from the serialized data, so the data must
+//! be deserialized with a format able to borrow strings. Maps are serialized
+//! as lists of pairs, as their keys are not strings.
+
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sql_parse::{Expression, Identifier, Span};
+
+/// An identifier as serialized
+#[derive(Serialize, Deserialize)]
+struct Ident<'a>(#[serde(borrow)] &'a str, Span);
+
+impl<'a> From<&Identifier<'a>> for Ident<'a> {
+    fn from(v: &Identifier<'a>) -> Self {
+        Ident(v.value, v.span.clone())
+    }
+}
+
+impl<'a> From<Ident<'a>> for Identifier<'a> {
+    fn from(v: Ident<'a>) -> Self {
+        Identifier::new(v.0, v.1)
+    }
+}
+
+pub(crate) mod identifier {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(v: &Identifier<'_>, s: S) -> Result<S::Ok, S::Error> {
+        Ident::from(v).serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de: 'a, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Identifier<'a>, D::Error> {
+        Ok(Ident::deserialize(d)?.into())
+    }
+}
+
+pub(crate) mod optional_identifier {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        v: &Option<Identifier<'_>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        v.as_ref().map(Ident::from).serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de: 'a, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Identifier<'a>>, D::Error> {
+        Ok(Option::<Ident<'a>>::deserialize(d)?.map(Into::into))
+    }
+}
+
+/// Lists of lists of identifiers, like the columns of the unique keys
+pub(crate) mod identifier_lists {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        v: &[Vec<Identifier<'_>>],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_seq(
+            v.iter()
+                .map(|l| l.iter().map(Ident::from).collect::<Vec<_>>()),
+        )
+    }
+
+    pub(crate) fn deserialize<'de: 'a, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Vec<Vec<Identifier<'a>>>, D::Error> {
+        Ok(Vec::<Vec<Ident<'a>>>::deserialize(d)?
+            .into_iter()
+            .map(|l| l.into_iter().map(Into::into).collect())
+            .collect())
+    }
+}
+
+/// Maps from identifiers to values
+pub(crate) mod identifier_map {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer, V: Serialize>(
+        v: &BTreeMap<Identifier<'_>, V>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_seq(v.iter().map(|(k, v)| (Ident::from(k), v)))
+    }
+
+    pub(crate) fn deserialize<'de: 'a, 'a, D: Deserializer<'de>, V: Deserialize<'de>>(
+        d: D,
+    ) -> Result<BTreeMap<Identifier<'a>, V>, D::Error> {
+        Ok(Vec::<(Ident<'a>, V)>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| (k.into(), v))
+            .collect())
+    }
+}
+
+/// Maps from database names to the maps of their tables
+pub(crate) mod database_map {
+    use super::*;
+    use crate::schema::Schema;
+
+    #[derive(Serialize)]
+    struct Tables<'s, 'a>(
+        #[serde(with = "identifier_map")] &'s BTreeMap<Identifier<'a>, Schema<'a>>,
+    );
+
+    #[derive(Deserialize)]
+    struct OwnedTables<'a>(
+        #[serde(borrow, with = "identifier_map")] BTreeMap<Identifier<'a>, Schema<'a>>,
+    );
+
+    pub(crate) fn serialize<S: Serializer>(
+        v: &BTreeMap<Identifier<'_>, BTreeMap<Identifier<'_>, Schema<'_>>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_seq(v.iter().map(|(k, v)| (Ident::from(k), Tables(v))))
+    }
+
+    pub(crate) fn deserialize<'de: 'a, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<BTreeMap<Identifier<'a>, BTreeMap<Identifier<'a>, Schema<'a>>>, D::Error> {
+        Ok(Vec::<(Ident<'a>, OwnedTables<'a>)>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.0))
+            .collect())
+    }
+}
+
+/// Maps with keys that are not strings
+pub(crate) mod pairs {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer, K: Serialize, V: Serialize>(
+        v: &BTreeMap<K, V>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_seq(v.iter())
+    }
+
+    pub(crate) fn deserialize<'de, D, K, V>(d: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(d)?.into_iter().collect())
+    }
+}
+
+/// The expressions of generated columns are not kept, only their span. A
+/// deserialized generated column has an invalid expression at that span
+pub(crate) mod generated {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        v: &Option<Box<Expression<'_>>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        v.as_ref()
+            .map(|e| sql_parse::Spanned::span(e.as_ref()))
+            .serialize(s)
+    }
+
+    pub(crate) fn deserialize<'de, 'a, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Box<Expression<'a>>>, D::Error> {
+        Ok(Option::<Span>::deserialize(d)?.map(|span| Box::new(Expression::Invalid(span))))
+    }
+}
@@ -0,0 +1,367 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use sql_parse::{
+    BinaryOperator, Expression, IdentifierPart, Issues, JoinSpecification, QualifiedName, Select,
+    Span, Spanned, Statement, TableReference,
+};
+
+use crate::{
+    plan::{collect_identifiers, collect_subqueries, select_subqueries, split_and},
+    schema::{find_table, Schema, Schemas},
+    TypeOptions,
+};
+
+/// An equality predicate binding a shard key column to a value
+#[derive(Debug, Clone)]
+pub struct ShardKeyBinding<'a> {
+    /// Name of the shard key column
+    pub column: &'a str,
+    /// Span of the predicate
+    pub predicate: Span,
+}
+
+/// Access to a table with a shard key
+#[derive(Debug, Clone)]
+pub struct ShardAccess<'a> {
+    /// Name of the table
+    pub table: &'a str,
+    /// Span of the table in the statement
+    pub span: Span,
+    /// Predicates binding the shard key columns of the table
+    pub bindings: Vec<ShardKeyBinding<'a>>,
+}
+
+struct Access<'a, 's> {
+    name: &'a str,
+    schema: &'s Schema<'a>,
+    access: ShardAccess<'a>,
+}
+
+/// The tables, predicates and subqueries of a single query
+#[derive(Default)]
+struct Scope<'a, 's, 'e> {
+    accesses: Vec<Access<'a, 's>>,
+    /// Names of every table in the FROM clause, including derived tables
+    names: Vec<&'a str>,
+    predicates: Vec<&'e Expression<'a>>,
+    subqueries: Vec<&'e Statement<'a>>,
+}
+
+/// Find the schema of a possibly database qualified table
+fn find_schema<'a, 's>(
+    schemas: &'s Schemas<'a>,
+    identifier: &QualifiedName<'a>,
+    options: &TypeOptions,
+) -> Option<&'s Schema<'a>> {
+    let tables = match identifier.prefix.as_slice() {
+        [] => &schemas.schemas,
+        [(database, _)] if options.is_default_database(database.value) => &schemas.schemas,
+        [(database, _)] => schemas.databases.get(database.value)?,
+        _ => return None,
+    };
+    find_table(tables, identifier.identifier.value, options).map(|(_, schema)| schema)
+}
+
+fn add_table<'a, 's>(
+    schemas: &'s Schemas<'a>,
+    identifier: &QualifiedName<'a>,
+    name: &'a str,
+    options: &TypeOptions,
+    scope: &mut Scope<'a, 's, '_>,
+) {
+    scope.names.push(name);
+    if let Some(schema) = find_schema(schemas, identifier, options) {
+        scope.accesses.push(Access {
+            name,
+            schema,
+            access: ShardAccess {
+                table: identifier.identifier.value,
+                span: identifier.span(),
+                bindings: Vec::new(),
+            },
+        });
+    }
+}
+
+fn collect_reference<'a, 's, 'e>(
+    schemas: &'s Schemas<'a>,
+    reference: &'e TableReference<'a>,
+    options: &TypeOptions,
+    scope: &mut Scope<'a, 's, 'e>,
+) {
+    match reference {
+        TableReference::Table {
+            identifier, as_, ..
+        } => {
+            let name = as_.as_ref().unwrap_or(&identifier.identifier).value;
+            add_table(schemas, identifier, name, options, scope);
+        }
+        TableReference::Query { query, as_, .. } => {
+            scope.names.extend(as_.as_ref().map(|v| v.value));
+            scope.subqueries.push(query);
+        }
+        TableReference::Join {
+            left,
+            right,
+            specification,
+            ..
+        } => {
+            collect_reference(schemas, left, options, scope);
+            collect_reference(schemas, right, options, scope);
+            if let Some(JoinSpecification::On(e, _)) = specification {
+                scope.predicates.push(e);
+            }
+        }
+    }
+}
+
+/// Find the access and shard key column named by an identifier if any
+fn shard_column<'a>(
+    accesses: &[Access<'a, '_>],
+    parts: &[IdentifierPart<'a>],
+    options: &TypeOptions,
+) -> Option<(usize, &'a str)> {
+    let (index, column) = match parts {
+        [IdentifierPart::Name(t), IdentifierPart::Name(c)] => (
+            accesses
+                .iter()
+                .position(|a| options.same_name(a.name, t.value))?,
+            c.value,
+        ),
+        [IdentifierPart::Name(c)] => {
+            let mut found = accesses
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| a.schema.find_column(c.value, options).is_some());
+            let (index, _) = found.next()?;
+            if found.next().is_some() {
+                return None;
+            }
+            (index, c.value)
+        }
+        _ => return None,
+    };
+    let column = accesses[index].schema.find_column(column, options)?;
+    if !column.shard_key {
+        return None;
+    }
+    Some((index, column.identifier.value))
+}
+
+/// Does the expression only depend on constants, arguments and columns of
+/// enclosing queries
+fn is_value(e: &Expression<'_>, names: &[&str], outer: &[&str], options: &TypeOptions) -> bool {
+    let mut identifiers = Vec::new();
+    collect_identifiers(e, &mut identifiers)
+        && identifiers.iter().all(|parts| match parts {
+            [IdentifierPart::Name(t), IdentifierPart::Name(_)] => {
+                !names.iter().any(|n| options.same_name(n, t.value))
+                    && outer.iter().any(|n| options.same_name(n, t.value))
+            }
+            _ => false,
+        })
+}
+
+fn is_bound(access: &Access<'_, '_>, column: &str) -> bool {
+    access.access.bindings.iter().any(|b| b.column == column)
+}
+
+fn check_accesses<'a>(
+    accesses: &mut [Access<'a, '_>],
+    names: &[&'a str],
+    predicates: &[&Expression<'a>],
+    outer: &[&'a str],
+    options: &TypeOptions,
+) {
+    let mut conjuncts = Vec::new();
+    for p in predicates {
+        split_and(p, &mut conjuncts);
+    }
+    let mut equalities = Vec::new();
+    for c in conjuncts {
+        let Expression::Binary {
+            op: BinaryOperator::Eq | BinaryOperator::NullSafeEq,
+            lhs,
+            rhs,
+            ..
+        } = c
+        else {
+            continue;
+        };
+        for (column, value) in [(lhs, rhs), (rhs, lhs)] {
+            let Expression::Identifier(parts) = column.as_ref() else {
+                continue;
+            };
+            let Some((index, column)) = shard_column(accesses, parts, options) else {
+                continue;
+            };
+            if is_value(value, names, outer, options) {
+                accesses[index].access.bindings.push(ShardKeyBinding {
+                    column,
+                    predicate: c.span(),
+                });
+            } else if let Expression::Identifier(other) = value.as_ref() {
+                equalities.push((index, column, other, c.span()));
+            }
+        }
+    }
+    // A shard key column equal to a bound shard key column is bound as well
+    loop {
+        let mut changed = false;
+        for (index, column, other, predicate) in &equalities {
+            if is_bound(&accesses[*index], column) {
+                continue;
+            }
+            let Some((other_index, other)) = shard_column(accesses, other, options) else {
+                continue;
+            };
+            if is_bound(&accesses[other_index], other) {
+                accesses[*index].access.bindings.push(ShardKeyBinding {
+                    column,
+                    predicate: predicate.clone(),
+                });
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+fn check_scope<'a>(
+    schemas: &Schemas<'a>,
+    scope: Scope<'a, '_, '_>,
+    outer: &[&'a str],
+    options: &TypeOptions,
+    issues: &mut Issues<'a>,
+    out: &mut Vec<ShardAccess<'a>>,
+) {
+    let Scope {
+        mut accesses,
+        names,
+        predicates,
+        subqueries,
+    } = scope;
+    check_accesses(&mut accesses, &names, &predicates, outer, options);
+    for access in accesses {
+        if !access.schema.columns.iter().any(|c| c.shard_key) {
+            continue;
+        }
+        let bound = access
+            .schema
+            .columns
+            .iter()
+            .filter(|c| c.shard_key)
+            .all(|c| is_bound(&access, c.identifier.value));
+        if !bound {
+            issues.warn(
+                "Sharded table accessed without constraining its shard key",
+                &access.access.span,
+            );
+        }
+        out.push(access.access);
+    }
+    // Columns of this query are values within its subqueries
+    let mut outer = outer.to_vec();
+    outer.extend(names);
+    for query in subqueries {
+        check_statement(schemas, query, &outer, options, issues, out);
+    }
+}
+
+fn check_select<'a>(
+    schemas: &Schemas<'a>,
+    select: &Select<'a>,
+    outer: &[&'a str],
+    options: &TypeOptions,
+    issues: &mut Issues<'a>,
+    out: &mut Vec<ShardAccess<'a>>,
+) {
+    let mut scope = Scope::default();
+    for reference in select.table_references.iter().flatten() {
+        collect_reference(schemas, reference, options, &mut scope);
+    }
+    select_subqueries(select, &mut scope.subqueries);
+    if let Some((e, _)) = &select.where_ {
+        scope.predicates.push(e);
+    }
+    check_scope(schemas, scope, outer, options, issues, out);
+}
+
+fn check_statement<'a>(
+    schemas: &Schemas<'a>,
+    statement: &Statement<'a>,
+    outer: &[&'a str],
+    options: &TypeOptions,
+    issues: &mut Issues<'a>,
+    out: &mut Vec<ShardAccess<'a>>,
+) {
+    let mut scope = Scope::default();
+    let where_ = match statement {
+        Statement::Select(s) => return check_select(schemas, s, outer, options, issues, out),
+        Statement::InsertReplace(i) => {
+            if let Some(s) = &i.select {
+                check_select(schemas, s, outer, options, issues, out);
+            }
+            return;
+        }
+        Statement::Update(u) => {
+            for reference in &u.tables {
+                collect_reference(schemas, reference, options, &mut scope);
+            }
+            for (_, e) in &u.set {
+                collect_subqueries(e, &mut scope.subqueries);
+            }
+            &u.where_
+        }
+        Statement::Delete(d) => {
+            if d.using.is_empty() {
+                for table in &d.tables {
+                    add_table(schemas, table, table.identifier.value, options, &mut scope);
+                }
+            }
+            for reference in &d.using {
+                collect_reference(schemas, reference, options, &mut scope);
+            }
+            &d.where_
+        }
+        Statement::Union(u) => {
+            check_statement(schemas, &u.left, outer, options, issues, out);
+            for w in &u.with {
+                check_statement(schemas, &w.union_statement, outer, options, issues, out);
+            }
+            return;
+        }
+        _ => return,
+    };
+    if let Some((e, _)) = where_ {
+        collect_subqueries(e, &mut scope.subqueries);
+        scope.predicates.push(e);
+    }
+    check_scope(schemas, scope, outer, options, issues, out);
+}
+
+/// Find the accesses to sharded tables in a statement and its subqueries,
+/// warning about those not binding every shard key column to a value or to
+/// a bound shard key column of another table
+pub(crate) fn check_shard_keys<'a>(
+    schemas: &Schemas<'a>,
+    statement: &Statement<'a>,
+    options: &TypeOptions,
+    issues: &mut Issues<'a>,
+    out: &mut Vec<ShardAccess<'a>>,
+) {
+    check_statement(schemas, statement, &[], options, issues, out);
+}
@@ -0,0 +1,297 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for writing tests of statements against a schema
+//!
+//! The helpers panic with a readable description of the problem when an
+//! expectation is not met. Types are written in a terse notation: `b`, `u8`,
+//! `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`, `f32`, `f64`, `i` (any
+//! integer), `f` (any float), `str`, `bytes`, `dt`, `date`, `time`, `ts`,
+//! `json` and `any`. A trailing `!` means not null and a trailing `[]`
+//! means a list given as _LIST_.
+//!
+//! ```
+//! use sql_type::{testing, SQLDialect, SQLArguments, TypeOptions};
+//! let options = TypeOptions::new()
+//!     .dialect(SQLDialect::MariaDB)
+//!     .arguments(SQLArguments::QuestionMark);
+//! let schemas = testing::schemas(
+//!     "CREATE TABLE `t` (`id` int NOT NULL, `name` text);",
+//!     &options,
+//! );
+//!
+//! let stmt = testing::type_ok(&schemas, "SELECT `id`, `name` FROM `t` WHERE `id` = ?", &options);
+//! testing::assert_columns(&stmt, "id:i32!,name:str");
+//! testing::assert_arguments(&stmt, "i");
+//!
+//! let issues = testing::type_err(&schemas, "SELECT `nope` FROM `t`", &options);
+//! testing::assert_issue(&issues, "Unknown identifier");
+//! ```
+
+use alloc::{fmt::Write, string::String, vec::Vec};
+use sql_parse::{Issue, Issues, Level};
+
+use crate::{
+    schema::{parse_schemas, Schemas},
+    type_statement, ArgumentKey, BaseType, FullType, SelectTypeColumn, StatementType, Type,
+    TypeOptions,
+};
+
+/// Parse a type given in the terse notation
+pub fn parse_type(t: &str) -> Option<FullType<'static>> {
+    let (t, not_null) = if let Some(t) = t.strip_suffix('!') {
+        (t, true)
+    } else {
+        (t, false)
+    };
+    let (t, list_hack) = if let Some(v) = t.strip_suffix("[]") {
+        (v, true)
+    } else {
+        (t, false)
+    };
+    let t = match t {
+        "b" => BaseType::Bool.into(),
+        "u8" => Type::U8,
+        "u16" => Type::U16,
+        "u32" => Type::U32,
+        "u64" => Type::U64,
+        "i8" => Type::I8,
+        "i16" => Type::I16,
+        "i32" => Type::I32,
+        "i64" => Type::I64,
+        "f32" => Type::F32,
+        "f64" => Type::F64,
+        "i" => BaseType::Integer.into(),
+        "f" => BaseType::Float.into(),
+        "str" => BaseType::String.into(),
+        "bytes" => BaseType::Bytes.into(),
+        "geometry" => BaseType::Geometry.into(),
+        "dt" => BaseType::DateTime.into(),
+        "date" => BaseType::Date.into(),
+        "time" => BaseType::Time.into(),
+        "ts" => BaseType::TimeStamp.into(),
+        "json" => Type::JSON,
+        "uuid" => Type::Uuid,
+        "any" => BaseType::Any.into(),
+        _ => return None,
+    };
+    let mut t = FullType::new(t, not_null);
+    t.list_hack = list_hack;
+    Some(t)
+}
+
+fn expect_type(t: &str) -> FullType<'static> {
+    match parse_type(t.trim()) {
+        Some(t) => t,
+        None => panic!("Unknown type '{}'", t),
+    }
+}
+
+/// Render issues with their level, line and source segment
+pub fn format_issues(src: &str, issues: &[Issue<'_>]) -> String {
+    let mut out = String::new();
+    for issue in issues {
+        let level = match issue.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        };
+        let line = src[..issue.span.start.min(src.len())].matches('\n').count() + 1;
+        let _ = writeln!(
+            out,
+            "{}: {} at line {}: {}",
+            level, issue.message, line, issue.sql_segment
+        );
+        for fragment in &issue.fragments {
+            let _ = writeln!(out, "    {}: {}", fragment.message, fragment.sql_segment);
+        }
+    }
+    out
+}
+
+/// Parse schemas panicking if there are any errors
+pub fn schemas<'a>(src: &'a str, options: &TypeOptions) -> Schemas<'a> {
+    let mut issues = Issues::new(src);
+    let schemas = parse_schemas(src, &mut issues, options);
+    if !issues.is_ok() {
+        panic!("Errors in schemas:\n{}", format_issues(src, issues.get()));
+    }
+    schemas
+}
+
+/// Type a statement panicking if there are any errors
+pub fn type_ok<'a>(
+    schemas: &'a Schemas<'a>,
+    sql: &'a str,
+    options: &TypeOptions,
+) -> StatementType<'a> {
+    let mut issues = Issues::new(sql);
+    let stmt = type_statement(schemas, sql, &mut issues, options);
+    if !issues.is_ok() {
+        panic!(
+            "Errors in statement {}:\n{}",
+            sql,
+            format_issues(sql, issues.get())
+        );
+    }
+    stmt
+}
+
+/// Type a statement panicking if there are no errors, the issues are returned
+pub fn type_err<'a>(
+    schemas: &'a Schemas<'a>,
+    sql: &'a str,
+    options: &TypeOptions,
+) -> Vec<Issue<'a>> {
+    let mut issues = Issues::new(sql);
+    type_statement(schemas, sql, &mut issues, options);
+    if issues.is_ok() {
+        panic!("Expected errors in statement {}", sql);
+    }
+    issues.into_vec()
+}
+
+/// Assert that an issue with a message containing `message` is present
+pub fn assert_issue(issues: &[Issue<'_>], message: &str) {
+    if !issues.iter().any(|i| i.message.contains(message)) {
+        let mut got = String::new();
+        for issue in issues {
+            let _ = writeln!(got, "    {}", issue.message);
+        }
+        panic!("Expected issue '{}' got:\n{}", message, got);
+    }
+}
+
+/// The columns returned by a statement
+pub fn columns<'b, 'a>(stmt: &'b StatementType<'a>) -> &'b [SelectTypeColumn<'a>] {
+    match stmt {
+        StatementType::Select { columns, .. } => columns,
+        StatementType::Delete { returning, .. }
+        | StatementType::Insert { returning, .. }
+        | StatementType::Replace { returning, .. } => returning.as_deref().unwrap_or_default(),
+        StatementType::Update { .. }
+        | StatementType::Set { .. }
+        | StatementType::Transaction
+        | StatementType::Truncate
+        | StatementType::TemporaryTable
+        | StatementType::Invalid => &[],
+    }
+}
+
+/// The arguments of a statement
+pub fn arguments<'b, 'a>(stmt: &'b StatementType<'a>) -> &'b [(ArgumentKey<'a>, FullType<'a>)] {
+    match stmt {
+        StatementType::Select { arguments, .. }
+        | StatementType::Delete { arguments, .. }
+        | StatementType::Insert { arguments, .. }
+        | StatementType::Update { arguments, .. }
+        | StatementType::Set { arguments, .. }
+        | StatementType::Replace { arguments, .. } => arguments,
+        StatementType::Transaction
+        | StatementType::Truncate
+        | StatementType::TemporaryTable
+        | StatementType::Invalid => &[],
+    }
+}
+
+/// Assert the names and types of the columns returned by a statement
+///
+/// The columns are given as a comma separated list of `name:type`
+/// where the name is left empty for unnamed columns
+pub fn assert_columns(stmt: &StatementType<'_>, expected: &str) {
+    let got = columns(stmt);
+    let mut problems = String::new();
+    let expected: Vec<&str> = expected
+        .split(',')
+        .filter(|v| !v.trim().is_empty())
+        .collect();
+    for (i, e) in expected.iter().enumerate() {
+        let (name, t) = match e.split_once(':') {
+            Some(v) => v,
+            None => panic!("Expected column '{}' should be on the form name:type", e),
+        };
+        let name = name.trim();
+        let t = expect_type(t);
+        match got.get(i) {
+            Some(c) => {
+                let cname = c.name.as_ref().map(|n| n.value).unwrap_or_default();
+                if cname != name || c.type_ != t {
+                    let _ = writeln!(
+                        problems,
+                        "    column {}: expected {} of type {} got {} of type {}",
+                        i, name, t, cname, c.type_
+                    );
+                }
+            }
+            None => {
+                let _ = writeln!(
+                    problems,
+                    "    column {}: expected {} of type {} got nothing",
+                    i, name, t
+                );
+            }
+        }
+    }
+    for (i, c) in got.iter().enumerate().skip(expected.len()) {
+        let cname = c.name.as_ref().map(|n| n.value).unwrap_or_default();
+        let _ = writeln!(
+            problems,
+            "    column {}: unexpected {} of type {}",
+            i, cname, c.type_
+        );
+    }
+    if !problems.is_empty() {
+        panic!("Columns did not match:\n{}", problems);
+    }
+}
+
+/// Assert the types of the unnamed arguments of a statement
+///
+/// The types are given as a comma separated list in argument order
+pub fn assert_arguments(stmt: &StatementType<'_>, expected: &str) {
+    let mut got = Vec::new();
+    for (k, v) in arguments(stmt) {
+        match k {
+            ArgumentKey::Index(i) => {
+                while got.len() <= *i {
+                    got.push(None);
+                }
+                got[*i] = Some(v);
+            }
+            ArgumentKey::Identifier(k) => panic!("Unexpected named argument {}", k),
+        }
+    }
+    let mut problems = String::new();
+    let expected: Vec<&str> = expected
+        .split(',')
+        .filter(|v| !v.trim().is_empty())
+        .collect();
+    for (i, e) in expected.iter().enumerate() {
+        let t = expect_type(e);
+        match got.get(i).copied().flatten() {
+            Some(v) if v == &t => (),
+            Some(v) => {
+                let _ = writeln!(problems, "    argument {}: expected {} got {}", i, t, v);
+            }
+            None => {
+                let _ = writeln!(problems, "    argument {}: expected {} got nothing", i, t);
+            }
+        }
+    }
+    for (i, v) in got.iter().enumerate().skip(expected.len()) {
+        if let Some(v) = v {
+            let _ = writeln!(problems, "    argument {}: unexpected {}", i, v);
+        }
+    }
+    if !problems.is_empty() {
+        panic!("Arguments did not match:\n{}", problems);
+    }
+}
@@ -19,7 +19,8 @@ use alloc::{
 use sql_parse::Span;
 
 /// Canonical base type of a type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BaseType {
     /// There are no constraint of the value
     Any,
@@ -31,6 +32,8 @@ pub enum BaseType {
     DateTime,
     /// The value must be some kind of float
     Float,
+    /// The value must be a spatial geometry
+    Geometry,
     /// The value must be some kind of integer
     Integer,
     String,
@@ -47,6 +50,7 @@ impl Display for BaseType {
             BaseType::Date => f.write_str("date"),
             BaseType::DateTime => f.write_str("datetime"),
             BaseType::Float => f.write_str("float"),
+            BaseType::Geometry => f.write_str("geometry"),
             BaseType::Integer => f.write_str("integer"),
             BaseType::String => f.write_str("string"),
             BaseType::Time => f.write_str("time"),
@@ -55,19 +59,116 @@ impl Display for BaseType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Size class of TEXT and BLOB columns, the largest value of each class is
+/// given by [SizeClass::max_bytes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SizeClass {
+    /// TINYTEXT and TINYBLOB
+    Tiny,
+    /// TEXT and BLOB
+    Regular,
+    /// MEDIUMTEXT and MEDIUMBLOB
+    Medium,
+    /// LONGTEXT and LONGBLOB
+    Long,
+}
+
+impl SizeClass {
+    /// The maximal number of bytes of values of the class
+    pub fn max_bytes(self) -> u64 {
+        match self {
+            SizeClass::Tiny => (1 << 8) - 1,
+            SizeClass::Regular => (1 << 16) - 1,
+            SizeClass::Medium => (1 << 24) - 1,
+            SizeClass::Long => (1 << 32) - 1,
+        }
+    }
+}
+
+/// A column type defined outside this crate, like a vendor or domain
+/// specific type, registered with [crate::TypeOptions::custom_type]
+pub trait CustomType: core::fmt::Debug + Send + Sync {
+    /// Name of the type, as used in schemas and shown in issues
+    fn name(&self) -> &str;
+
+    /// The base type values of the type behave as
+    fn base(&self) -> BaseType;
+
+    /// Can values of the type be compared with and combined with values of
+    /// the given base type
+    fn accepts(&self, base: BaseType) -> bool {
+        base == self.base()
+    }
+}
+
+/// Shared reference to a custom type, custom types are identified by name
+#[derive(Debug, Clone)]
+pub struct CustomTypeRef(pub Arc<dyn CustomType>);
+
+impl PartialEq for CustomTypeRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl Eq for CustomTypeRef {}
+
+impl core::hash::Hash for CustomTypeRef {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.name().hash(state)
+    }
+}
+
+/// Custom types are serialized by name and base type. A deserialized custom
+/// type is equal to the registered type of the same name, but only accepts
+/// its own base type
+#[cfg(feature = "serde")]
+impl serde::Serialize for CustomTypeRef {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.0.name(), self.0.base()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CustomTypeRef {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Debug)]
+        struct Deserialized(alloc::string::String, BaseType);
+
+        impl CustomType for Deserialized {
+            fn name(&self) -> &str {
+                &self.0
+            }
+
+            fn base(&self) -> BaseType {
+                self.1
+            }
+        }
+
+        let (name, base) = serde::Deserialize::deserialize(deserializer)?;
+        Ok(CustomTypeRef(Arc::new(Deserialized(name, base))))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ArgType {
     Normal,
     ListHack,
 }
 
 /// Represent the type of a value
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type<'a> {
     // This type is used internally and should not escape to the user
     #[doc(hidden)]
     Args(BaseType, Arc<Vec<(usize, ArgType, Span)>>),
     Base(BaseType),
+    Custom(CustomTypeRef),
+    /// Exact fixed point number with the given precision and scale
+    Decimal(u8, u8),
     Enum(Arc<Vec<Cow<'a, str>>>),
     F32,
     F64,
@@ -82,6 +183,7 @@ pub enum Type<'a> {
     U32,
     U64,
     U8,
+    Uuid,
     // This type is used internally and should not escape to the user
     #[doc(hidden)]
     Null,
@@ -98,6 +200,8 @@ impl<'a> Display for Type<'a> {
                 f.write_char(')')
             }
             Type::Base(t) => t.fmt(f),
+            Type::Custom(c) => f.write_str(c.0.name()),
+            Type::Decimal(p, s) => write!(f, "decimal({},{})", p, s),
             Type::F32 => f.write_str("f32"),
             Type::F64 => f.write_str("f64"),
             Type::I16 => f.write_str("i16"),
@@ -110,6 +214,7 @@ impl<'a> Display for Type<'a> {
             Type::U32 => f.write_str("u32"),
             Type::U64 => f.write_str("u64"),
             Type::U8 => f.write_str("u8"),
+            Type::Uuid => f.write_str("uuid"),
             Type::Null => f.write_str("null"),
             Type::Enum(v) => {
                 f.write_str("enum(")?;
@@ -136,11 +241,47 @@ impl<'a> Display for Type<'a> {
 }
 
 impl<'a> Type<'a> {
+    /// Copy the values of enums and sets, so the type no longer borrows
+    /// from the source it was parsed from
+    pub fn into_owned(self) -> Type<'static> {
+        let own = |v: Arc<Vec<Cow<'a, str>>>| {
+            Arc::new(
+                v.iter()
+                    .map(|v| Cow::Owned(v.clone().into_owned()))
+                    .collect(),
+            )
+        };
+        match self {
+            Type::Args(t, a) => Type::Args(t, a),
+            Type::Base(t) => Type::Base(t),
+            Type::Custom(c) => Type::Custom(c),
+            Type::Decimal(p, s) => Type::Decimal(p, s),
+            Type::Enum(v) => Type::Enum(own(v)),
+            Type::F32 => Type::F32,
+            Type::F64 => Type::F64,
+            Type::I16 => Type::I16,
+            Type::I32 => Type::I32,
+            Type::I64 => Type::I64,
+            Type::I8 => Type::I8,
+            Type::Invalid => Type::Invalid,
+            Type::JSON => Type::JSON,
+            Type::Null => Type::Null,
+            Type::Set(v) => Type::Set(own(v)),
+            Type::U16 => Type::U16,
+            Type::U32 => Type::U32,
+            Type::U64 => Type::U64,
+            Type::U8 => Type::U8,
+            Type::Uuid => Type::Uuid,
+        }
+    }
+
     /// Compute the canonical base type
     pub fn base(&self) -> BaseType {
         match self {
             Type::Args(t, _) => *t,
             Type::Base(t) => *t,
+            Type::Custom(c) => c.0.base(),
+            Type::Decimal(_, _) => BaseType::Float,
             Type::Enum(_) => BaseType::String,
             Type::F32 => BaseType::Float,
             Type::F64 => BaseType::Float,
@@ -156,6 +297,21 @@ impl<'a> Type<'a> {
             Type::U32 => BaseType::Integer,
             Type::U64 => BaseType::Integer,
             Type::U8 => BaseType::Integer,
+            Type::Uuid => BaseType::String,
+        }
+    }
+
+    /// Precision and scale of decimals, and of integers as they are
+    /// promoted when combined with decimals
+    pub(crate) fn decimal_digits(&self) -> Option<(u8, u8)> {
+        match self {
+            Type::Decimal(p, s) => Some((*p, *s)),
+            Type::I8 | Type::U8 => Some((3, 0)),
+            Type::I16 | Type::U16 => Some((5, 0)),
+            Type::I32 | Type::U32 => Some((10, 0)),
+            Type::I64 => Some((19, 0)),
+            Type::U64 | Type::Base(BaseType::Integer) => Some((20, 0)),
+            _ => None,
         }
     }
 }
@@ -167,28 +323,126 @@ impl<'a> From<BaseType> for Type<'a> {
 }
 
 /// Represent a type with not_null information
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The remaining fields describe the declaration of the column a value comes
+/// from. They are not compared or hashed, so two types are equal if they have
+/// the same type and nullability
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FullType<'a> {
     pub t: Type<'a>,
     pub not_null: bool,
     pub list_hack: bool,
+    /// Number of fractional second digits of temporal columns declared
+    /// with an explicit precision, as in DATETIME(6)
+    pub precision: Option<u8>,
+    /// Maximal number of characters of string columns declared with a
+    /// length, as in VARCHAR(100)
+    pub length: Option<u32>,
+    /// The column is a CHAR column, whose values are padded to the length
+    pub fixed_length: bool,
+    /// Character set of string columns declared with one, directly or
+    /// through the default of the table
+    pub charset: Option<Cow<'a, str>>,
+    /// Collation of string columns declared with one, directly or through
+    /// the default of the table
+    pub collation: Option<Cow<'a, str>>,
+    /// Size class of TEXT and BLOB columns, so code generators may choose to
+    /// stream large values
+    pub size_class: Option<SizeClass>,
+}
+
+impl PartialEq for FullType<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.t == other.t && self.not_null == other.not_null && self.list_hack == other.list_hack
+    }
+}
+
+impl Eq for FullType<'_> {}
+
+impl core::hash::Hash for FullType<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.t.hash(state);
+        self.not_null.hash(state);
+        self.list_hack.hash(state);
+    }
 }
 
 impl<'a> FullType<'a> {
-    pub(crate) fn new(t: impl Into<Type<'a>>, not_null: bool) -> Self {
+    /// Construct a new type
+    pub fn new(t: impl Into<Type<'a>>, not_null: bool) -> Self {
         Self {
             t: t.into(),
             not_null,
             list_hack: false,
+            precision: None,
+            length: None,
+            fixed_length: false,
+            charset: None,
+            collation: None,
+            size_class: None,
+        }
+    }
+
+    /// Copy the values of enums and sets, so the type no longer borrows
+    /// from the source it was parsed from
+    pub fn into_owned(self) -> FullType<'static> {
+        FullType {
+            t: self.t.into_owned(),
+            not_null: self.not_null,
+            list_hack: self.list_hack,
+            precision: self.precision,
+            length: self.length,
+            fixed_length: self.fixed_length,
+            charset: self.charset.map(|v| Cow::Owned(v.into_owned())),
+            collation: self.collation.map(|v| Cow::Owned(v.into_owned())),
+            size_class: self.size_class,
         }
     }
 
+    /// Are the types equal including the column declaration they describe
+    pub(crate) fn same_declaration(&self, other: &FullType<'_>) -> bool {
+        self == other
+            && self.precision == other.precision
+            && self.length == other.length
+            && self.fixed_length == other.fixed_length
+            && self.charset == other.charset
+            && self.collation == other.collation
+            && self.size_class == other.size_class
+    }
+
+    /// Hash the type including the column declaration it describes
+    pub(crate) fn hash_declaration<H: core::hash::Hasher>(&self, state: &mut H) {
+        use core::hash::Hash;
+        self.hash(state);
+        self.precision.hash(state);
+        self.length.hash(state);
+        self.fixed_length.hash(state);
+        self.charset.hash(state);
+        self.collation.hash(state);
+        self.size_class.hash(state);
+    }
+
+    /// Copy the character set and collation of other, for string values
+    /// derived from values of type other
+    pub(crate) fn with_collation_of(mut self, other: &FullType<'a>) -> Self {
+        self.charset = other.charset.clone();
+        self.collation = other.collation.clone();
+        self
+    }
+
     /// Construct a new invalid type
     pub fn invalid() -> Self {
         Self {
             t: Type::Invalid,
             not_null: false,
             list_hack: false,
+            precision: None,
+            length: None,
+            fixed_length: false,
+            charset: None,
+            collation: None,
+            size_class: None,
         }
     }
 }
@@ -204,6 +458,9 @@ impl<'a> core::ops::Deref for FullType<'a> {
 impl<'a> Display for FullType<'a> {
     fn fmt(&self, f: &mut alloc::fmt::Formatter<'_>) -> alloc::fmt::Result {
         self.t.fmt(f)?;
+        if let Some(precision) = self.precision {
+            write!(f, "({})", precision)?;
+        }
         if self.list_hack {
             f.write_str(" list_hack")?;
         }
@@ -16,10 +16,65 @@ use sql_parse::{BinaryOperator, Expression, Span};
 use crate::{
     type_::{BaseType, FullType},
     type_expression::{type_expression, ExpressionFlags},
-    typer::Typer,
+    typer::{column_name, Typer},
     Type,
 };
 
+/// Warn about comparing temporal values of different fractional second
+/// precision, as the more precise value is not truncated before comparing
+fn check_precision<'a>(
+    typer: &mut Typer<'a, '_>,
+    op_span: &Span,
+    lhs: &Expression<'a>,
+    lhs_type: &FullType<'a>,
+    rhs: &Expression<'a>,
+    rhs_type: &FullType<'a>,
+) {
+    if let (Some(l), Some(r)) = (lhs_type.precision, rhs_type.precision) {
+        if l != r {
+            typer
+                .warn(
+                    "Comparison of temporal values with different precision",
+                    op_span,
+                )
+                .frag(format!("Has {} fractional digits", l), lhs)
+                .frag(format!("Has {} fractional digits", r), rhs);
+        }
+    }
+}
+
+/// Precision and scale of an operand combined with a decimal, integer
+/// literals take the digits they have
+fn decimal_digits(e: &Expression<'_>, t: &Type<'_>) -> Option<(u8, u8)> {
+    match (e, t) {
+        (Expression::Integer((v, _)), Type::Base(BaseType::Integer)) => {
+            Some((v.checked_ilog10().unwrap_or(0) as u8 + 1, 0))
+        }
+        _ => t.decimal_digits(),
+    }
+}
+
+/// Type of the arithmetic on a decimal and a decimal or an integer, following
+/// the precision and scale rules of MySQL and MariaDB
+fn decimal_arithmetic(op: &BinaryOperator, l: (u8, u8), r: (u8, u8)) -> Type<'static> {
+    let ((p1, s1), (p2, s2)) = ((l.0 as usize, l.1 as usize), (r.0 as usize, r.1 as usize));
+    let (p, s) = match op {
+        BinaryOperator::Add | BinaryOperator::Subtract => {
+            let s = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + s + 1, s)
+        }
+        BinaryOperator::Mult => (p1 + p2, s1 + s2),
+        // The scale is extended by div_precision_increment, which defaults to 4
+        BinaryOperator::Divide => (p1 + s2 + 4, s1 + 4),
+        _ => {
+            let s = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + s, s)
+        }
+    };
+    let s = s.min(30);
+    Type::Decimal(p.clamp(s, 65) as u8, s as u8)
+}
+
 pub(crate) fn type_binary_expression<'a>(
     typer: &mut Typer<'a, '_>,
     op: &BinaryOperator,
@@ -81,6 +136,17 @@ pub(crate) fn type_binary_expression<'a>(
 
     let lhs_type = type_expression(typer, lhs, flags, context);
     let rhs_type = type_expression(typer, rhs, flags, context);
+    if !matches!(
+        op,
+        BinaryOperator::Or | BinaryOperator::Xor | BinaryOperator::And
+    ) {
+        if let Some(name) = column_name(lhs) {
+            typer.suggest_arg_name(rhs, name);
+        }
+        if let Some(name) = column_name(rhs) {
+            typer.suggest_arg_name(lhs, name);
+        }
+    }
     match op {
         BinaryOperator::Or | BinaryOperator::Xor | BinaryOperator::And => {
             typer.ensure_base(lhs, &lhs_type, BaseType::Bool);
@@ -99,21 +165,42 @@ pub(crate) fn type_binary_expression<'a>(
             if rhs_type.t == Type::Null {
                 typer.warn("Comparison with null", rhs);
             }
+            typer.check_string_literal(lhs, &rhs_type);
+            typer.check_string_literal(rhs, &lhs_type);
+            typer.check_collations(
+                "Comparison of strings with different collations",
+                op_span,
+                (lhs, &lhs_type),
+                (rhs, &rhs_type),
+            );
+            typer.check_uuid_comparison(op_span, (lhs, &lhs_type), (rhs, &rhs_type));
             if typer.matched_type(&lhs_type, &rhs_type).is_none() {
                 typer
-                    .err("Type error in comparison", op_span)
+                    .err_type_mismatch(
+                        "Type error in comparison",
+                        op_span,
+                        &rhs_type.t,
+                        core::slice::from_ref(&lhs_type.t),
+                    )
                     .frag(format!("Of type {}", lhs_type.t), lhs)
                     .frag(format!("Of type {}", rhs_type.t), rhs);
             }
+            check_precision(typer, op_span, lhs, &lhs_type, rhs, &rhs_type);
             FullType::new(BaseType::Bool, lhs_type.not_null && rhs_type.not_null)
         }
         BinaryOperator::NullSafeEq => {
             if typer.matched_type(&lhs_type, &rhs_type).is_none() {
                 typer
-                    .err("Type error in comparison", op_span)
+                    .err_type_mismatch(
+                        "Type error in comparison",
+                        op_span,
+                        &rhs_type.t,
+                        core::slice::from_ref(&lhs_type.t),
+                    )
                     .frag(format!("Of type {}", lhs_type.t), lhs)
                     .frag(format!("Of type {}", rhs_type.t), rhs);
             }
+            check_precision(typer, op_span, lhs, &lhs_type, rhs, &rhs_type);
             FullType::new(BaseType::Bool, true)
         }
         BinaryOperator::ShiftLeft
@@ -131,6 +218,31 @@ pub(crate) fn type_binary_expression<'a>(
         | BinaryOperator::Div
         | BinaryOperator::Mod
         | BinaryOperator::Mult => {
+            let not_null = lhs_type.not_null && rhs_type.not_null;
+            let l = decimal_digits(lhs, &lhs_type.t);
+            let r = decimal_digits(rhs, &rhs_type.t);
+            if matches!(lhs_type.t, Type::Decimal(..)) || matches!(rhs_type.t, Type::Decimal(..)) {
+                let t = match (l, r) {
+                    (Some(l), Some(r)) => Some(decimal_arithmetic(op, l, r)),
+                    // Arguments and nulls take the type of the decimal
+                    (Some(_), None) if rhs_type.base() == BaseType::Any => {
+                        typer.matched_type(&lhs_type, &rhs_type);
+                        Some(lhs_type.t.clone())
+                    }
+                    (None, Some(_)) if lhs_type.base() == BaseType::Any => {
+                        typer.matched_type(&lhs_type, &rhs_type);
+                        Some(rhs_type.t.clone())
+                    }
+                    _ => None,
+                };
+                if let Some(t) = t {
+                    return if matches!(op, BinaryOperator::Div) {
+                        FullType::new(BaseType::Integer, not_null)
+                    } else {
+                        FullType::new(t, not_null)
+                    };
+                }
+            }
             if let Some(t) = typer.matched_type(&lhs_type, &rhs_type) {
                 match t.base() {
                     BaseType::Any | BaseType::Float | BaseType::Integer => {
@@ -146,13 +258,24 @@ pub(crate) fn type_binary_expression<'a>(
                 }
             } else {
                 typer
-                    .err("Type error in addition/subtraction", op_span)
+                    .err_type_mismatch(
+                        "Type error in addition/subtraction",
+                        op_span,
+                        &rhs_type.t,
+                        core::slice::from_ref(&lhs_type.t),
+                    )
                     .frag(format!("type {}", lhs_type.t), lhs)
                     .frag(format!("type {}", rhs_type.t), rhs);
                 FullType::invalid()
             }
         }
         BinaryOperator::Like | BinaryOperator::NotLike => {
+            typer.check_collations(
+                "Comparison of strings with different collations",
+                op_span,
+                (lhs, &lhs_type),
+                (rhs, &rhs_type),
+            );
             typer.ensure_base(lhs, &lhs_type, BaseType::String);
             typer.ensure_base(rhs, &rhs_type, BaseType::String);
             FullType::new(BaseType::Bool, lhs_type.not_null && rhs_type.not_null)
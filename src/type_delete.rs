@@ -11,13 +11,13 @@
 // limitations under the License.
 
 use alloc::vec::Vec;
-use sql_parse::{Delete, OptSpanned, Spanned};
+use sql_parse::{Delete, Identifier, OptSpanned, Spanned, TruncateTable};
 
 use crate::{
     type_expression::{type_expression, ExpressionFlags},
-    type_reference::type_reference,
+    type_reference::{base_tables, type_reference},
     type_select::{type_select_exprs, SelectType},
-    typer::{typer_stack, unqualified_name, ReferenceType, Typer},
+    typer::{typer_stack, ReferenceType, Typer},
     SelectTypeColumn,
 };
 
@@ -46,10 +46,38 @@ pub(crate) fn type_delete<'a>(
         for reference in &delete.using {
             type_reference(typer, reference, false);
         }
+        let mut tables = Vec::new();
+        for reference in &delete.using {
+            base_tables(reference, &mut tables);
+        }
+        let options = typer.options;
+        let same = |a: &Identifier<'_>, b: &Identifier<'_>| options.same_name(a.value, b.value);
         for table in &delete.tables {
-            let identifier = unqualified_name(typer.issues, table);
-            if typer.get_schema(identifier.value).is_none() {
-                typer.err("Unknown table or view", identifier);
+            let found = tables
+                .iter()
+                .any(|(name, qualified)| match table.prefix.as_slice() {
+                    [] => same(name, &table.identifier),
+                    prefix => {
+                        same(&qualified.identifier, &table.identifier)
+                            && qualified.prefix.len() == prefix.len()
+                            && qualified
+                                .prefix
+                                .iter()
+                                .zip(prefix)
+                                .all(|(a, b)| same(&a.0, &b.0))
+                    }
+                });
+            if found {
+                continue;
+            }
+            if typer
+                .reference_types
+                .iter()
+                .any(|r| r.name.as_ref().is_some_and(|n| same(n, &table.identifier)))
+            {
+                typer.err("Only tables can be deleted from", table);
+            } else {
+                typer.err("Unknown table in multi delete", table);
             }
         }
     } else {
@@ -59,7 +87,7 @@ pub(crate) fn type_delete<'a>(
                 &delete.tables.opt_span().unwrap(),
             );
         }
-        let identifier = unqualified_name(typer.issues, &delete.tables[0]);
+        let identifier = typer.local_name(&delete.tables[0]);
         if let Some(s) = typer.get_schema(identifier.value) {
             let mut columns = Vec::new();
             for col in &s.columns {
@@ -67,8 +95,10 @@ pub(crate) fn type_delete<'a>(
             }
             typer.reference_types.push(ReferenceType {
                 name: Some(identifier.clone()),
+                database: None,
                 span: identifier.span(),
                 columns,
+                using: Vec::new(),
             });
         } else {
             typer.err("", identifier);
@@ -101,3 +131,36 @@ pub(crate) fn type_delete<'a>(
         None => None,
     }
 }
+
+pub(crate) fn type_truncate<'a>(typer: &mut Typer<'a, '_>, truncate: &TruncateTable<'a>) {
+    let name = &truncate.table_name;
+    let schema = match name.prefix.as_slice() {
+        [(database, _)] if !typer.options.is_default_database(database.value) => {
+            let schema = typer
+                .schemas
+                .databases
+                .get(database.value)
+                .and_then(|tables| tables.get(name.identifier.value));
+            if schema.is_some() {
+                typer
+                    .info
+                    .database_tables
+                    .push((database.value, name.identifier.value));
+            }
+            schema
+        }
+        _ => {
+            let identifier = typer.local_name(name);
+            typer.get_schema(identifier.value)
+        }
+    };
+    match schema {
+        Some(s) if s.view => {
+            typer.err("Views can not be truncated", name);
+        }
+        Some(_) => (),
+        None => {
+            typer.err("Unknown table", name);
+        }
+    }
+}
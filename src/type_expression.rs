@@ -15,12 +15,13 @@ use core::ops::Deref;
 use sql_parse::{issue_todo, Expression, Identifier, Span, UnaryOperator, Variable};
 
 use crate::{
+    cardinality::at_most_one_row,
     schema::parse_column,
     type_::{ArgType, BaseType, FullType},
     type_binary_expression::type_binary_expression,
-    type_function::type_function,
+    type_function::{is_window_function, type_function},
     type_select::{resolve_kleene_identifier, type_union_select},
-    typer::Typer,
+    typer::{column_name, Typer},
     Type,
 };
 
@@ -70,11 +71,21 @@ fn type_unary_expression<'a>(
         | UnaryOperator::Minus => {
             let op_type = type_expression(typer, operand, flags.with_true(false), BaseType::Any);
             let t = match &op_type.t {
-                Type::Args(..) | Type::Base(..) | Type::Enum(..) | Type::JSON | Type::Set(..) => {
+                Type::Custom(c) if matches!(c.0.base(), BaseType::Integer | BaseType::Float) => {
+                    op_type.t
+                }
+                Type::Args(..)
+                | Type::Base(..)
+                | Type::Custom(..)
+                | Type::Enum(..)
+                | Type::JSON
+                | Type::Set(..)
+                | Type::Uuid => {
                     typer.err(format!("Expected numeric type got {}", op_type.t), op_span);
                     Type::Invalid
                 }
-                Type::F32
+                Type::Decimal(..)
+                | Type::F32
                 | Type::F64
                 | Type::I16
                 | Type::I32
@@ -97,6 +108,28 @@ fn type_unary_expression<'a>(
     }
 }
 
+/// IN lists with more arguments than this are warned about when a maximal
+/// number of arguments is configured
+const LARGE_IN_LIST: usize = 1000;
+
+/// Report the first argument exceeding the configured maximal number of
+/// arguments. Arguments may be numbered with gaps, so any index past the
+/// maximum exceeds it, but it is only reported once per statement
+fn check_argument_count(typer: &mut Typer<'_, '_>, idx: usize, span: &Span) {
+    if let Some(max) = typer.options.max_arguments {
+        if idx < max {
+            return;
+        }
+        if !typer.info.too_many_arguments {
+            typer.info.too_many_arguments = true;
+            typer.err(
+                format!("Statement uses more than the allowed {} arguments", max),
+                span,
+            );
+        }
+    }
+}
+
 pub(crate) fn type_expression<'a>(
     typer: &mut Typer<'a, '_>,
     expression: &Expression<'a>,
@@ -117,6 +150,11 @@ pub(crate) fn type_expression<'a>(
         } => type_unary_expression(typer, op, op_span, operand, flags),
         Expression::Subquery(select) => {
             let select_type = type_union_select(typer, select, false);
+            if typer.options.warn_multi_row_subquery
+                && !at_most_one_row(typer.schemas, select, typer.options)
+            {
+                typer.warn("Scalar subquery may return more than one row", select);
+            }
             if let [v] = select_type.columns.as_slice() {
                 let mut r = v.type_.clone();
                 r.not_null = false;
@@ -135,7 +173,12 @@ pub(crate) fn type_expression<'a>(
         Expression::String(_) => FullType::new(BaseType::String, true),
         Expression::Integer(_) => FullType::new(BaseType::Integer, true),
         Expression::Float(_) => FullType::new(BaseType::Float, true),
-        Expression::Function(func, args, span) => type_function(typer, func, args, span, flags),
+        Expression::Function(func, args, span) => {
+            if is_window_function(func) {
+                typer.err("Window function requires an OVER clause", span);
+            }
+            type_function(typer, func, args, span, flags)
+        }
         Expression::WindowFunction {
             function,
             args,
@@ -149,6 +192,7 @@ pub(crate) fn type_expression<'a>(
             type_function(typer, function, args, function_span, flags)
         }
         Expression::Identifier(i) => {
+            let options = typer.options;
             let mut t = None;
             match i.as_slice() {
                 [part] => {
@@ -161,8 +205,11 @@ pub(crate) fn type_expression<'a>(
                     };
                     let mut cnt = 0;
                     for r in &mut typer.reference_types {
+                        if r.is_using(col, options) {
+                            continue;
+                        }
                         for c in &mut r.columns {
-                            if c.0 == *col {
+                            if options.same_name(&c.0, col) {
                                 cnt += 1;
                                 if flags.not_null {
                                     c.1.not_null = true;
@@ -173,9 +220,13 @@ pub(crate) fn type_expression<'a>(
                     }
                     if cnt > 1 {
                         let mut issue = typer.issues.err("Ambiguous reference", col);
-                        for r in &typer.reference_types {
+                        for r in typer
+                            .reference_types
+                            .iter()
+                            .filter(|r| !r.is_using(col, options))
+                        {
                             for c in &r.columns {
-                                if c.0 == *col {
+                                if options.same_name(&c.0, col) {
                                     issue.frag("Defined here", &r.span);
                                 }
                             }
@@ -198,10 +249,49 @@ pub(crate) fn type_expression<'a>(
                             return FullType::invalid();
                         }
                     };
+                    let mut cnt = 0;
+                    for r in &mut typer.reference_types {
+                        if r.name.as_ref().is_some_and(|n| options.same_name(n, tbl)) {
+                            cnt += 1;
+                            for c in &mut r.columns {
+                                if options.same_name(&c.0, col) {
+                                    if flags.not_null {
+                                        c.1.not_null = true;
+                                    }
+                                    t = Some(c);
+                                }
+                            }
+                        }
+                    }
+                    if cnt > 1 {
+                        // Tables of the same name from different databases
+                        let mut issue = typer.issues.err("Ambiguous reference", tbl);
+                        for r in &typer.reference_types {
+                            if r.name.as_ref().is_some_and(|n| options.same_name(n, tbl)) {
+                                issue.frag("Defined here", &r.span);
+                            }
+                        }
+                        return FullType::invalid();
+                    }
+                }
+                [p0, p1, p2] => {
+                    let (
+                        sql_parse::IdentifierPart::Name(db),
+                        sql_parse::IdentifierPart::Name(tbl),
+                        sql_parse::IdentifierPart::Name(col),
+                    ) = (p0, p1, p2)
+                    else {
+                        typer.err("Not supported here", expression);
+                        return FullType::invalid();
+                    };
                     for r in &mut typer.reference_types {
-                        if r.name == Some(tbl.clone()) {
+                        if r.database
+                            .as_ref()
+                            .is_some_and(|d| options.same_name(d, db))
+                            && r.name.as_ref().is_some_and(|n| options.same_name(n, tbl))
+                        {
                             for c in &mut r.columns {
-                                if c.0 == *col {
+                                if options.same_name(&c.0, col) {
                                     if flags.not_null {
                                         c.1.not_null = true;
                                     }
@@ -224,13 +314,17 @@ pub(crate) fn type_expression<'a>(
                 Some((_, type_)) => type_.clone(),
             }
         }
-        Expression::Arg((idx, span)) => FullType::new(
-            Type::Args(
-                BaseType::Any,
-                Arc::new(vec![(*idx, ArgType::Normal, span.clone())]),
-            ),
-            false,
-        ),
+        Expression::Arg((idx, span)) => {
+            check_argument_count(typer, *idx, span);
+            typer.use_arg(*idx);
+            FullType::new(
+                Type::Args(
+                    BaseType::Any,
+                    Arc::new(vec![(*idx, ArgType::Normal, span.clone())]),
+                ),
+                false,
+            )
+        }
         Expression::Exists(s) => {
             type_union_select(typer, s, false);
             FullType::new(BaseType::Bool, true)
@@ -249,7 +343,26 @@ pub(crate) fn type_expression<'a>(
             // Hack to allow null arguments on the right hand side of an in expression
             // where the lhs is not null
             lhs_type.not_null = false;
+            let lhs_name = column_name(lhs);
+            if typer.options.max_arguments.is_some() {
+                let placeholders = rhs
+                    .iter()
+                    .filter(|e| matches!(e, Expression::Arg(_)))
+                    .count();
+                if placeholders > LARGE_IN_LIST {
+                    typer.warn(
+                        format!(
+                            "IN list with {} arguments, consider using a temporary table",
+                            placeholders
+                        ),
+                        in_span,
+                    );
+                }
+            }
             for rhs in rhs {
+                if let Some(name) = lhs_name {
+                    typer.suggest_arg_name(rhs, name);
+                }
                 let rhs_type = match rhs {
                     Expression::Subquery(q) => {
                         let rhs_type = type_union_select(typer, q, false);
@@ -268,19 +381,29 @@ pub(crate) fn type_expression<'a>(
                             FullType::invalid()
                         }
                     }
-                    Expression::ListHack((idx, span)) => FullType::new(
-                        Type::Args(
-                            BaseType::Any,
-                            Arc::new(vec![(*idx, ArgType::ListHack, span.clone())]),
-                        ),
-                        false,
-                    ),
+                    Expression::ListHack((idx, span)) => {
+                        check_argument_count(typer, *idx, span);
+                        typer.use_arg(*idx);
+                        FullType::new(
+                            Type::Args(
+                                BaseType::Any,
+                                Arc::new(vec![(*idx, ArgType::ListHack, span.clone())]),
+                            ),
+                            false,
+                        )
+                    }
                     _ => type_expression(typer, rhs, flags.without_values(), BaseType::Any),
                 };
                 not_null &= rhs_type.not_null;
+                typer.check_string_literal(rhs, &lhs_type);
                 if typer.matched_type(&lhs_type, &rhs_type).is_none() {
                     typer
-                        .err("Incompatible types", in_span)
+                        .err_type_mismatch(
+                            "Incompatible types",
+                            in_span,
+                            &rhs_type.t,
+                            core::slice::from_ref(&lhs_type.t),
+                        )
                         .frag(lhs_type.t.to_string(), lhs)
                         .frag(rhs_type.to_string(), rhs);
                 }
@@ -342,7 +465,12 @@ pub(crate) fn type_expression<'a>(
                     typer.ensure_base(&when.when, &op_type, BaseType::Bool);
                     let t2 = type_expression(typer, &when.then, flags, BaseType::Any);
                     if let Some(t1) = t {
-                        t = typer.matched_type(&t1, &t2.t)
+                        // Arms of unrelated types give a string
+                        t = Some(
+                            typer
+                                .matched_type(&t1, &t2.t)
+                                .unwrap_or_else(|| BaseType::String.into()),
+                        );
                     } else {
                         t = Some(t2.t);
                     }
@@ -350,7 +478,12 @@ pub(crate) fn type_expression<'a>(
                 if let Some((_, else_)) = else_ {
                     let t2 = type_expression(typer, else_, flags, BaseType::Any);
                     if let Some(t1) = t {
-                        t = typer.matched_type(&t1, &t2.t)
+                        // Arms of unrelated types give a string
+                        t = Some(
+                            typer
+                                .matched_type(&t1, &t2.t)
+                                .unwrap_or_else(|| BaseType::String.into()),
+                        );
                     } else {
                         t = Some(t2.t);
                     }
@@ -372,6 +505,7 @@ pub(crate) fn type_expression<'a>(
                 type_.clone(),
                 Identifier::new("", as_span.clone()),
                 typer.issues,
+                typer.options,
             );
             if typer.dialect().is_maria() {
                 match type_.type_ {
@@ -383,6 +517,7 @@ pub(crate) fn type_expression<'a>(
                     | sql_parse::Type::Double(_)
                     | sql_parse::Type::Float8
                     | sql_parse::Type::Float(_)
+                    | sql_parse::Type::Numeric(_, _, _)
                     | sql_parse::Type::Integer(_)
                     | sql_parse::Type::Int(_)
                     | sql_parse::Type::Binary(_)
@@ -399,7 +534,6 @@ pub(crate) fn type_expression<'a>(
                     | sql_parse::Type::LongText(_)
                     | sql_parse::Type::Enum(_)
                     | sql_parse::Type::Set(_)
-                    | sql_parse::Type::Numeric(_, _, _)
                     | sql_parse::Type::Timestamp(_)
                     | sql_parse::Type::TinyBlob(_)
                     | sql_parse::Type::MediumBlob(_)
@@ -419,7 +553,9 @@ pub(crate) fn type_expression<'a>(
             }
             let e = type_expression(typer, expr, flags, col.type_.base());
             //TODO check if it can possible be valid cast
-            FullType::new(col.type_.t, e.not_null)
+            let mut t = FullType::new(col.type_.t, e.not_null);
+            t.precision = col.type_.precision;
+            t
         }
         Expression::Count { expr, .. } => {
             match expr.deref() {
@@ -433,8 +569,8 @@ pub(crate) fn type_expression<'a>(
             FullType::new(BaseType::Integer, true)
         }
         Expression::GroupConcat { expr, .. } => {
-            type_expression(typer, expr, flags.without_values(), BaseType::Any);
-            FullType::new(BaseType::String, true)
+            let t = type_expression(typer, expr, flags.without_values(), BaseType::Any);
+            FullType::new(BaseType::String, typer.grouped && t.not_null)
         }
         Expression::Variable {
             variable,
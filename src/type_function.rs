@@ -14,9 +14,11 @@ use alloc::{format, vec::Vec};
 use sql_parse::{Expression, Function, Span};
 
 use crate::{
+    json_path::validate_json_path,
+    schema::Functions,
     type_::{BaseType, FullType},
     type_expression::{type_expression, ExpressionFlags},
-    typer::Typer,
+    typer::{column_name, Typer},
     Type,
 };
 
@@ -71,6 +73,447 @@ fn typed_args<'a, 'b, 'c>(
     typed
 }
 
+pub(crate) fn is_temporal(t: BaseType) -> bool {
+    matches!(
+        t,
+        BaseType::Date | BaseType::DateTime | BaseType::TimeStamp | BaseType::Time
+    )
+}
+
+/// Ensure that an argument has the expected base type, where dates and times
+/// may be given as any kind of date or time or as a string
+fn ensure_arg_base<'a>(
+    typer: &mut Typer<'a, '_>,
+    arg: &Expression<'a>,
+    t: &FullType<'a>,
+    expected: BaseType,
+) {
+    if is_temporal(expected) && (is_temporal(t.base()) || t.base() == BaseType::String) {
+        return;
+    }
+    typer.ensure_base(arg, t, expected);
+}
+
+/// Ensure that an argument is an integer or a float
+fn ensure_numeric<'a>(typer: &mut Typer<'a, '_>, arg: &Expression<'a>, t: &FullType<'a>) {
+    match t.base() {
+        BaseType::Integer | BaseType::Float => (),
+        BaseType::Any => typer.ensure_base(arg, t, BaseType::Float),
+        _ => {
+            typer.err_type_mismatch(
+                format!("Expected numeric type got {}", t.t),
+                arg,
+                &t.t,
+                &[BaseType::Integer.into(), BaseType::Float.into()],
+            );
+        }
+    }
+}
+
+/// Type an argument to a hash, checksum or encoding function, that may be
+/// given either as a string or as bytes
+fn type_data_arg<'a>(
+    typer: &mut Typer<'a, '_>,
+    arg: &Expression<'a>,
+    flags: ExpressionFlags,
+) -> FullType<'a> {
+    let t = type_expression(typer, arg, flags.without_values(), BaseType::Any);
+    match t.base() {
+        BaseType::String | BaseType::Bytes => (),
+        BaseType::Any => typer.ensure_base(arg, &t, BaseType::String),
+        _ => {
+            typer.err_type_mismatch(
+                format!("Expected type String or Bytes got {}", t.t),
+                arg,
+                &t.t,
+                &[BaseType::String.into(), BaseType::Bytes.into()],
+            );
+        }
+    }
+    t
+}
+
+/// Validate the JSON path given by the argument if it is a string literal
+fn check_json_path<'a>(typer: &mut Typer<'a, '_>, arg: &Expression<'a>) {
+    if let Expression::String(s) = arg {
+        if let Err(message) = validate_json_path(&s.value) {
+            typer.err(message, s);
+        }
+    }
+}
+
+/// Check that the argument is a unit as used by TIMESTAMPDIFF and TIMESTAMPADD
+fn check_time_unit<'a>(typer: &mut Typer<'a, '_>, arg: &Expression<'a>) {
+    if let Some(name) = column_name(arg) {
+        if matches!(
+            name.to_ascii_uppercase().as_str(),
+            "MICROSECOND"
+                | "SECOND"
+                | "MINUTE"
+                | "HOUR"
+                | "DAY"
+                | "WEEK"
+                | "MONTH"
+                | "QUARTER"
+                | "YEAR"
+        ) {
+            return;
+        }
+    }
+    typer.err("Expected time unit", arg);
+}
+
+/// Signature of a function typed only by a fixed list of arguments
+struct Signature {
+    /// Type returned, not null when all arguments are not null
+    return_type: Type<'static>,
+    /// Base types of the required arguments
+    required: &'static [BaseType],
+    /// Base types of the optional arguments following the required ones
+    optional: &'static [BaseType],
+    /// The result may be null even when all arguments are not null
+    nullable: bool,
+}
+
+/// Look up the signature of functions typed only by their arguments
+fn signature(func: &Function<'_>) -> Option<Signature> {
+    let (return_type, required, optional, nullable): (
+        Type<'static>,
+        &'static [BaseType],
+        &'static [BaseType],
+        bool,
+    ) = match func {
+        Function::Rand => (Type::F64, &[], &[BaseType::Integer], false),
+        Function::Pi => (Type::F64, &[], &[], false),
+        Function::Right | Function::Left => (
+            BaseType::String.into(),
+            &[BaseType::String, BaseType::Integer],
+            &[],
+            false,
+        ),
+        Function::FindInSet => (
+            BaseType::Integer.into(),
+            &[BaseType::String, BaseType::String],
+            &[],
+            false,
+        ),
+        Function::SubStringIndex => (
+            BaseType::String.into(),
+            &[BaseType::String, BaseType::String, BaseType::Integer],
+            &[],
+            false,
+        ),
+        Function::ExtractValue => (
+            BaseType::String.into(),
+            &[BaseType::String, BaseType::String],
+            &[],
+            false,
+        ),
+        Function::Replace => (
+            BaseType::String.into(),
+            &[BaseType::String, BaseType::String, BaseType::String],
+            &[],
+            false,
+        ),
+        Function::CharacterLength => (BaseType::Integer.into(), &[BaseType::String], &[], false),
+        Function::Now => (BaseType::DateTime.into(), &[], &[BaseType::Integer], false),
+        Function::CurDate => (BaseType::Date.into(), &[], &[], false),
+        Function::CurrentTimestamp => {
+            (BaseType::TimeStamp.into(), &[], &[BaseType::Integer], false)
+        }
+        Function::CurTime | Function::UtcTime => {
+            (BaseType::Time.into(), &[], &[BaseType::Integer], false)
+        }
+        Function::UtcDate => (BaseType::Date.into(), &[], &[], false),
+        Function::UtcTimeStamp => (BaseType::DateTime.into(), &[], &[BaseType::Integer], false),
+        Function::Date => (BaseType::Date.into(), &[BaseType::DateTime], &[], false),
+        Function::Time => (BaseType::Time.into(), &[BaseType::DateTime], &[], false),
+        Function::DayOfMonth
+        | Function::DayOfWeek
+        | Function::DayOfYear
+        | Function::Weekday
+        | Function::WeekOfYear
+        | Function::Quarter
+        | Function::ToDays
+        | Function::ToSeconds => (BaseType::Integer.into(), &[BaseType::DateTime], &[], false),
+        Function::Week => (
+            BaseType::Integer.into(),
+            &[BaseType::DateTime],
+            &[BaseType::Integer],
+            false,
+        ),
+        Function::Minute | Function::Second | Function::MicroSecond | Function::TimeToSec => {
+            (BaseType::Integer.into(), &[BaseType::Time], &[], false)
+        }
+        Function::DayName | Function::MonthName => {
+            (BaseType::String.into(), &[BaseType::DateTime], &[], false)
+        }
+        Function::MakeDate => (
+            BaseType::Date.into(),
+            &[BaseType::Integer, BaseType::Integer],
+            &[],
+            false,
+        ),
+        Function::MakeTime => (
+            BaseType::Time.into(),
+            &[BaseType::Integer, BaseType::Integer, BaseType::Integer],
+            &[],
+            false,
+        ),
+        Function::SecToTime => (BaseType::Time.into(), &[BaseType::Integer], &[], false),
+        Function::FromDays => (BaseType::Date.into(), &[BaseType::Integer], &[], false),
+        Function::DateDiff => (
+            Type::I64,
+            &[BaseType::DateTime, BaseType::DateTime],
+            &[],
+            false,
+        ),
+        Function::TimeDiff => (
+            BaseType::Time.into(),
+            &[BaseType::Time, BaseType::Time],
+            &[],
+            false,
+        ),
+        Function::TimeFormat => (
+            BaseType::String.into(),
+            &[BaseType::Time, BaseType::String],
+            &[],
+            false,
+        ),
+        Function::StrToDate => (
+            BaseType::DateTime.into(),
+            &[BaseType::String, BaseType::String],
+            &[],
+            true,
+        ),
+        Function::LTrim
+        | Function::RTrim
+        | Function::Upper
+        | Function::UCase
+        | Function::Lower
+        | Function::LCase
+        | Function::Reverse => (BaseType::String.into(), &[BaseType::String], &[], false),
+        Function::LPad | Function::RPad => (
+            BaseType::String.into(),
+            &[BaseType::String, BaseType::Integer],
+            &[BaseType::String],
+            false,
+        ),
+        Function::Locate => (
+            BaseType::Integer.into(),
+            &[BaseType::String, BaseType::String],
+            &[BaseType::Integer],
+            false,
+        ),
+        Function::InStr => (
+            BaseType::Integer.into(),
+            &[BaseType::String, BaseType::String],
+            &[],
+            false,
+        ),
+        Function::UnHex => (BaseType::Bytes.into(), &[BaseType::String], &[], true),
+        Function::DateFormat => (
+            BaseType::String.into(),
+            &[BaseType::DateTime, BaseType::String],
+            &[BaseType::String],
+            false,
+        ),
+        Function::Other(name) => match name.to_ascii_uppercase().as_str() {
+            "ROW_NUMBER" | "RANK" | "DENSE_RANK" => (Type::U64, &[], &[], false),
+            "NTILE" => (Type::U64, &[BaseType::Integer], &[], false),
+            "YEAR" | "MONTH" | "DAY" => {
+                (BaseType::Integer.into(), &[BaseType::DateTime], &[], false)
+            }
+            "HOUR" => (BaseType::Integer.into(), &[BaseType::Time], &[], false),
+            "LAST_DAY" => (BaseType::Date.into(), &[BaseType::DateTime], &[], false),
+            "TRIM" => (BaseType::String.into(), &[BaseType::String], &[], false),
+            "LAST_INSERT_ID" => (Type::U64, &[], &[BaseType::Integer], false),
+            "ROW_COUNT" => (Type::I64, &[], &[], false),
+            "UUID" | "GEN_RANDOM_UUID" => (Type::Uuid, &[], &[], false),
+            "ST_GEOMFROMTEXT"
+            | "ST_GEOMETRYFROMTEXT"
+            | "ST_POINTFROMTEXT"
+            | "ST_LINEFROMTEXT"
+            | "ST_LINESTRINGFROMTEXT"
+            | "ST_POLYFROMTEXT"
+            | "ST_POLYGONFROMTEXT"
+            | "ST_GEOMFROMGEOJSON" => (
+                BaseType::Geometry.into(),
+                &[BaseType::String],
+                &[BaseType::Integer],
+                true,
+            ),
+            "ST_GEOMFROMWKB" | "ST_GEOMETRYFROMWKB" | "ST_POINTFROMWKB" => (
+                BaseType::Geometry.into(),
+                &[BaseType::Bytes],
+                &[BaseType::Integer],
+                true,
+            ),
+            "ST_X" | "ST_Y" | "ST_AREA" | "ST_LENGTH" => {
+                (Type::F64, &[BaseType::Geometry], &[], false)
+            }
+            "ST_DISTANCE" | "ST_DISTANCE_SPHERE" => (
+                Type::F64,
+                &[BaseType::Geometry, BaseType::Geometry],
+                &[],
+                false,
+            ),
+            "ST_CONTAINS" | "ST_WITHIN" | "ST_INTERSECTS" | "ST_DISJOINT" | "ST_EQUALS"
+            | "ST_OVERLAPS" | "ST_TOUCHES" | "ST_CROSSES" => (
+                BaseType::Bool.into(),
+                &[BaseType::Geometry, BaseType::Geometry],
+                &[],
+                false,
+            ),
+            "ST_ISEMPTY" | "ST_ISCLOSED" => {
+                (BaseType::Bool.into(), &[BaseType::Geometry], &[], false)
+            }
+            "ST_ASTEXT" | "ST_ASWKT" | "ST_ASGEOJSON" | "ST_GEOMETRYTYPE" => {
+                (BaseType::String.into(), &[BaseType::Geometry], &[], false)
+            }
+            "ST_ASBINARY" | "ST_ASWKB" => {
+                (BaseType::Bytes.into(), &[BaseType::Geometry], &[], false)
+            }
+            "ST_SRID" => (Type::U32, &[BaseType::Geometry], &[], false),
+            "ST_NUMPOINTS" | "ST_NUMGEOMETRIES" => {
+                (BaseType::Integer.into(), &[BaseType::Geometry], &[], false)
+            }
+            "ST_CENTROID" | "ST_ENVELOPE" | "ST_STARTPOINT" | "ST_ENDPOINT" | "ST_BOUNDARY" => {
+                (BaseType::Geometry.into(), &[BaseType::Geometry], &[], false)
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Signature {
+        return_type,
+        required,
+        optional,
+        nullable,
+    })
+}
+
+/// Name of a function with a signature, as used in issues
+fn function_name<'a>(func: &Function<'a>) -> &'a str {
+    match func {
+        Function::CharacterLength => "CHAR_LENGTH",
+        Function::CurDate => "CURDATE",
+        Function::CurTime => "CURTIME",
+        Function::CurrentTimestamp => "CURRENT_TIMESTAMP",
+        Function::Date => "DATE",
+        Function::DateDiff => "DATEDIFF",
+        Function::DateFormat => "DATE_FORMAT",
+        Function::DayName => "DAYNAME",
+        Function::DayOfMonth => "DAYOFMONTH",
+        Function::DayOfWeek => "DAYOFWEEK",
+        Function::DayOfYear => "DAYOFYEAR",
+        Function::ExtractValue => "EXTRACTVALUE",
+        Function::FindInSet => "FIND_IN_SET",
+        Function::FromDays => "FROM_DAYS",
+        Function::InStr => "INSTR",
+        Function::LCase => "LCASE",
+        Function::LPad => "LPAD",
+        Function::LTrim => "LTRIM",
+        Function::Left => "LEFT",
+        Function::Locate => "LOCATE",
+        Function::Lower => "LOWER",
+        Function::MakeDate => "MAKEDATE",
+        Function::MakeTime => "MAKETIME",
+        Function::MicroSecond => "MICROSECOND",
+        Function::Minute => "MINUTE",
+        Function::MonthName => "MONTHNAME",
+        Function::Now => "NOW",
+        Function::Pi => "PI",
+        Function::Quarter => "QUARTER",
+        Function::RPad => "RPAD",
+        Function::RTrim => "RTRIM",
+        Function::Rand => "RAND",
+        Function::Replace => "REPLACE",
+        Function::Reverse => "REVERSE",
+        Function::Right => "RIGHT",
+        Function::SecToTime => "SEC_TO_TIME",
+        Function::Second => "SECOND",
+        Function::StrToDate => "STR_TO_DATE",
+        Function::SubStringIndex => "SUBSTRING_INDEX",
+        Function::Time => "TIME",
+        Function::TimeDiff => "TIMEDIFF",
+        Function::TimeFormat => "TIME_FORMAT",
+        Function::TimeToSec => "TIME_TO_SEC",
+        Function::ToDays => "TO_DAYS",
+        Function::ToSeconds => "TO_SECONDS",
+        Function::UCase => "UCASE",
+        Function::UnHex => "UNHEX",
+        Function::Upper => "UPPER",
+        Function::UtcDate => "UTC_DATE",
+        Function::UtcTime => "UTC_TIME",
+        Function::UtcTimeStamp => "UTC_TIMESTAMP",
+        Function::Week => "WEEK",
+        Function::WeekOfYear => "WEEKOFYEAR",
+        Function::Weekday => "WEEKDAY",
+        Function::Other(name) => name,
+        _ => "function",
+    }
+}
+
+/// Type a call to a function with a signature, reporting wrong argument
+/// types on the offending argument
+fn type_signature<'a>(
+    typer: &mut Typer<'a, '_>,
+    func: &Function<'a>,
+    signature: &Signature,
+    args: &[Expression<'a>],
+    span: &Span,
+    flags: ExpressionFlags,
+) -> FullType<'a> {
+    let required = signature.required.len();
+    arg_cnt(
+        typer,
+        required..required + signature.optional.len(),
+        args,
+        span,
+    );
+    let name = function_name(func);
+    let mut not_null = !signature.nullable;
+    let mut collation = None;
+    let expected_types = signature.required.iter().chain(signature.optional);
+    for (i, (arg, expected)) in args.iter().zip(expected_types).enumerate() {
+        let t = type_expression(typer, arg, flags.without_values(), *expected);
+        not_null = not_null && t.not_null;
+        if collation.is_none() && t.base() == BaseType::String && t.collation.is_some() {
+            collation = Some(t.clone());
+        }
+        if is_temporal(*expected) && (is_temporal(t.base()) || t.base() == BaseType::String) {
+            continue;
+        }
+        if typer
+            .matched_type(&t, &FullType::new(*expected, false))
+            .is_none()
+        {
+            typer.err_type_mismatch(
+                format!(
+                    "Argument {} of {} should be of type {} got {}",
+                    i + 1,
+                    name,
+                    expected,
+                    t.t
+                ),
+                arg,
+                &t.t,
+                &[(*expected).into()],
+            );
+        }
+    }
+    for arg in args.iter().skip(required + signature.optional.len()) {
+        type_expression(typer, arg, flags.without_values(), BaseType::Any);
+    }
+    let t = FullType::new(signature.return_type.clone(), not_null);
+    // String results take the collation of the first string argument with one
+    match collation {
+        Some(c) if t.base() == BaseType::String => t.with_collation_of(&c),
+        _ => t,
+    }
+}
+
 pub(crate) fn type_function<'a, 'b>(
     typer: &mut Typer<'a, 'b>,
     func: &Function<'a>,
@@ -78,52 +521,93 @@ pub(crate) fn type_function<'a, 'b>(
     span: &Span,
     flags: ExpressionFlags,
 ) -> FullType<'a> {
-    let mut tf = |return_type: Type<'a>,
-                  required_args: &[BaseType],
-                  optional_args: &[BaseType]|
-     -> FullType<'a> {
-        let mut not_null = true;
-        let mut arg_iter = args.iter();
-        arg_cnt(
-            typer,
-            required_args.len()..required_args.len() + optional_args.len(),
-            args,
-            span,
-        );
-        for et in required_args {
-            if let Some(arg) = arg_iter.next() {
-                let t = type_expression(typer, arg, flags.without_values(), *et);
-                not_null = not_null && t.not_null;
-                typer.ensure_base(arg, &t, *et);
+    if let Some(signature) = signature(func) {
+        let mut t = type_signature(typer, func, &signature, args, span, flags);
+        // The current time is given with as many fractional second digits as asked for
+        if let (
+            Function::Now
+            | Function::CurrentTimestamp
+            | Function::CurTime
+            | Function::UtcTime
+            | Function::UtcTimeStamp,
+            Some(Expression::Integer((precision, _))),
+        ) = (func, args.first())
+        {
+            t.precision = Some((*precision).min(6) as u8);
+        }
+        return t;
+    }
+
+    match func {
+        Function::Abs | Function::Floor | Function::Ceil | Function::Round | Function::Truncate => {
+            let typed = typed_args(typer, args, flags);
+            match func {
+                Function::Round => arg_cnt(typer, 1..2, args, span),
+                Function::Truncate => arg_cnt(typer, 2..2, args, span),
+                _ => arg_cnt(typer, 1..1, args, span),
+            }
+            // The result has the type of the first argument
+            let mut not_null = true;
+            let mut t: Type<'a> = BaseType::Float.into();
+            for (i, (a, at)) in typed.iter().enumerate() {
+                not_null = not_null && at.not_null;
+                if i == 0 {
+                    ensure_numeric(typer, a, at);
+                    if at.base() != BaseType::Any {
+                        t = at.t.clone();
+                    }
+                } else {
+                    typer.ensure_base(*a, at, BaseType::Integer);
+                }
             }
+            FullType::new(t, not_null)
         }
-        for et in optional_args {
-            if let Some(arg) = arg_iter.next() {
-                let t = type_expression(typer, arg, flags.without_values(), *et);
-                not_null = not_null && t.not_null;
-                typer.ensure_base(arg, &t, *et);
+        Function::Pow
+        | Function::Exp
+        | Function::Sqrt
+        | Function::Ln
+        | Function::Log
+        | Function::Log2
+        | Function::Log10 => {
+            let typed = typed_args(typer, args, flags);
+            match func {
+                Function::Pow => arg_cnt(typer, 2..2, args, span),
+                Function::Log => arg_cnt(typer, 1..2, args, span),
+                _ => arg_cnt(typer, 1..1, args, span),
+            }
+            let mut not_null = true;
+            for (a, at) in &typed {
+                not_null = not_null && at.not_null;
+                ensure_numeric(typer, a, at);
             }
+            // Square roots and logarithms of values out of range are null
+            if !matches!(func, Function::Pow | Function::Exp) {
+                not_null = false;
+            }
+            FullType::new(Type::F64, not_null)
         }
-        for arg in arg_iter {
-            type_expression(typer, arg, flags.without_values(), BaseType::Any);
+        Function::Sign => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 1..1, args, span);
+            let mut not_null = true;
+            for (a, at) in &typed {
+                not_null = not_null && at.not_null;
+                ensure_numeric(typer, a, at);
+            }
+            FullType::new(Type::I8, not_null)
         }
-        FullType::new(return_type, not_null)
-    };
-
-    match func {
-        Function::Rand => tf(Type::F64, &[], &[BaseType::Integer]),
-        Function::Right | Function::Left => tf(
-            BaseType::String.into(),
-            &[BaseType::String, BaseType::Integer],
-            &[],
-        ),
-        Function::SubStr => {
+        Function::SubStr | Function::Mid => {
             arg_cnt(typer, 2..3, args, span);
 
             let mut return_type = if let Some(arg) = args.first() {
                 let t = type_expression(typer, arg, flags.without_values(), BaseType::Any);
                 if !matches!(t.base(), BaseType::Any | BaseType::String | BaseType::Bytes) {
-                    typer.err(format!("Expected type String or Bytes got {}", t), arg);
+                    typer.err_type_mismatch(
+                        format!("Expected type String or Bytes got {}", t),
+                        arg,
+                        &t.t,
+                        &[BaseType::String.into(), BaseType::Bytes.into()],
+                    );
                 }
                 t
             } else {
@@ -144,35 +628,13 @@ pub(crate) fn type_function<'a, 'b>(
 
             return_type
         }
-        Function::FindInSet => tf(
-            BaseType::Integer.into(),
-            &[BaseType::String, BaseType::String],
-            &[],
-        ),
-        Function::SubStringIndex => tf(
-            BaseType::String.into(),
-            &[BaseType::String, BaseType::String, BaseType::Integer],
-            &[],
-        ),
-        Function::ExtractValue => tf(
-            BaseType::String.into(),
-            &[BaseType::String, BaseType::String],
-            &[],
-        ),
-        Function::Replace => tf(
-            BaseType::String.into(),
-            &[BaseType::String, BaseType::String, BaseType::String],
-            &[],
-        ),
-        Function::CharacterLength => tf(BaseType::Integer.into(), &[BaseType::String], &[]),
         Function::UnixTimestamp => {
             let mut not_null = true;
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 0..1, args, span);
             if let Some((a, t)) = typed.first() {
                 not_null = not_null && t.not_null;
-                // TODO the argument can be both a DATE, a DATE_TIME or a TIMESTAMP
-                typer.ensure_base(*a, t, BaseType::DateTime);
+                ensure_arg_base(typer, a, t, BaseType::DateTime);
             }
             FullType::new(Type::I64, not_null)
         }
@@ -188,21 +650,60 @@ pub(crate) fn type_function<'a, 'b>(
                 FullType::invalid()
             };
             if let Some((e, t2)) = typed.get(1) {
-                typer.ensure_type(*e, t2, &t);
-                t2.clone()
+                // The result is only null if both arguments are null
+                let not_null = t.not_null || t2.not_null;
+                if let Some(tt) = typer.matched_type(&t, t2) {
+                    FullType::new(tt, not_null)
+                } else {
+                    typer.err_type_mismatch(
+                        format!("Expected type {} got {}", t.t, t2.t),
+                        *e,
+                        &t2.t,
+                        core::slice::from_ref(&t.t),
+                    );
+                    FullType::new(t.t, not_null)
+                }
             } else {
-                t.clone()
+                t
+            }
+        }
+        Function::NullIf => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 2..2, args, span);
+            match (typed.first(), typed.get(1)) {
+                (Some((_, t1)), Some((e2, t2))) => {
+                    // The result is null whenever the arguments are equal
+                    if let Some(t) = typer.matched_type(t1, t2) {
+                        FullType::new(t, false)
+                    } else {
+                        typer.err_type_mismatch(
+                            format!("Expected type {} got {}", t1.t, t2.t),
+                            *e2,
+                            &t2.t,
+                            core::slice::from_ref(&t1.t),
+                        );
+                        FullType::new(t1.t.clone(), false)
+                    }
+                }
+                (Some((_, t1)), None) => FullType::new(t1.t.clone(), false),
+                _ => FullType::invalid(),
             }
         }
         Function::Lead | Function::Lag => {
             let typed = typed_args(typer, args, flags);
-            arg_cnt(typer, 1..2, args, span);
+            arg_cnt(typer, 1..3, args, span);
             if let Some((a, t)) = typed.get(1) {
                 typer.ensure_base(*a, t, BaseType::Integer);
             }
             if let Some((_, t)) = typed.first() {
                 let mut t = t.clone();
-                t.not_null = false;
+                // Without a default the value is null outside the window frame
+                if let Some((a, dt)) = typed.get(2) {
+                    typer.ensure_type(*a, dt, &t);
+                    t.not_null = t.not_null && dt.not_null;
+                } else {
+                    t.not_null = false;
+                }
                 t
             } else {
                 FullType::invalid()
@@ -211,6 +712,9 @@ pub(crate) fn type_function<'a, 'b>(
         Function::JsonExtract => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 2..999, args, span);
+            for path in args.iter().skip(1) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
@@ -219,14 +723,23 @@ pub(crate) fn type_function<'a, 'b>(
         Function::JsonValue => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 2..2, args, span);
+            for path in args.iter().skip(1) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
             FullType::new(Type::JSON, false)
         }
-        Function::JsonReplace => {
+        Function::JsonSet | Function::JsonInsert | Function::JsonReplace => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 3..999, args, span);
+            for path in args.iter().skip(1).step_by(2) {
+                check_json_path(typer, path);
+            }
+            if args.len() % 2 == 0 {
+                typer.err("Expected a document followed by path and value pairs", span);
+            }
             for (i, (a, t)) in typed.iter().enumerate() {
                 if i == 0 || i % 2 == 1 {
                     typer.ensure_base(*a, t, BaseType::String);
@@ -234,27 +747,51 @@ pub(crate) fn type_function<'a, 'b>(
             }
             FullType::new(Type::JSON, false)
         }
-        Function::JsonSet => {
+        Function::JsonObject => {
             let typed = typed_args(typer, args, flags);
-            arg_cnt(typer, 3..999, args, span);
+            if args.len() % 2 != 0 {
+                typer.err("Expected key and value pairs", span);
+            }
             for (i, (a, t)) in typed.iter().enumerate() {
-                if i == 0 || i % 2 == 1 {
+                if i % 2 == 0 {
                     typer.ensure_base(*a, t, BaseType::String);
                 }
             }
-            FullType::new(Type::JSON, false)
+            FullType::new(Type::JSON, true)
+        }
+        Function::JsonArray => {
+            typed_args(typer, args, flags);
+            FullType::new(Type::JSON, true)
+        }
+        Function::JsonLength => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 1..2, args, span);
+            for path in args.iter().skip(1) {
+                check_json_path(typer, path);
+            }
+            for (a, t) in &typed {
+                typer.ensure_base(*a, t, BaseType::String);
+            }
+            // The result is null if the path does not exist in the document
+            let not_null = typed.len() == 1 && typed[0].1.not_null;
+            FullType::new(BaseType::Integer, not_null)
         }
         Function::JsonUnquote => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 1..1, args, span);
+            let mut not_null = true;
             for (a, t) in &typed {
+                not_null = not_null && t.not_null;
                 typer.ensure_base(*a, t, BaseType::String);
             }
-            FullType::new(BaseType::String, false)
+            FullType::new(BaseType::String, not_null)
         }
         Function::JsonQuery => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 2..2, args, span);
+            for path in args.iter().skip(1) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
@@ -263,6 +800,9 @@ pub(crate) fn type_function<'a, 'b>(
         Function::JsonRemove => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 2..999, args, span);
+            for path in args.iter().skip(1) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
@@ -271,6 +811,9 @@ pub(crate) fn type_function<'a, 'b>(
         Function::JsonContains => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 2..3, args, span);
+            for path in args.iter().skip(2) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
@@ -285,6 +828,9 @@ pub(crate) fn type_function<'a, 'b>(
         Function::JsonContainsPath => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 3..999, args, span);
+            for path in args.iter().skip(2) {
+                check_json_path(typer, path);
+            }
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::String);
             }
@@ -308,24 +854,120 @@ pub(crate) fn type_function<'a, 'b>(
             arg_cnt(typer, 1..1, args, span);
             if let Some((_, t2)) = typed.first() {
                 // TODO check that the type can be mined or maxed
-                // Result can be null if there are no rows to aggregate over
+                // Result can be null if there are no rows to aggregate over,
+                // or if all values are null
                 let mut v = t2.clone();
-                v.not_null = false;
+                v.not_null = typer.grouped && v.not_null;
+                // Sums of decimals keep the scale and may need all the precision
+                if let (Function::Sum, Type::Decimal(_, s)) = (func, &v.t) {
+                    v.t = Type::Decimal(65, *s);
+                }
                 v
             } else {
                 FullType::invalid()
             }
         }
-        Function::Now => tf(BaseType::DateTime.into(), &[], &[BaseType::Integer]),
-        Function::CurDate => tf(BaseType::Date.into(), &[], &[]),
-        Function::CurrentTimestamp => tf(BaseType::TimeStamp.into(), &[], &[BaseType::Integer]),
+        Function::AddDate | Function::SubDate | Function::AddTime | Function::SubTime => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 2..2, args, span);
+            let mut not_null = true;
+            let mut t: Type<'a> = BaseType::DateTime.into();
+            let mut precision = None;
+            if let Some((a, at)) = typed.first() {
+                not_null = not_null && at.not_null;
+                ensure_arg_base(typer, a, at, BaseType::DateTime);
+                // The result has the type of the first argument if that is a date or time
+                if is_temporal(at.base()) {
+                    t = at.t.clone();
+                    precision = at.precision;
+                }
+            }
+            if let Some((b, bt)) = typed.get(1) {
+                not_null = not_null && bt.not_null;
+                let expected = match func {
+                    Function::AddDate | Function::SubDate => BaseType::Integer,
+                    _ => BaseType::Time,
+                };
+                ensure_arg_base(typer, b, bt, expected);
+            }
+            let mut t = FullType::new(t, not_null);
+            t.precision = precision;
+            t
+        }
+        Function::TimestampDiff | Function::TimestampAdd => {
+            arg_cnt(typer, 3..3, args, span);
+            if let Some(unit) = args.first() {
+                check_time_unit(typer, unit);
+            }
+            let typed = typed_args(typer, args.get(1..).unwrap_or_default(), flags);
+            let mut not_null = true;
+            for (i, (a, t)) in typed.iter().enumerate() {
+                not_null = not_null && t.not_null;
+                let expected = match (func, i) {
+                    (Function::TimestampAdd, 0) => BaseType::Integer,
+                    _ => BaseType::DateTime,
+                };
+                ensure_arg_base(typer, a, t, expected);
+            }
+            if matches!(func, Function::TimestampDiff) {
+                FullType::new(Type::I64, not_null)
+            } else {
+                FullType::new(BaseType::DateTime, not_null)
+            }
+        }
         Function::Concat => {
             let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 1..9999, args, span);
+            // The result is null if any of the arguments is null
             let mut not_null = true;
             for (a, t) in &typed {
                 typer.ensure_base(*a, t, BaseType::Any);
                 not_null = not_null && t.not_null;
             }
+            // The result takes the collation of the first string argument with one
+            let t = FullType::new(BaseType::String, not_null);
+            match typed.iter().find(|(_, t)| t.collation.is_some()) {
+                Some((_, c)) => t.with_collation_of(c),
+                None => t,
+            }
+        }
+        Function::ConcatWs => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 2..9999, args, span);
+            // Null arguments are skipped, so only a null separator gives a null result
+            let mut not_null = true;
+            for (i, (a, t)) in typed.iter().enumerate() {
+                if i == 0 {
+                    typer.ensure_base(*a, t, BaseType::String);
+                    not_null = t.not_null;
+                } else {
+                    typer.ensure_base(*a, t, BaseType::Any);
+                }
+            }
+            FullType::new(BaseType::String, not_null)
+        }
+        Function::Hex => {
+            let typed = typed_args(typer, args, flags);
+            arg_cnt(typer, 1..1, args, span);
+            let mut not_null = true;
+            for (a, t) in &typed {
+                not_null = not_null && t.not_null;
+                if !matches!(
+                    t.base(),
+                    BaseType::Any | BaseType::Integer | BaseType::String | BaseType::Bytes
+                ) {
+                    typer.err_type_mismatch(
+                        format!("Expected type Integer, String or Bytes got {}", t.t),
+                        *a,
+                        &t.t,
+                        &[
+                            BaseType::Integer.into(),
+                            BaseType::String.into(),
+                            BaseType::Bytes.into(),
+                        ],
+                    );
+                }
+            }
             FullType::new(BaseType::String, not_null)
         }
         Function::Least | Function::Greatest => {
@@ -343,7 +985,12 @@ pub(crate) fn type_function<'a, 'b>(
                         t = tt;
                     } else {
                         typer
-                            .err("None matching input types", span)
+                            .err_type_mismatch(
+                                "None matching input types",
+                                span,
+                                &bt.t,
+                                core::slice::from_ref(&t),
+                            )
                             .frag(format!("Type {}", at.t), *a)
                             .frag(format!("Type {}", bt.t), *b);
                     }
@@ -355,9 +1002,10 @@ pub(crate) fn type_function<'a, 'b>(
         Function::If => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 3..3, args, span);
+            // A null condition selects the last argument, so only the
+            // nullability of the two branches matter
             let mut not_null = true;
             if let Some((e, t)) = typed.first() {
-                not_null = not_null && t.not_null;
                 typer.ensure_base(*e, t, BaseType::Bool);
             }
             let mut ans = FullType::invalid();
@@ -369,7 +1017,12 @@ pub(crate) fn type_function<'a, 'b>(
                         ans = FullType::new(t, not_null);
                     } else {
                         typer
-                            .err("Incompatible types", span)
+                            .err_type_mismatch(
+                                "Incompatible types",
+                                span,
+                                &t2.t,
+                                core::slice::from_ref(&t1.t),
+                            )
                             .frag(format!("Of type {}", t1.t), *e1)
                             .frag(format!("Of type {}", t2.t), *e2);
                     }
@@ -383,8 +1036,9 @@ pub(crate) fn type_function<'a, 'b>(
             let mut not_null = true;
             if let Some((e, t)) = typed.first() {
                 not_null = not_null && t.not_null;
-                // TODO float og int
-                typer.ensure_base(*e, t, BaseType::Float);
+                if t.base() != BaseType::Integer {
+                    typer.ensure_base(*e, t, BaseType::Float);
+                }
             }
             if let Some((e, t)) = typed.get(1) {
                 not_null = not_null && t.not_null;
@@ -394,11 +1048,6 @@ pub(crate) fn type_function<'a, 'b>(
                 FullType::new(BaseType::DateTime, not_null)
             }
         }
-        Function::DateFormat => tf(
-            BaseType::String.into(),
-            &[BaseType::DateTime, BaseType::String],
-            &[BaseType::String],
-        ),
         Function::Value => {
             let typed = typed_args(typer, args, flags);
             if !flags.in_on_duplicate_key_update {
@@ -411,7 +1060,34 @@ pub(crate) fn type_function<'a, 'b>(
                 FullType::invalid()
             }
         }
-        Function::Length => {
+        Function::Crc32 | Function::Crc32c => {
+            // CRC32(expr) or CRC32(par, expr) to continue a checksum
+            arg_cnt(typer, 1..2, args, span);
+            let mut not_null = true;
+            if let [par, _] = args {
+                let t = type_expression(typer, par, flags.without_values(), BaseType::Integer);
+                not_null = t.not_null;
+                typer.ensure_base(par, &t, BaseType::Integer);
+            }
+            if let Some(arg) = args.get(1).or(args.first()) {
+                not_null = not_null && type_data_arg(typer, arg, flags).not_null;
+            }
+            FullType::new(Type::U32, not_null)
+        }
+        Function::ToBase64 | Function::FromBase64 => {
+            arg_cnt(typer, 1..1, args, span);
+            let mut not_null = true;
+            for arg in args {
+                not_null = not_null && type_data_arg(typer, arg, flags).not_null;
+            }
+            if matches!(func, Function::ToBase64) {
+                FullType::new(BaseType::String, not_null)
+            } else {
+                // Invalid base64 decodes to NULL
+                FullType::new(BaseType::Bytes, false)
+            }
+        }
+        Function::Length | Function::OctetLength | Function::LengthB => {
             let typed = typed_args(typer, args, flags);
             arg_cnt(typer, 1..1, args, span);
             let mut not_null = true;
@@ -424,14 +1100,208 @@ pub(crate) fn type_function<'a, 'b>(
                         .matched_type(t, &FullType::new(BaseType::Bytes, false))
                         .is_none()
                 {
-                    typer.err(format!("Expected type Bytes or String got {}", t), span);
+                    typer.err_type_mismatch(
+                        format!("Expected type Bytes or String got {}", t),
+                        span,
+                        t,
+                        &[BaseType::Bytes.into(), BaseType::String.into()],
+                    );
                 }
             }
             FullType::new(Type::I64, not_null)
         }
+        Function::Other(name) => match name.to_ascii_uppercase().as_str() {
+            "FOUND_ROWS" => {
+                arg_cnt(typer, 0..0, args, span);
+                typer.warn("FOUND_ROWS() is deprecated, use COUNT(*) instead", span);
+                FullType::new(Type::U64, true)
+            }
+            "POINT" => {
+                let typed = typed_args(typer, args, flags);
+                arg_cnt(typer, 2..2, args, span);
+                let mut not_null = true;
+                for (a, at) in &typed {
+                    not_null = not_null && at.not_null;
+                    ensure_numeric(typer, a, at);
+                }
+                FullType::new(BaseType::Geometry, not_null)
+            }
+            "AVG" => {
+                let typed = typed_args(typer, args, flags);
+                arg_cnt(typer, 1..1, args, span);
+                let mut not_null = typer.grouped;
+                let mut t = Type::F64;
+                for (a, at) in &typed {
+                    not_null = not_null && at.not_null;
+                    ensure_numeric(typer, a, at);
+                    // Averages of decimals are extended by div_precision_increment digits
+                    if let Type::Decimal(p, s) = at.t {
+                        t = Type::Decimal((p + 4).min(65), (s + 4).min(30));
+                    }
+                }
+                FullType::new(t, not_null)
+            }
+            "MD5" | "SHA" | "SHA1" => {
+                arg_cnt(typer, 1..1, args, span);
+                let mut not_null = true;
+                for arg in args {
+                    not_null = not_null && type_data_arg(typer, arg, flags).not_null;
+                }
+                FullType::new(BaseType::String, not_null)
+            }
+            "SHA2" => {
+                arg_cnt(typer, 2..2, args, span);
+                if let Some(arg) = args.first() {
+                    type_data_arg(typer, arg, flags);
+                }
+                if let Some(arg) = args.get(1) {
+                    let t = type_expression(typer, arg, flags.without_values(), BaseType::Integer);
+                    typer.ensure_base(arg, &t, BaseType::Integer);
+                }
+                // Unsupported hash lengths give NULL
+                FullType::new(BaseType::String, false)
+            }
+            "AES_ENCRYPT" | "AES_DECRYPT" => {
+                // The optional arguments are the initialization vector and
+                // in MySQL the key derivation parameters
+                arg_cnt(typer, 2..6, args, span);
+                let mut not_null = true;
+                for (i, arg) in args.iter().enumerate() {
+                    let t = if i < 2 {
+                        type_data_arg(typer, arg, flags)
+                    } else {
+                        type_expression(typer, arg, flags.without_values(), BaseType::Any)
+                    };
+                    not_null = not_null && t.not_null;
+                }
+                // Decryption with the wrong key gives NULL
+                FullType::new(
+                    BaseType::Bytes,
+                    not_null && name.eq_ignore_ascii_case("AES_ENCRYPT"),
+                )
+            }
+            "COALESCE" => {
+                let typed = typed_args(typer, args, flags);
+                arg_cnt(typer, 1..9999, args, span);
+                if let Some((a, at)) = typed.first() {
+                    // The result is not null as soon as one of the arguments is not null
+                    let mut not_null = at.not_null;
+                    let mut t = at.t.clone();
+                    for (b, bt) in &typed[1..] {
+                        not_null = not_null || bt.not_null;
+                        if bt.t == t {
+                            continue;
+                        }
+                        if let Some(tt) = typer.matched_type(&bt.t, &t) {
+                            t = tt;
+                        } else {
+                            typer
+                                .err_type_mismatch(
+                                    "None matching input types",
+                                    span,
+                                    &bt.t,
+                                    core::slice::from_ref(&t),
+                                )
+                                .frag(format!("Type {}", at.t), *a)
+                                .frag(format!("Type {}", bt.t), *b);
+                        }
+                    }
+                    FullType::new(t, not_null)
+                } else {
+                    FullType::invalid()
+                }
+            }
+            "FIRST_VALUE" | "LAST_VALUE" => {
+                let typed = typed_args(typer, args, flags);
+                arg_cnt(typer, 1..1, args, span);
+                if let Some((_, t)) = typed.first() {
+                    t.clone()
+                } else {
+                    FullType::invalid()
+                }
+            }
+            _ => {
+                if let Some(function) = typer.schemas.get_function(name) {
+                    return type_schema_function(typer, function, args, span, flags);
+                }
+                typer.err("Typing for function not implemented", span);
+                FullType::invalid()
+            }
+        },
         _ => {
             typer.err("Typing for function not implemented", span);
             FullType::invalid()
         }
     }
 }
+
+/// Type a call to a function registered in the schemas
+fn type_schema_function<'a>(
+    typer: &mut Typer<'a, '_>,
+    function: &Functions<'a>,
+    args: &[Expression<'a>],
+    span: &Span,
+    flags: ExpressionFlags,
+) -> FullType<'a> {
+    let cnt = function.arguments.len();
+    if function.variadic {
+        arg_cnt(typer, cnt.saturating_sub(1)..9999, args, span);
+    } else {
+        arg_cnt(typer, cnt..cnt, args, span);
+    }
+    for (i, arg) in args.iter().enumerate() {
+        let expected = match function.arguments.get(i) {
+            Some(t) => Some(t),
+            None if function.variadic => function.arguments.last(),
+            None => None,
+        };
+        match expected {
+            Some(expected) => {
+                let t = type_expression(typer, arg, flags.without_values(), expected.base());
+                ensure_arg_base(typer, arg, &t, expected.base());
+            }
+            None => {
+                type_expression(typer, arg, flags.without_values(), BaseType::Any);
+            }
+        }
+    }
+    function.return_type.clone()
+}
+
+/// Is the function an aggregate function
+pub(crate) fn is_aggregate_function(func: &Function<'_>) -> bool {
+    match func {
+        Function::Min
+        | Function::Max
+        | Function::Sum
+        | Function::JsonArrayAgg
+        | Function::JsonObjectAgg => true,
+        Function::Other(name) => matches!(
+            name.to_ascii_uppercase().as_str(),
+            "AVG"
+                | "BIT_AND"
+                | "BIT_OR"
+                | "BIT_XOR"
+                | "STD"
+                | "STDDEV"
+                | "STDDEV_POP"
+                | "STDDEV_SAMP"
+                | "VARIANCE"
+                | "VAR_POP"
+                | "VAR_SAMP"
+        ),
+        _ => false,
+    }
+}
+
+/// Is the function only valid together with an OVER clause
+pub(crate) fn is_window_function(func: &Function<'_>) -> bool {
+    match func {
+        Function::Lead | Function::Lag => true,
+        Function::Other(name) => matches!(
+            name.to_ascii_uppercase().as_str(),
+            "ROW_NUMBER" | "RANK" | "DENSE_RANK" | "NTILE" | "FIRST_VALUE" | "LAST_VALUE"
+        ),
+        _ => false,
+    }
+}
@@ -12,13 +12,14 @@
 
 use alloc::{format, vec::Vec};
 use sql_parse::{
-    issue_todo, InsertReplace, InsertReplaceFlag, InsertReplaceSetPair, InsertReplaceType, Spanned,
+    issue_todo, Identifier, InsertReplace, InsertReplaceFlag, InsertReplaceSetPair, OptSpanned,
+    Spanned,
 };
 
 use crate::{
     type_expression::{type_expression, ExpressionFlags},
     type_select::{type_select, type_select_exprs, SelectType},
-    typer::{typer_stack, unqualified_name, ReferenceType, Typer},
+    typer::{typer_stack, ReferenceType, Typer},
     BaseType, SelectTypeColumn, Type,
 };
 
@@ -30,47 +31,135 @@ pub enum AutoIncrementId {
     Optional,
 }
 
+/// Report the NOT NULL columns of the table without a default value that are
+/// not among the given columns. With IGNORE the server inserts an implicit
+/// default, so only a warning is given
+fn check_missing_columns<'a>(
+    typer: &mut Typer<'a, '_>,
+    table: &Identifier<'a>,
+    given: &[&Identifier<'a>],
+    ignore: bool,
+) {
+    let Some(schema) = typer.get_schema(table.value) else {
+        return;
+    };
+    for c in &schema.columns {
+        if c.declared_not_null
+            && !c.default
+            && !c.auto_increment
+            && c.as_.is_none()
+            && !given
+                .iter()
+                .any(|g| typer.options.same_name(g, &c.identifier))
+        {
+            let message = format!("Missing value for column {}", c.identifier);
+            if ignore {
+                typer.warn(message, table);
+            } else {
+                typer.err(message, table);
+            }
+        }
+    }
+}
+
+/// Report the given columns that are generated from other columns
+fn check_generated_columns<'a>(
+    typer: &mut Typer<'a, '_>,
+    table: &Identifier<'a>,
+    given: &[&Identifier<'a>],
+) {
+    let Some(schema) = typer.get_schema(table.value) else {
+        return;
+    };
+    for col in given {
+        if schema
+            .find_column(col.value, typer.options)
+            .is_some_and(|c| c.as_.is_some())
+        {
+            typer.err("Generated columns can not be inserted into", *col);
+        }
+    }
+}
+
 pub(crate) fn type_insert_replace<'a>(
     typer: &mut Typer<'a, '_>,
     ior: &InsertReplace<'a>,
-) -> (AutoIncrementId, Option<SelectType<'a>>) {
-    let table = unqualified_name(typer.issues, &ior.table);
+) -> (AutoIncrementId, Option<Type<'a>>, Option<SelectType<'a>>) {
+    let table = typer.local_name(&ior.table);
     let columns = &ior.columns;
+    let ignore = ior
+        .flags
+        .iter()
+        .any(|f| matches!(f, InsertReplaceFlag::Ignore(_)));
 
-    let (s, auto_increment) = if let Some(schema) = typer.schemas.schemas.get(table.value) {
+    let (s, auto_increment) = if let Some(schema) = typer.get_schema(table.value) {
         if schema.view {
             typer.err("Inserts into views not yet implemented", table);
         }
         let mut col_types = Vec::new();
 
         for col in columns {
-            if let Some(schema_col) = schema.get_column(col.value) {
+            if let Some(schema_col) = schema.find_column(col.value, typer.options) {
                 col_types.push((schema_col.type_.clone(), col.span()));
             } else {
                 typer.err("No such column in schema", col);
             }
         }
+        // Without a column list every column of the table is inserted
+        if columns.is_empty() {
+            for c in &schema.columns {
+                col_types.push((c.type_.clone(), table.span()));
+            }
+        }
         (
             Some(col_types),
-            schema.columns.iter().any(|c| c.auto_increment),
+            schema
+                .columns
+                .iter()
+                .find(|c| c.auto_increment)
+                .map(|c| c.type_.t.clone()),
         )
     } else {
         typer.err("Unknown table", table);
-        (None, false)
+        (None, None)
     };
 
+    if !columns.is_empty() {
+        let given: Vec<_> = columns.iter().collect();
+        check_missing_columns(typer, table, &given, ignore);
+        check_generated_columns(typer, table, &given);
+    }
+
     if let Some(values) = &ior.values {
         for row in &values.1 {
+            if let (Some(s), Some(span)) = (&s, row.opt_span()) {
+                if row.len() != s.len() {
+                    typer.err(
+                        format!("Got {} values expected {}", row.len(), s.len()),
+                        &span,
+                    );
+                }
+            }
             for (j, e) in row.iter().enumerate() {
+                if let Some(col) = columns.get(j) {
+                    typer.suggest_arg_name(e, col.value);
+                }
                 if let Some((et, ets)) = s.as_ref().and_then(|v| v.get(j)) {
                     let t = type_expression(typer, e, ExpressionFlags::default(), et.base());
+                    typer.check_string_literal(e, et);
+                    typer.check_precision_loss(e, &t, et);
                     if typer.matched_type(&t, et).is_none() {
                         typer
-                            .err(format!("Got type {}", t.t), e)
+                            .err_type_mismatch(
+                                format!("Got type {}", t.t),
+                                e,
+                                &t.t,
+                                core::slice::from_ref(&et.t),
+                            )
                             .frag(format!("Expected {}", et.t), ets);
                     } else if let Type::Args(_, args) = &t.t {
-                        for (idx, arg_type, _) in args.iter() {
-                            typer.constrain_arg(*idx, arg_type, et);
+                        for (idx, arg_type, span) in args.iter() {
+                            typer.constrain_arg(*idx, arg_type, span, et);
                         }
                     }
                 } else {
@@ -86,9 +175,15 @@ pub(crate) fn type_insert_replace<'a>(
             for i in 0..usize::max(s.len(), select.columns.len()) {
                 match (s.get(i), select.columns.get(i)) {
                     (Some((et, ets)), Some(t)) => {
+                        typer.check_precision_loss(&t.span, &t.type_, et);
                         if typer.matched_type(&t.type_, et).is_none() {
                             typer
-                                .err(format!("Got type {}", t.type_.t), &t.span)
+                                .err_type_mismatch(
+                                    format!("Got type {}", t.type_.t),
+                                    &t.span,
+                                    &t.type_.t,
+                                    core::slice::from_ref(&et.t),
+                                )
                                 .frag(format!("Expected {}", et.t), ets);
                         }
                     }
@@ -113,7 +208,7 @@ pub(crate) fn type_insert_replace<'a>(
     );
     let typer = &mut guard.typer;
 
-    if let Some(s) = typer.schemas.schemas.get(table.value) {
+    if let Some(s) = typer.get_schema(table.value) {
         let mut columns = Vec::new();
         for c in &s.columns {
             columns.push((c.identifier.clone(), c.type_.clone()));
@@ -128,18 +223,24 @@ pub(crate) fn type_insert_replace<'a>(
         }
         typer.reference_types.push(ReferenceType {
             name: Some(table.clone()),
+            database: None,
             span: table.span(),
             columns,
+            using: Vec::new(),
         });
     }
 
     if let Some(set) = &ior.set {
+        let given: Vec<_> = set.pairs.iter().map(|p| &p.column).collect();
+        check_missing_columns(typer, table, &given, ignore);
+        check_generated_columns(typer, table, &given);
         for InsertReplaceSetPair { column, value, .. } in &set.pairs {
+            typer.suggest_arg_name(value, column.value);
             let mut cnt = 0;
             let mut t = None;
             for r in &typer.reference_types {
                 for c in &r.columns {
-                    if c.0 == *column {
+                    if typer.options.same_name(&c.0, column) {
                         cnt += 1;
                         t = Some(c.clone());
                     }
@@ -150,7 +251,7 @@ pub(crate) fn type_insert_replace<'a>(
                 let mut issue = typer.issues.err("Ambiguous reference", column);
                 for r in &typer.reference_types {
                     for c in &r.columns {
-                        if c.0 == *column {
+                        if typer.options.same_name(&c.0, column) {
                             issue.frag("Defined here", &r.span);
                         }
                     }
@@ -158,11 +259,18 @@ pub(crate) fn type_insert_replace<'a>(
             } else if let Some(t) = t {
                 let value_type =
                     type_expression(typer, value, ExpressionFlags::default(), t.1.base());
+                typer.check_string_literal(value, &t.1);
+                typer.check_precision_loss(value, &value_type, &t.1);
                 if typer.matched_type(&value_type, &t.1).is_none() {
-                    typer.err(format!("Got type {} expected {}", value_type, t.1), value);
+                    typer.err_type_mismatch(
+                        format!("Got type {} expected {}", value_type, t.1),
+                        value,
+                        &value_type.t,
+                        core::slice::from_ref(&t.1.t),
+                    );
                 } else if let Type::Args(_, args) = &value_type.t {
-                    for (idx, arg_type, _) in args.iter() {
-                        typer.constrain_arg(*idx, arg_type, &t.1);
+                    for (idx, arg_type, span) in args.iter() {
+                        typer.constrain_arg(*idx, arg_type, span, &t.1);
                     }
                 }
             } else {
@@ -174,11 +282,12 @@ pub(crate) fn type_insert_replace<'a>(
 
     if let Some(up) = &ior.on_duplicate_key_update {
         for InsertReplaceSetPair { value, column, .. } in &up.pairs {
+            typer.suggest_arg_name(value, column.value);
             let mut cnt = 0;
             let mut t = None;
             for r in &typer.reference_types {
                 for c in &r.columns {
-                    if c.0 == *column {
+                    if typer.options.same_name(&c.0, column) {
                         cnt += 1;
                         t = Some(c.clone());
                     }
@@ -190,18 +299,25 @@ pub(crate) fn type_insert_replace<'a>(
                 let mut issue = typer.issues.err("Ambiguous reference", column);
                 for r in &typer.reference_types {
                     for c in &r.columns {
-                        if c.0 == *column {
+                        if typer.options.same_name(&c.0, column) {
                             issue.frag("Defined here", &r.span);
                         }
                     }
                 }
             } else if let Some(t) = t {
                 let value_type = type_expression(typer, value, flags, t.1.base());
+                typer.check_string_literal(value, &t.1);
+                typer.check_precision_loss(value, &value_type, &t.1);
                 if typer.matched_type(&value_type, &t.1).is_none() {
-                    typer.err(format!("Got type {} expected {}", value_type, t.1), value);
+                    typer.err_type_mismatch(
+                        format!("Got type {} expected {}", value_type, t.1),
+                        value,
+                        &value_type.t,
+                        core::slice::from_ref(&t.1.t),
+                    );
                 } else if let Type::Args(_, args) = &value_type.t {
-                    for (idx, arg_type, _) in args.iter() {
-                        typer.constrain_arg(*idx, arg_type, &t.1);
+                    for (idx, arg_type, span) in args.iter() {
+                        typer.constrain_arg(*idx, arg_type, span, &t.1);
                     }
                 }
             } else {
@@ -217,7 +333,7 @@ pub(crate) fn type_insert_replace<'a>(
                 let mut t = None;
                 for r in &typer.reference_types {
                     for c in &r.columns {
-                        if c.0 == *name {
+                        if typer.options.same_name(&c.0, name) {
                             t = Some(c.clone());
                         }
                     }
@@ -239,11 +355,12 @@ pub(crate) fn type_insert_replace<'a>(
             sql_parse::OnConflictAction::DoNothing(_) => (),
             sql_parse::OnConflictAction::DoUpdateSet { sets, where_, .. } => {
                 for (key, value) in sets {
+                    typer.suggest_arg_name(value, key.value);
                     let mut cnt = 0;
                     let mut t = None;
                     for r in &typer.reference_types {
                         for c in &r.columns {
-                            if c.0 == *key {
+                            if typer.options.same_name(&c.0, key) {
                                 cnt += 1;
                                 t = Some(c.clone());
                             }
@@ -255,18 +372,25 @@ pub(crate) fn type_insert_replace<'a>(
                         let mut issue = typer.issues.err("Ambiguous reference", key);
                         for r in &typer.reference_types {
                             for c in &r.columns {
-                                if c.0 == *key {
+                                if typer.options.same_name(&c.0, key) {
                                     issue.frag("Defined here", &r.span);
                                 }
                             }
                         }
                     } else if let Some(t) = t {
                         let value_type = type_expression(typer, value, flags, t.1.base());
+                        typer.check_string_literal(value, &t.1);
+                        typer.check_precision_loss(value, &value_type, &t.1);
                         if typer.matched_type(&value_type, &t.1).is_none() {
-                            typer.err(format!("Got type {} expected {}", value_type, t.1), value);
+                            typer.err_type_mismatch(
+                                format!("Got type {} expected {}", value_type, t.1),
+                                value,
+                                &value_type.t,
+                                core::slice::from_ref(&t.1.t),
+                            );
                         } else if let Type::Args(_, args) = &value_type.t {
-                            for (idx, arg_type, _) in args.iter() {
-                                typer.constrain_arg(*idx, arg_type, &t.1);
+                            for (idx, arg_type, span) in args.iter() {
+                                typer.constrain_arg(*idx, arg_type, span, &t.1);
                             }
                         }
                     } else {
@@ -297,13 +421,8 @@ pub(crate) fn type_insert_replace<'a>(
 
     core::mem::drop(guard);
 
-    let auto_increment_id = if auto_increment && matches!(ior.type_, InsertReplaceType::Insert(_)) {
-        if ior
-            .flags
-            .iter()
-            .any(|f| matches!(f, InsertReplaceFlag::Ignore(_)))
-            || ior.on_duplicate_key_update.is_some()
-        {
+    let auto_increment_id = if auto_increment.is_some() {
+        if ignore || ior.on_duplicate_key_update.is_some() {
             AutoIncrementId::Optional
         } else {
             AutoIncrementId::Yes
@@ -312,5 +431,5 @@ pub(crate) fn type_insert_replace<'a>(
         AutoIncrementId::No
     };
 
-    (auto_increment_id, returning_select)
+    (auto_increment_id, auto_increment, returning_select)
 }
@@ -11,14 +11,141 @@
 // limitations under the License.
 
 use crate::{
-    schema::IndexKey,
-    type_::BaseType,
+    schema::{find_table, IndexKey},
+    type_::{BaseType, FullType},
     type_expression::{type_expression, ExpressionFlags},
     type_select::type_union_select,
     typer::{unqualified_name, ReferenceType, Typer},
+    Type, TypeOptions,
 };
-use alloc::vec::Vec;
-use sql_parse::{issue_todo, OptSpanned, Spanned, TableReference};
+use alloc::{format, vec::Vec};
+use sql_parse::{
+    issue_todo, Identifier, JoinType, OptSpanned, QualifiedName, Span, Spanned, TableReference,
+};
+
+/// Find the types of the columns named col not merged by an earlier USING
+fn using_column<'a>(
+    refs: &[ReferenceType<'a>],
+    col: &str,
+    options: &TypeOptions,
+) -> Vec<FullType<'a>> {
+    refs.iter()
+        .filter(|r| !r.is_using(col, options))
+        .flat_map(|r| r.columns.iter())
+        .filter(|c| options.same_name(c.0.value, col))
+        .map(|c| c.1.clone())
+        .collect()
+}
+
+/// Merge the columns of the two sides of a join named in USING, or shared by
+/// a natural join, into single columns that are the only ones found when the
+/// name is not qualified. The references of the left side of the join are
+/// `start..left_refs` and those of the right side follow. Issues are reported
+/// at the span given with a column
+fn merge_join_columns<'a>(
+    typer: &mut Typer<'a, '_>,
+    join: &JoinType,
+    start: usize,
+    left_refs: usize,
+    columns: &[(Identifier<'a>, Span)],
+) {
+    let options = typer.options;
+    let mut merged = Vec::new();
+    for (col, span) in columns {
+        let (left, right) = typer.reference_types[start..].split_at(left_refs - start);
+        let (l, r) = match (
+            using_column(left, col, options).as_slice(),
+            using_column(right, col, options).as_slice(),
+        ) {
+            ([l], [r]) => (l.clone(), r.clone()),
+            ([], _) | (_, []) => {
+                typer.err("Column in USING not found on both sides of the join", span);
+                continue;
+            }
+            _ => {
+                typer.err("Ambiguous reference", span);
+                continue;
+            }
+        };
+        let t = if l.t == r.t {
+            l.t.clone()
+        } else if let Some(t) = typer.matched_type(&l, &r) {
+            t
+        } else {
+            typer.err_type_mismatch(
+                format!("Type error in joined column {}", col),
+                span,
+                &r.t,
+                core::slice::from_ref(&l.t),
+            );
+            Type::Invalid
+        };
+        let not_null = match join {
+            JoinType::Left(_) | JoinType::NaturalLeft(_) => l.not_null,
+            JoinType::Right(_) | JoinType::NaturalRight(_) => r.not_null,
+            _ => l.not_null || r.not_null,
+        };
+        // The columns merged are no longer found on the two sides
+        for r in &mut typer.reference_types[start..] {
+            if !r.is_using(col, options) && r.columns.iter().any(|c| options.same_name(&c.0, col)) {
+                r.using.push(col.clone());
+            }
+        }
+        merged.push((col.clone(), FullType::new(t, not_null)));
+    }
+    if merged.is_empty() {
+        return;
+    }
+    // Merged columns come before the other columns of the join
+    typer.reference_types.insert(
+        start,
+        ReferenceType {
+            name: None,
+            database: None,
+            span: columns.first().map_or(0..0, |(_, s)| s.clone()),
+            columns: merged,
+            using: Vec::new(),
+        },
+    );
+}
+
+/// The names of the columns found unqualified on both sides of a join
+fn natural_columns<'a>(
+    typer: &Typer<'a, '_>,
+    start: usize,
+    left_refs: usize,
+) -> Vec<Identifier<'a>> {
+    let options = typer.options;
+    let (left, right) = typer.reference_types[start..].split_at(left_refs - start);
+    let mut ans: Vec<Identifier<'a>> = Vec::new();
+    for r in left {
+        for c in r.columns.iter().filter(|c| !r.is_using(&c.0, options)) {
+            if !ans.iter().any(|a| options.same_name(a, &c.0))
+                && !using_column(right, &c.0, options).is_empty()
+            {
+                ans.push(c.0.clone());
+            }
+        }
+    }
+    ans
+}
+
+/// Collect the names the tables in the reference are known by
+pub(crate) fn base_tables<'a, 'e>(
+    reference: &'e TableReference<'a>,
+    out: &mut Vec<(&'e Identifier<'a>, &'e QualifiedName<'a>)>,
+) {
+    match reference {
+        TableReference::Table {
+            identifier, as_, ..
+        } => out.push((as_.as_ref().unwrap_or(&identifier.identifier), identifier)),
+        TableReference::Query { .. } => (),
+        TableReference::Join { left, right, .. } => {
+            base_tables(left, out);
+            base_tables(right, out);
+        }
+    }
+}
 
 pub(crate) fn type_reference<'a>(
     typer: &mut Typer<'a, '_>,
@@ -33,8 +160,31 @@ pub(crate) fn type_reference<'a>(
             index_hints,
             ..
         } => {
-            let identifier = unqualified_name(typer.issues, identifier);
-            if let Some(s) = typer.get_schema(identifier.value) {
+            let (database, identifier) = match identifier.prefix.as_slice() {
+                [(database, _)] => (Some(database), &identifier.identifier),
+                _ => (None, unqualified_name(typer.issues, identifier)),
+            };
+            let qualifier = database;
+            // Tables of the default database are found in the schema
+            let database = database.filter(|d| !typer.options.is_default_database(d.value));
+            let schema = if let Some(database) = database {
+                let schemas = typer.schemas;
+                let schema = schemas
+                    .databases
+                    .get(database.value)
+                    .and_then(|tables| find_table(tables, identifier.value, typer.options))
+                    .map(|(_, schema)| schema);
+                if schema.is_some() {
+                    typer
+                        .info
+                        .database_tables
+                        .push((database.value, identifier.value));
+                }
+                schema
+            } else {
+                typer.get_schema(identifier.value)
+            };
+            if let Some(s) = schema {
                 let mut columns = Vec::new();
                 for c in &s.columns {
                     let mut type_ = c.type_.clone();
@@ -42,15 +192,21 @@ pub(crate) fn type_reference<'a>(
                     columns.push((c.identifier.clone(), type_));
                 }
                 let name = as_.as_ref().unwrap_or(identifier).clone();
+                let reference_database = if as_.is_none() {
+                    qualifier.cloned()
+                } else {
+                    None
+                };
                 for v in &typer.reference_types {
-                    if v.name == Some(name.clone()) {
+                    if v.name == Some(name.clone()) && v.database == reference_database {
                         typer
                             .issues
                             .err("Duplicate definitions", &name)
                             .frag("Already defined here", &v.span);
                     }
                 }
-                for index_hint in index_hints {
+                // Indices are only known for tables in the default database
+                for index_hint in index_hints.iter().filter(|_| database.is_none()) {
                     if matches!(index_hint.type_, sql_parse::IndexHintType::Index(_)) {
                         for index in &index_hint.index_list {
                             if !typer.schemas.indices.contains_key(&IndexKey {
@@ -65,8 +221,10 @@ pub(crate) fn type_reference<'a>(
 
                 typer.reference_types.push(ReferenceType {
                     name: Some(name.clone()),
+                    database: reference_database,
                     span: name.span(),
                     columns,
+                    using: Vec::new(),
                 });
             } else {
                 typer.issues.err("Unknown table or view", identifier);
@@ -78,17 +236,24 @@ pub(crate) fn type_reference<'a>(
             let span = if let Some(as_) = as_ {
                 as_.span.clone()
             } else {
+                if typer.dialect().is_maria() {
+                    typer
+                        .issues
+                        .err("Every derived table must have its own alias", query);
+                }
                 select.columns.opt_span().unwrap_or_else(|| query.span())
             };
 
             typer.reference_types.push(ReferenceType {
                 name: as_.clone(),
+                database: None,
                 span,
                 columns: select
                     .columns
                     .iter()
                     .filter_map(|v| v.name.as_ref().map(|name| (name.clone(), v.type_.clone())))
                     .collect(),
+                using: Vec::new(),
             });
         }
         sql_parse::TableReference::Join {
@@ -98,28 +263,56 @@ pub(crate) fn type_reference<'a>(
             specification,
         } => {
             let (left_force_null, right_force_null) = match join {
-                sql_parse::JoinType::Left(_) => (force_null, true),
-                sql_parse::JoinType::Right(_) => (true, force_null),
+                sql_parse::JoinType::Left(_) | sql_parse::JoinType::NaturalLeft(_) => {
+                    (force_null, true)
+                }
+                sql_parse::JoinType::Right(_) | sql_parse::JoinType::NaturalRight(_) => {
+                    (true, force_null)
+                }
                 sql_parse::JoinType::Inner(_)
                 | sql_parse::JoinType::Cross(_)
-                | sql_parse::JoinType::Normal(_) => (force_null, force_null),
+                | sql_parse::JoinType::Normal(_)
+                | sql_parse::JoinType::Natural(_)
+                | sql_parse::JoinType::NaturalInner(_) => (force_null, force_null),
                 _ => {
                     issue_todo!(typer.issues, join);
                     (force_null, force_null)
                 }
             };
+            let start = typer.reference_types.len();
             type_reference(typer, left, left_force_null);
+            let left_refs = typer.reference_types.len();
             type_reference(typer, right, right_force_null);
             match &specification {
                 Some(sql_parse::JoinSpecification::On(e, _)) => {
                     let t = type_expression(typer, e, ExpressionFlags::default(), BaseType::Bool);
                     typer.ensure_base(e, &t, BaseType::Bool);
                 }
-                Some(s @ sql_parse::JoinSpecification::Using(_, _)) => {
-                    issue_todo!(typer.issues, s);
+                Some(sql_parse::JoinSpecification::Using(columns, _)) => {
+                    let columns: Vec<_> = columns.iter().map(|c| (c.clone(), c.span())).collect();
+                    merge_join_columns(typer, join, start, left_refs, &columns);
                 }
                 None => (),
             }
+            if matches!(
+                join,
+                sql_parse::JoinType::Natural(_)
+                    | sql_parse::JoinType::NaturalInner(_)
+                    | sql_parse::JoinType::NaturalLeft(_)
+                    | sql_parse::JoinType::NaturalRight(_)
+            ) {
+                if typer.options.warn_natural_join {
+                    typer.warn(
+                        "NATURAL JOIN silently changes when columns are added to either side",
+                        join,
+                    );
+                }
+                let columns: Vec<_> = natural_columns(typer, start, left_refs)
+                    .into_iter()
+                    .map(|c| (c, join.span()))
+                    .collect();
+                merge_join_columns(typer, join, start, left_refs, &columns);
+            }
         }
     }
 
@@ -17,6 +17,7 @@ use sql_parse::{
 };
 
 use crate::{
+    aggregate::check_aggregates,
     type_::{BaseType, FullType},
     type_expression::{type_expression, ExpressionFlags},
     type_reference::type_reference,
@@ -55,19 +56,32 @@ impl<'a> Spanned for SelectType<'a> {
     }
 }
 
+/// The database given in a three part identifier if any
+fn database_part<'c, 'a>(parts: &'c [IdentifierPart<'a>]) -> Option<&'c Identifier<'a>> {
+    match parts {
+        [IdentifierPart::Name(database), _, _] => Some(database),
+        _ => None,
+    }
+}
+
 pub(crate) fn resolve_kleene_identifier<'a, 'b>(
     typer: &mut Typer<'a, 'b>,
     parts: &[IdentifierPart<'a>],
     as_: &Option<Identifier<'a>>,
     mut cb: impl FnMut(&mut Issues<'a>, Option<Identifier<'a>>, FullType<'a>, Span, bool),
 ) {
+    let options = typer.options;
     match parts {
         [sql_parse::IdentifierPart::Name(col)] => {
             let mut cnt = 0;
             let mut t = None;
-            for r in &typer.reference_types {
+            for r in typer
+                .reference_types
+                .iter()
+                .filter(|r| !r.is_using(col, options))
+            {
                 for c in &r.columns {
-                    if c.0 == *col {
+                    if options.same_name(&c.0, col) {
                         cnt += 1;
                         t = Some(c);
                     }
@@ -76,9 +90,13 @@ pub(crate) fn resolve_kleene_identifier<'a, 'b>(
             let name = as_.as_ref().unwrap_or(col);
             if cnt > 1 {
                 let mut issue = typer.issues.err("Ambigious reference", col);
-                for r in &typer.reference_types {
+                for r in typer
+                    .reference_types
+                    .iter()
+                    .filter(|r| !r.is_using(col, options))
+                {
                     for c in &r.columns {
-                        if c.0 == *col {
+                        if options.same_name(&c.0, col) {
                             issue.frag("Defined here", &r.span);
                         }
                     }
@@ -114,7 +132,7 @@ pub(crate) fn resolve_kleene_identifier<'a, 'b>(
                 typer.err("As not supported for *", as_);
             }
             for r in &typer.reference_types {
-                for c in &r.columns {
+                for c in r.columns.iter().filter(|c| !r.is_using(&c.0, options)) {
                     cb(
                         typer.issues,
                         Some(c.0.clone()),
@@ -125,19 +143,45 @@ pub(crate) fn resolve_kleene_identifier<'a, 'b>(
                 }
             }
         }
-        [sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Name(col)] => {
+        [sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Name(col)]
+        | [sql_parse::IdentifierPart::Name(_), sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Name(col)] =>
+        {
+            let database = database_part(parts);
+            let mut cnt = 0;
             let mut t = None;
             for r in &typer.reference_types {
-                if r.name == Some(tbl.clone()) {
+                if r.name.as_ref().is_some_and(|n| options.same_name(n, tbl))
+                    && database.map_or(true, |d| {
+                        r.database
+                            .as_ref()
+                            .is_some_and(|rd| options.same_name(rd, d))
+                    })
+                {
+                    cnt += 1;
                     for c in &r.columns {
-                        if c.0 == *col {
+                        if options.same_name(&c.0, col) {
                             t = Some(c);
                         }
                     }
                 }
             }
             let name = as_.as_ref().unwrap_or(col);
-            if let Some(t) = t {
+            if cnt > 1 {
+                // Tables of the same name from different databases
+                let mut issue = typer.issues.err("Ambiguous reference", tbl);
+                for r in &typer.reference_types {
+                    if r.name.as_ref().is_some_and(|n| options.same_name(n, tbl)) {
+                        issue.frag("Defined here", &r.span);
+                    }
+                }
+                cb(
+                    typer.issues,
+                    Some(name.clone()),
+                    FullType::invalid(),
+                    name.span(),
+                    as_.is_some(),
+                );
+            } else if let Some(t) = t {
                 cb(
                     typer.issues,
                     Some(name.clone()),
@@ -156,13 +200,22 @@ pub(crate) fn resolve_kleene_identifier<'a, 'b>(
                 );
             }
         }
-        [sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Star(v)] => {
+        [sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Star(v)]
+        | [sql_parse::IdentifierPart::Name(_), sql_parse::IdentifierPart::Name(tbl), sql_parse::IdentifierPart::Star(v)] =>
+        {
             if let Some(as_) = as_ {
                 typer.err("As not supported for *", as_);
             }
+            let database = database_part(parts);
             let mut t = None;
             for r in &typer.reference_types {
-                if r.name == Some(tbl.clone()) {
+                if r.name.as_ref().is_some_and(|n| options.same_name(n, tbl))
+                    && database.map_or(true, |d| {
+                        r.database
+                            .as_ref()
+                            .is_some_and(|rd| options.same_name(rd, d))
+                    })
+                {
                     t = Some(r);
                 }
             }
@@ -189,6 +242,46 @@ pub(crate) fn resolve_kleene_identifier<'a, 'b>(
     }
 }
 
+/// Type a LIMIT count or offset, which must be an unsigned integer
+fn type_limit<'a>(typer: &mut Typer<'a, '_>, e: &Expression<'a>, name: &'static str) {
+    typer.suggest_arg_name(e, name);
+    let t = type_expression(typer, e, ExpressionFlags::default(), BaseType::Integer);
+    let expected = FullType::new(Type::U64, true);
+    if typer.matched_type(&t, &expected).is_none() {
+        typer.err_type_mismatch(
+            format!("Expected integer type got {}", t.t),
+            e,
+            &t.t,
+            &[BaseType::Integer.into()],
+        );
+    } else if let Type::Args(_, args) = &t.t {
+        for (idx, arg_type, span) in args.iter() {
+            typer.constrain_arg(*idx, arg_type, span, &expected);
+        }
+    }
+}
+
+/// Type the items of an ORDER BY clause, where integer literals refer to the
+/// columns of the select list by position
+fn type_order_by<'a, F>(
+    typer: &mut Typer<'a, '_>,
+    order_by: &[(Expression<'a>, F)],
+    columns: usize,
+    select_span: &Span,
+) {
+    for (e, _) in order_by {
+        if let Expression::Integer((position, span)) = e {
+            if *position == 0 || *position > columns as u64 {
+                typer
+                    .err("Position in ORDER BY is not in the select list", span)
+                    .frag(format!("Select list has {} columns", columns), select_span);
+            }
+            continue;
+        }
+        type_expression(typer, e, ExpressionFlags::default(), BaseType::Any);
+    }
+}
+
 pub(crate) fn type_select<'a>(
     typer: &mut Typer<'a, '_>,
     select: &Select<'a>,
@@ -196,10 +289,14 @@ pub(crate) fn type_select<'a>(
 ) -> SelectType<'a> {
     let mut guard = typer_stack(
         typer,
-        |t| t.reference_types.clone(),
-        |t, v| t.reference_types = v,
+        |t| (t.reference_types.clone(), t.grouped),
+        |t, (reference_types, grouped)| {
+            t.reference_types = reference_types;
+            t.grouped = grouped;
+        },
     );
     let typer = &mut guard.typer;
+    typer.grouped = select.group_by.is_some();
 
     for flag in &select.flags {
         match &flag {
@@ -242,9 +339,8 @@ pub(crate) fn type_select<'a>(
     }
 
     if let Some((_, order_by)) = &select.order_by {
-        for (e, _) in order_by {
-            type_expression(typer, e, ExpressionFlags::default(), BaseType::Any);
-        }
+        let span = select.select_exprs.opt_span().expect("select_exprs span");
+        type_order_by(typer, order_by, result.len(), &span);
     }
 
     if let Some((having, _)) = &select.having {
@@ -259,23 +355,25 @@ pub(crate) fn type_select<'a>(
         typer.ensure_base(having, &t, BaseType::Bool);
     }
 
-    if let Some((_, offset, count)) = &select.limit {
-        if let Some(offset) = offset {
-            let t = type_expression(typer, offset, ExpressionFlags::default(), BaseType::Integer);
-            if typer
-                .matched_type(&t, &FullType::new(Type::U64, true))
-                .is_none()
-            {
-                typer.err(format!("Expected integer type got {}", t.t), offset);
+    check_aggregates(typer, select);
+
+    if let Some((_, tables)) = select.locking.as_ref().and_then(|l| l.of.as_ref()) {
+        for table in tables {
+            if !typer.reference_types.iter().any(|r| {
+                r.name
+                    .as_ref()
+                    .is_some_and(|n| typer.options.same_name(n.value, table.value))
+            }) {
+                typer.err("Unknown table in locking clause", table);
             }
         }
-        let t = type_expression(typer, count, ExpressionFlags::default(), BaseType::Integer);
-        if typer
-            .matched_type(&t, &FullType::new(Type::U64, true))
-            .is_none()
-        {
-            typer.err(format!("Expected integer type got {}", t.t), count);
+    }
+
+    if let Some((_, offset, count)) = &select.limit {
+        if let Some(offset) = offset {
+            type_limit(typer, offset, "offset");
         }
+        type_limit(typer, count, "limit");
     }
 
     SelectType {
@@ -295,8 +393,10 @@ pub(crate) fn type_select_exprs<'a, 'b>(
     let mut result = Vec::new();
     let mut select_reference = ReferenceType {
         name: None,
+        database: None,
         span: select_exprs.opt_span().expect("select_exprs span"),
         columns: Vec::new(),
+        using: Vec::new(),
     };
 
     for e in select_exprs {
@@ -329,7 +429,7 @@ pub(crate) fn type_select_exprs<'a, 'b>(
                 if typer.options.warn_unnamed_column_in_select {
                     typer.issues.warn("Unnamed column in select", e);
                 }
-                add_result(typer.issues, None, type_, 0..0, false);
+                add_result(typer.issues, None, type_, e.expr.span(), false);
             };
         }
     }
@@ -339,80 +439,118 @@ pub(crate) fn type_select_exprs<'a, 'b>(
     result
 }
 
+/// Signedness and width in bits of a sized integer type
+fn integer_size(t: &Type<'_>) -> Option<(bool, u8)> {
+    match t {
+        Type::I8 => Some((true, 8)),
+        Type::I16 => Some((true, 16)),
+        Type::I32 => Some((true, 32)),
+        Type::I64 => Some((true, 64)),
+        Type::U8 => Some((false, 8)),
+        Type::U16 => Some((false, 16)),
+        Type::U32 => Some((false, 32)),
+        Type::U64 => Some((false, 64)),
+        _ => None,
+    }
+}
+
+/// The type of a union column with values of the types l and r
+///
+/// Integers are widened to hold values of both sides, decimals are widened to
+/// hold both sides when combined with decimals or integers, integers and
+/// floats combine to doubles and strings, enums and sets combine to strings
+fn union_type<'a>(typer: &mut Typer<'a, '_>, l: &Type<'a>, r: &Type<'a>) -> Option<Type<'a>> {
+    if l == r {
+        return Some(l.clone());
+    }
+    if matches!(l, Type::Args(..)) || matches!(r, Type::Args(..)) {
+        return typer.matched_type(l, r);
+    }
+    if matches!(l, Type::Decimal(..)) || matches!(r, Type::Decimal(..)) {
+        if let (Some((lp, ls)), Some((rp, rs))) = (l.decimal_digits(), r.decimal_digits()) {
+            let s = ls.max(rs);
+            return Some(Type::Decimal(((lp - ls).max(rp - rs) + s).min(65), s));
+        }
+    }
+    match (l.base(), r.base()) {
+        (BaseType::Integer, BaseType::Integer) => {
+            let t = match (integer_size(l), integer_size(r)) {
+                (Some((ls, lb)), Some((rs, rb))) if ls == rs => {
+                    if lb >= rb {
+                        l.clone()
+                    } else {
+                        r.clone()
+                    }
+                }
+                (Some((true, sb)), Some((false, ub))) | (Some((false, ub)), Some((true, sb))) => {
+                    match sb.max(ub * 2) {
+                        16 => Type::I16,
+                        32 => Type::I32,
+                        64 => Type::I64,
+                        _ => BaseType::Integer.into(),
+                    }
+                }
+                _ => BaseType::Integer.into(),
+            };
+            Some(t)
+        }
+        (BaseType::Integer | BaseType::Float, BaseType::Integer | BaseType::Float) => {
+            Some(Type::F64)
+        }
+        (BaseType::String, BaseType::String) => Some(BaseType::String.into()),
+        _ => typer.matched_type(l, r),
+    }
+}
+
+/// Type a union, the columns are named and ordered as in the first select
 pub(crate) fn type_union<'a>(typer: &mut Typer<'a, '_>, union: &Union<'a>) -> SelectType<'a> {
     let mut t = type_union_select(typer, &union.left, true);
     let mut left = union.left.span();
     for w in &union.with {
         let t2 = type_union_select(typer, &w.union_statement, true);
 
-        for i in 0..usize::max(t.columns.len(), t2.columns.len()) {
-            if let Some(l) = t.columns.get_mut(i) {
-                if let Some(r) = t2.columns.get(i) {
-                    if l.name != r.name {
-                        if let Some(ln) = &l.name {
-                            if let Some(rn) = &r.name {
-                                typer
-                                    .err("Incompatible names in union", &w.union_span)
-                                    .frag(format!("Column {} is named {}", i, ln), &left)
-                                    .frag(
-                                        format!("Column {} is named {}", i, rn),
-                                        &w.union_statement,
-                                    );
-                            } else {
-                                typer
-                                    .err("Incompatible names in union", &w.union_span)
-                                    .frag(format!("Column {} is named {}", i, ln), &left)
-                                    .frag(format!("Column {} has no name", i), &w.union_statement);
-                            }
-                        } else {
-                            typer
-                                .err("Incompatible names in union", &w.union_span)
-                                .frag(format!("Column {} has no name", i), &left)
-                                .frag(
-                                    format!(
-                                        "Column {} is named {}",
-                                        i,
-                                        r.name.as_ref().expect("name")
-                                    ),
-                                    &w.union_statement,
-                                );
-                        }
-                    }
-                    if l.type_.t == r.type_.t {
-                        l.type_ =
-                            FullType::new(l.type_.t.clone(), l.type_.not_null && r.type_.not_null);
-                    } else if let Some(t) = typer.matched_type(&l.type_, &r.type_) {
-                        l.type_ = FullType::new(t, l.type_.not_null && r.type_.not_null);
-                    } else {
-                        typer
-                            .err("Incompatible types in union", &w.union_span)
-                            .frag(format!("Column {} is of type {}", i, l.type_.t), &left)
-                            .frag(
-                                format!("Column {} is of type {}", i, r.type_.t),
-                                &w.union_statement,
-                            );
-                    }
-                } else if let Some(n) = &l.name {
-                    typer
-                        .err("Incompatible types in union", &w.union_span)
-                        .frag(format!("Column {} ({}) only on this side", i, n), &left);
-                } else {
-                    typer
-                        .err("Incompatible types in union", &w.union_span)
-                        .frag(format!("Column {} only on this side", i), &left);
-                }
-            } else if let Some(n) = &t2.columns[i].name {
+        if t.columns.len() != t2.columns.len() {
+            typer
+                .err("Different number of columns in union", &w.union_span)
+                .frag(format!("{} columns", t.columns.len()), &left)
+                .frag(format!("{} columns", t2.columns.len()), &w.union_statement);
+        }
+
+        for (i, (l, r)) in t.columns.iter_mut().zip(&t2.columns).enumerate() {
+            if l.name != r.name && typer.options.warn_union_name_mismatch {
+                let describe = |name: &Option<Identifier<'a>>| match name {
+                    Some(n) => format!("Column {} is named {}", i, n),
+                    None => format!("Column {} has no name", i),
+                };
                 typer
-                    .err("Incompatible types in union", &w.union_span)
-                    .frag(
-                        format!("Column {} ({}) only on this side", i, n),
-                        &w.union_statement,
-                    );
+                    .warn("Incompatible names in union", &w.union_span)
+                    .frag(describe(&l.name), &left)
+                    .frag(describe(&r.name), &w.union_statement);
+            }
+            typer.check_collations(
+                "Union of strings with different collations",
+                &w.union_span,
+                (&left, &l.type_),
+                (&w.union_statement, &r.type_),
+            );
+            if let Some(t) = union_type(typer, &l.type_.t, &r.type_.t) {
+                let mut t = FullType::new(t, l.type_.not_null && r.type_.not_null);
+                // The collation is kept when both sides agree on it
+                if l.type_.charset == r.type_.charset && l.type_.collation == r.type_.collation {
+                    t = t.with_collation_of(&l.type_);
+                }
+                l.type_ = t;
             } else {
                 typer
-                    .err("Incompatible types in union", &w.union_span)
+                    .err_type_mismatch(
+                        "Incompatible types in union",
+                        &w.union_span,
+                        &r.type_.t,
+                        core::slice::from_ref(&l.type_.t),
+                    )
+                    .frag(format!("Column {} is of type {}", i, l.type_.t), &left)
                     .frag(
-                        format!("Column {} only on this side", i),
+                        format!("Column {} is of type {}", i, r.type_.t),
                         &w.union_statement,
                     );
             }
@@ -422,37 +560,25 @@ pub(crate) fn type_union<'a>(typer: &mut Typer<'a, '_>, union: &Union<'a>) -> Se
 
     typer.reference_types.push(ReferenceType {
         name: None,
+        database: None,
         span: t.span(),
         columns: t
             .columns
             .iter()
             .filter_map(|v| v.name.as_ref().map(|name| (name.clone(), v.type_.clone())))
             .collect(),
+        using: Vec::new(),
     });
 
     if let Some((_, order_by)) = &union.order_by {
-        for (e, _) in order_by {
-            type_expression(typer, e, ExpressionFlags::default(), BaseType::Any);
-        }
+        type_order_by(typer, order_by, t.columns.len(), &t.span());
     }
 
     if let Some((_, offset, count)) = &union.limit {
         if let Some(offset) = offset {
-            let t = type_expression(typer, offset, ExpressionFlags::default(), BaseType::Integer);
-            if typer
-                .matched_type(&t, &FullType::new(Type::U64, true))
-                .is_none()
-            {
-                typer.err(format!("Expected integer type got {}", t.t), offset);
-            }
-        }
-        let t = type_expression(typer, count, ExpressionFlags::default(), BaseType::Integer);
-        if typer
-            .matched_type(&t, &FullType::new(Type::U64, true))
-            .is_none()
-        {
-            typer.err(format!("Expected integer type got {}", t.t), count);
+            type_limit(typer, offset, "offset");
         }
+        type_limit(typer, count, "limit");
     }
 
     typer.reference_types.pop();
@@ -0,0 +1,116 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::format;
+use sql_parse::{Expression, Identifier, IdentifierPart};
+
+use crate::{
+    type_::{BaseType, FullType},
+    type_expression::{type_expression, ExpressionFlags},
+    typer::{typer_stack, Typer},
+    Type,
+};
+
+/// Base types of the values of commonly set system variables
+const SYSTEM_VARIABLES: &[(&str, BaseType)] = &[
+    ("auto_increment_increment", BaseType::Integer),
+    ("auto_increment_offset", BaseType::Integer),
+    ("autocommit", BaseType::Bool),
+    ("big_tables", BaseType::Bool),
+    ("character_set_client", BaseType::String),
+    ("character_set_connection", BaseType::String),
+    ("character_set_results", BaseType::String),
+    ("collation_connection", BaseType::String),
+    ("default_storage_engine", BaseType::String),
+    ("div_precision_increment", BaseType::Integer),
+    ("foreign_key_checks", BaseType::Bool),
+    ("group_concat_max_len", BaseType::Integer),
+    ("innodb_lock_wait_timeout", BaseType::Integer),
+    ("insert_id", BaseType::Integer),
+    ("interactive_timeout", BaseType::Integer),
+    ("last_insert_id", BaseType::Integer),
+    ("lc_time_names", BaseType::String),
+    ("lock_wait_timeout", BaseType::Integer),
+    ("long_query_time", BaseType::Float),
+    ("max_join_size", BaseType::Integer),
+    ("max_sort_length", BaseType::Integer),
+    ("max_statement_time", BaseType::Float),
+    ("net_read_timeout", BaseType::Integer),
+    ("net_write_timeout", BaseType::Integer),
+    ("optimizer_switch", BaseType::String),
+    ("profiling", BaseType::Bool),
+    ("sql_auto_is_null", BaseType::Bool),
+    ("sql_big_selects", BaseType::Bool),
+    ("sql_log_bin", BaseType::Bool),
+    ("sql_mode", BaseType::String),
+    ("sql_notes", BaseType::Bool),
+    ("sql_safe_updates", BaseType::Bool),
+    ("sql_select_limit", BaseType::Integer),
+    ("sql_warnings", BaseType::Bool),
+    ("time_zone", BaseType::String),
+    ("timestamp", BaseType::Float),
+    ("transaction_isolation", BaseType::String),
+    ("transaction_read_only", BaseType::Bool),
+    ("tx_isolation", BaseType::String),
+    ("tx_read_only", BaseType::Bool),
+    ("unique_checks", BaseType::Bool),
+    ("wait_timeout", BaseType::Integer),
+];
+
+/// Type the assignments of a SET statement
+pub(crate) fn type_set<'a>(typer: &mut Typer<'a, '_>, values: &[(Identifier<'a>, Expression<'a>)]) {
+    let mut guard = typer_stack(
+        typer,
+        |t| core::mem::take(&mut t.reference_types),
+        |t, v| t.reference_types = v,
+    );
+    let typer = &mut guard.typer;
+
+    for (name, value) in values {
+        let base = SYSTEM_VARIABLES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name.value))
+            .map(|(_, b)| *b);
+        if base.is_none() {
+            typer.warn("Unknown system variable", name);
+        }
+        // Values such as ON, OFF and DEFAULT are given as bare names
+        if matches!(value, Expression::Identifier(parts)
+            if matches!(parts.as_slice(), [IdentifierPart::Name(_)]))
+        {
+            continue;
+        }
+        let Some(base) = base else {
+            type_expression(typer, value, ExpressionFlags::default(), BaseType::Any);
+            continue;
+        };
+        typer.suggest_arg_name(value, name.value);
+        let expected = FullType::new(base, true);
+        let t = type_expression(typer, value, ExpressionFlags::default(), base);
+        // Boolean variables may be set to 0 or 1
+        if base == BaseType::Bool && t.base() == BaseType::Integer {
+            continue;
+        }
+        if typer.matched_type(&t, &expected).is_none() {
+            typer.err_type_mismatch(
+                format!("Got type {} expected {}", t, expected),
+                value,
+                &t.t,
+                core::slice::from_ref(&expected.t),
+            );
+        } else if let Type::Args(_, args) = &t.t {
+            for (idx, arg_type, span) in args.iter() {
+                typer.constrain_arg(*idx, arg_type, span, &expected);
+            }
+        }
+    }
+}
@@ -16,11 +16,13 @@ use alloc::vec::Vec;
 
 use crate::{
     schema::{Column, Schema},
-    type_delete::type_delete,
+    type_delete::{type_delete, type_truncate},
     type_insert_replace::{type_insert_replace, AutoIncrementId},
     type_select::{type_union, SelectType},
+    type_set::type_set,
     type_update::type_update,
     typer::Typer,
+    Type,
 };
 
 pub(crate) enum InnerStatementType<'a> {
@@ -30,10 +32,16 @@ pub(crate) enum InnerStatementType<'a> {
     },
     Insert {
         auto_increment_id: AutoIncrementId,
+        auto_increment_type: Option<Type<'a>>,
         returning: Option<SelectType<'a>>,
     },
     Update,
+    Set,
+    Transaction,
+    Truncate,
     Replace {
+        auto_increment_id: AutoIncrementId,
+        auto_increment_type: Option<Type<'a>>,
         returning: Option<SelectType<'a>>,
     },
     Invalid,
@@ -59,8 +67,12 @@ fn type_with_query<'a>(
                     columns.push(Column {
                         identifier: name.clone(),
                         type_: c.type_,
+                        declared_not_null: false,
                         auto_increment: false,
+                        default: false,
                         as_: None,
+                        semantic: None,
+                        shard_key: false,
                     });
                 }
             }
@@ -68,12 +80,16 @@ fn type_with_query<'a>(
                 identifier_span: block.identifier.span.clone(),
                 columns,
                 view: true,
+                unique_keys: Vec::new(),
             };
 
             let mut schemas = typer.with_schemas.clone();
             schemas.insert(block.identifier.as_str(), &schema);
-            let mut typer = typer.with_schemas(schemas);
-            type_with_query(&mut typer, rem_blocks, inner)
+            let mut block_typer = typer.with_schemas(schemas);
+            let r = type_with_query(&mut block_typer, rem_blocks, inner);
+            // Keep the arguments typed by the following blocks and the statement
+            typer.arg_types = core::mem::take(&mut block_typer.arg_types);
+            r
         } else {
             type_with_query(typer, rem_blocks, inner)
         }
@@ -97,19 +113,37 @@ pub(crate) fn type_statement<'a>(
             InnerStatementType::Delete { returning }
         }
         Statement::InsertReplace(ior) => {
-            let (auto_increment_id, returning) = type_insert_replace(typer, ior);
+            let (auto_increment_id, auto_increment_type, returning) =
+                type_insert_replace(typer, ior);
             match &ior.type_ {
                 InsertReplaceType::Insert(_) => InnerStatementType::Insert {
                     auto_increment_id,
+                    auto_increment_type,
+                    returning,
+                },
+                InsertReplaceType::Replace(_) => InnerStatementType::Replace {
+                    auto_increment_id,
+                    auto_increment_type,
                     returning,
                 },
-                InsertReplaceType::Replace(_) => InnerStatementType::Replace { returning },
             }
         }
         Statement::Update(u) => {
             type_update(typer, u);
             InnerStatementType::Update
         }
+        Statement::Set(s) => {
+            type_set(typer, &s.values);
+            InnerStatementType::Set
+        }
+        Statement::TruncateTable(t) => {
+            type_truncate(typer, t);
+            InnerStatementType::Truncate
+        }
+        Statement::Begin(_)
+        | Statement::End(_)
+        | Statement::Commit(_)
+        | Statement::StartTransaction(_) => InnerStatementType::Transaction,
         Statement::Union(u) => InnerStatementType::Select(type_union(typer, u)),
         Statement::WithQuery(w) => type_with_query(typer, &w.with_blocks, &w.statement),
         s => {
@@ -10,16 +10,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use sql_parse::{OptSpanned, Update};
+use alloc::vec::Vec;
+use sql_parse::{Identifier, OptSpanned, QualifiedName, Span, Update};
 
 use crate::{
+    schema::{find_table, Schema},
     type_::BaseType,
     type_expression::{type_expression, ExpressionFlags},
-    type_reference::type_reference,
+    type_reference::{base_tables, type_reference},
     typer::{typer_stack, Typer},
     Type,
 };
 
+/// Report assignments to columns that are not stored columns of a table
+fn check_updatable<'a>(
+    typer: &mut Typer<'a, '_>,
+    tables: &[(&Identifier<'a>, &QualifiedName<'a>)],
+    reference: Option<&Identifier<'a>>,
+    column: &Identifier<'a>,
+    span: &Span,
+) {
+    let Some((_, table)) = tables
+        .iter()
+        .find(|(n, _)| reference.is_some_and(|r| typer.options.same_name(n, r)))
+    else {
+        typer.err("Only columns of tables can be updated", span);
+        return;
+    };
+    let schema: Option<&Schema<'a>> = match table.prefix.as_slice() {
+        [(database, _)] if !typer.options.is_default_database(database.value) => typer
+            .schemas
+            .databases
+            .get(database.value)
+            .and_then(|tables| find_table(tables, table.identifier.value, typer.options))
+            .map(|(_, schema)| schema),
+        _ => typer.get_schema(table.identifier.value),
+    };
+    if schema
+        .and_then(|s| s.find_column(column.value, typer.options))
+        .is_some_and(|c| c.as_.is_some())
+    {
+        typer.err("Generated columns can not be updated", span);
+    }
+}
+
 pub(crate) fn type_update<'a>(typer: &mut Typer<'a, '_>, update: &Update<'a>) {
     let mut guard = typer_stack(
         typer,
@@ -34,21 +68,28 @@ pub(crate) fn type_update<'a>(typer: &mut Typer<'a, '_>, update: &Update<'a>) {
         }
     }
 
+    let mut tables = Vec::new();
     for reference in &update.tables {
         type_reference(typer, reference, false);
+        base_tables(reference, &mut tables);
     }
 
     for (key, value) in &update.set {
         let flags = ExpressionFlags::default();
+        if let Some(column) = key.last() {
+            typer.suggest_arg_name(value, column.value);
+        }
         match key.as_slice() {
             [key] => {
                 let mut cnt = 0;
                 let mut t = None;
+                let mut from = None;
                 for r in &typer.reference_types {
                     for c in &r.columns {
-                        if c.0 == *key {
+                        if typer.options.same_name(&c.0, key) {
                             cnt += 1;
                             t = Some(c.clone());
+                            from = r.name.clone();
                         }
                     }
                 }
@@ -59,21 +100,27 @@ pub(crate) fn type_update<'a>(typer: &mut Typer<'a, '_>, update: &Update<'a>) {
                         .err("Ambiguous reference", &key.opt_span().unwrap());
                     for r in &typer.reference_types {
                         for c in &r.columns {
-                            if c.0 == *key {
+                            if typer.options.same_name(&c.0, key) {
                                 issue.frag("Defined here", &r.span);
                             }
                         }
                     }
                 } else if let Some(t) = t {
+                    let span = key.opt_span().unwrap();
+                    check_updatable(typer, &tables, from.as_ref(), key, &span);
                     let value_type = type_expression(typer, value, flags, t.1.base());
+                    typer.check_string_literal(value, &t.1);
+                    typer.check_precision_loss(value, &value_type, &t.1);
                     if typer.matched_type(&value_type, &t.1).is_none() {
-                        typer.err(
+                        typer.err_type_mismatch(
                             alloc::format!("Got type {} expected {}", value_type, t.1),
                             value,
+                            &value_type.t,
+                            core::slice::from_ref(&t.1.t),
                         );
                     } else if let Type::Args(_, args) = &value_type.t {
-                        for (idx, arg_type, _) in args.iter() {
-                            typer.constrain_arg(*idx, arg_type, &t.1);
+                        for (idx, arg_type, span) in args.iter() {
+                            typer.constrain_arg(*idx, arg_type, span, &t.1);
                         }
                     }
                 } else {
@@ -86,25 +133,35 @@ pub(crate) fn type_update<'a>(typer: &mut Typer<'a, '_>, update: &Update<'a>) {
             [table, column] => {
                 let mut t = None;
                 for r in &typer.reference_types {
-                    if r.name != Some(table.clone()) {
+                    if !r
+                        .name
+                        .as_ref()
+                        .is_some_and(|n| typer.options.same_name(n, table))
+                    {
                         continue;
                     }
                     for c in &r.columns {
-                        if c.0 == column.clone() {
+                        if typer.options.same_name(&c.0, column) {
                             t = Some(c.clone());
                         }
                     }
                 }
                 if let Some(t) = t {
+                    let span = key.opt_span().unwrap();
+                    check_updatable(typer, &tables, Some(table), column, &span);
                     let value_type = type_expression(typer, value, flags, t.1.base());
+                    typer.check_string_literal(value, &t.1);
+                    typer.check_precision_loss(value, &value_type, &t.1);
                     if typer.matched_type(&value_type, &t.1).is_none() {
-                        typer.err(
+                        typer.err_type_mismatch(
                             alloc::format!("Got type {} expected {}", value_type, t.1),
                             value,
+                            &value_type.t,
+                            core::slice::from_ref(&t.1.t),
                         );
                     } else if let Type::Args(_, args) = &value_type.t {
-                        for (idx, arg_type, _) in args.iter() {
-                            typer.constrain_arg(*idx, arg_type, &t.1);
+                        for (idx, arg_type, span) in args.iter() {
+                            typer.constrain_arg(*idx, arg_type, span, &t.1);
                         }
                     }
                 } else {
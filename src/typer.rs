@@ -13,22 +13,35 @@
 use alloc::borrow::Cow;
 
 use crate::{
-    schema::{Schema, Schemas},
+    schema::{find_table, Schema, Schemas},
     type_::{ArgType, BaseType, FullType},
-    ArgumentKey, Type, TypeOptions,
+    type_function::is_temporal,
+    ArgumentKey, StatementInfo, Type, TypeMismatch, TypeOptions,
 };
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use alloc::{collections::BTreeMap, format};
 use sql_parse::{
-    Identifier, IssueHandle, Issues, OptSpanned, QualifiedName, SQLDialect, Span, Spanned,
+    Expression, Identifier, IdentifierPart, IssueHandle, Issues, OptSpanned, QualifiedName,
+    SQLDialect, Span, Spanned,
 };
 
 #[derive(Clone, Debug)]
 pub(crate) struct ReferenceType<'a> {
     pub(crate) name: Option<Identifier<'a>>,
+    /// Database of a table given by a qualified name without alias
+    pub(crate) database: Option<Identifier<'a>>,
     pub(crate) span: Span,
     pub(crate) columns: Vec<(Identifier<'a>, FullType<'a>)>,
+    /// Columns merged by JOIN ... USING, they are only found when qualified
+    pub(crate) using: Vec<Identifier<'a>>,
+}
+
+impl ReferenceType<'_> {
+    /// Is the column named col merged by JOIN ... USING
+    pub(crate) fn is_using(&self, col: &str, options: &TypeOptions) -> bool {
+        self.using.iter().any(|u| options.same_name(u.value, col))
+    }
 }
 
 pub(crate) struct Typer<'a, 'b> {
@@ -38,6 +51,10 @@ pub(crate) struct Typer<'a, 'b> {
     pub(crate) reference_types: Vec<ReferenceType<'a>>,
     pub(crate) arg_types: Vec<(ArgumentKey<'a>, FullType<'a>)>,
     pub(crate) options: &'b TypeOptions,
+    pub(crate) info: &'b mut StatementInfo<'a>,
+    /// The select being typed has a GROUP BY, so every aggregate is over at
+    /// least one row
+    pub(crate) grouped: bool,
 }
 
 impl<'a, 'b> Typer<'a, 'b> {
@@ -55,6 +72,8 @@ impl<'a, 'b> Typer<'a, 'b> {
             reference_types: self.reference_types.clone(),
             arg_types: self.arg_types.clone(),
             options: self.options,
+            info: self.info,
+            grouped: self.grouped,
         }
     }
 
@@ -62,7 +81,40 @@ impl<'a, 'b> Typer<'a, 'b> {
         self.options.parse_options.get_dialect()
     }
 
-    pub(crate) fn constrain_arg(&mut self, idx: usize, arg_type: &ArgType, t: &FullType<'a>) {
+    /// Record a use of the argument with index `idx`. Arguments declared with
+    /// [crate::TypeOptions::argument_type] get their declared type, declared
+    /// arguments that are never used are not part of the statement's arguments
+    pub(crate) fn use_arg(&mut self, idx: usize) {
+        let key = ArgumentKey::Index(idx);
+        if self.arg_types.iter().any(|(k, _)| k == &key) {
+            return;
+        }
+        if let Some((_, declared)) = self.options.argument_types.iter().find(|(i, _)| *i == idx) {
+            self.arg_types.push((key, declared.clone()));
+        }
+    }
+
+    pub(crate) fn constrain_arg(
+        &mut self,
+        idx: usize,
+        arg_type: &ArgType,
+        span: &Span,
+        t: &FullType<'a>,
+    ) {
+        if let Some((_, declared)) = self.options.argument_types.iter().find(|(i, _)| *i == idx) {
+            self.use_arg(idx);
+            // The declared type is kept, uses must agree with its base type
+            if t.base() != BaseType::Any && t.base() != declared.base() {
+                let declared = declared.t.clone();
+                self.err_type_mismatch(
+                    format!("Argument declared as {} used as {}", declared, t.t),
+                    span,
+                    &t.t,
+                    &[declared],
+                );
+            }
+            return;
+        }
         // TODO Use arg_type
         let ot = match self
             .arg_types
@@ -95,6 +147,41 @@ impl<'a, 'b> Typer<'a, 'b> {
             return Some(t1.clone());
         }
 
+        match (t1, t2) {
+            (Type::Custom(c1), Type::Custom(c2)) => {
+                if c1 == c2 {
+                    return Some(t1.clone());
+                }
+                if c1.0.accepts(c2.0.base()) && c2.0.accepts(c1.0.base()) {
+                    return Some(c1.0.base().into());
+                }
+                return None;
+            }
+            (Type::Custom(c), o) | (o, Type::Custom(c)) => {
+                let b = o.base();
+                if b != BaseType::Any && !c.0.accepts(b) {
+                    return None;
+                }
+                if let Type::Args(_, a) = o {
+                    for (idx, arg_type, span) in a.iter() {
+                        self.constrain_arg(*idx, arg_type, span, &FullType::new(c.0.base(), false));
+                    }
+                }
+                return Some(Type::Custom(c.clone()));
+            }
+            // Integers are compared and combined with decimals exactly
+            (Type::Decimal(..), o) | (o, Type::Decimal(..))
+                if o.base() == BaseType::Integer && !matches!(o, Type::Args(..)) =>
+            {
+                return Some(if matches!(t1, Type::Decimal(..)) {
+                    t1.clone()
+                } else {
+                    t2.clone()
+                });
+            }
+            _ => (),
+        }
+
         let mut t1b = t1.base();
         let mut t2b = t2.base();
         if t1b == BaseType::Any {
@@ -109,8 +196,8 @@ impl<'a, 'b> Typer<'a, 'b> {
 
         for t in &[t1, t2] {
             if let Type::Args(_, a) = t {
-                for (idx, arg_type, _) in a.iter() {
-                    self.constrain_arg(*idx, arg_type, &FullType::new(t1b, false));
+                for (idx, arg_type, span) in a.iter() {
+                    self.constrain_arg(*idx, arg_type, span, &FullType::new(t1b, false));
                 }
             }
         }
@@ -135,9 +222,11 @@ impl<'a, 'b> Typer<'a, 'b> {
         expected: &FullType<'a>,
     ) {
         if self.matched_type(given, expected).is_none() {
-            self.issues.err(
+            self.err_type_mismatch(
                 format!("Expected type {} got {}", expected.t, given.t),
                 span,
+                given,
+                core::slice::from_ref(&expected.t),
             );
         }
     }
@@ -151,14 +240,186 @@ impl<'a, 'b> Typer<'a, 'b> {
         self.ensure_type(span, given, &FullType::new(expected, false));
     }
 
-    pub(crate) fn get_schema(&self, name: &str) -> Option<&'b Schema<'a>> {
-        if let Some(schema) = self.with_schemas.get(name) {
+    /// Check a string literal assigned to or compared with a value of type
+    /// `t`. Values of enums and sets must be declared members, and with
+    /// [TypeOptions::warn_string_length] values should fit the declared length
+    pub(crate) fn check_string_literal(&mut self, expr: &Expression<'a>, t: &FullType<'a>) {
+        let Expression::String(s) = expr else {
+            return;
+        };
+        match &t.t {
+            Type::Enum(members) => self.check_members("enum", expr, [&*s.value], members),
+            // The value of a set is a comma separated list of members
+            Type::Set(members) if !s.value.is_empty() => {
+                self.check_members("set", expr, s.value.split(','), members)
+            }
+            Type::Uuid if !is_uuid(&s.value) => {
+                self.err("Invalid uuid", expr);
+            }
+            _ => (),
+        }
+        if !self.options.warn_string_length {
+            return;
+        }
+        if let Some(length) = t.length {
+            let n = s.value.chars().count();
+            if n > length as usize {
+                self.warn(
+                    format!(
+                        "String of length {} is longer than the column length {}",
+                        n, length
+                    ),
+                    expr,
+                );
+            }
+        }
+    }
+
+    fn check_members<'v>(
+        &mut self,
+        kind: &str,
+        expr: &Expression<'a>,
+        values: impl IntoIterator<Item = &'v str>,
+        members: &[Cow<'a, str>],
+    ) {
+        for v in values {
+            // Members are compared without regard to case, as with the default collations
+            if members.iter().any(|m| m.eq_ignore_ascii_case(v)) {
+                continue;
+            }
+            let closest = members
+                .iter()
+                .map(|m| (edit_distance(m, v), m))
+                .min_by_key(|(d, _)| *d)
+                .filter(|(d, _)| d * 3 <= v.chars().count().max(3));
+            match closest {
+                Some((_, m)) => self.err(
+                    format!("Unknown {} value '{}', did you mean '{}'", kind, v, m),
+                    expr,
+                ),
+                None => self.err(format!("Unknown {} value '{}'", kind, v), expr),
+            };
+        }
+    }
+
+    /// Warn about assigning a temporal value with more fractional second
+    /// digits than the column of type `t` holds, as the digits are dropped
+    pub(crate) fn check_precision_loss(
+        &mut self,
+        span: &impl Spanned,
+        value_type: &FullType<'a>,
+        t: &FullType<'a>,
+    ) {
+        if !is_temporal(t.base()) || !is_temporal(value_type.base()) {
+            return;
+        }
+        let (got, max) = (value_type.precision.unwrap_or(0), t.precision.unwrap_or(0));
+        if got > max {
+            self.warn(
+                format!(
+                    "Value with {} fractional second digits is truncated to {}",
+                    got, max
+                ),
+                span,
+            );
+        }
+    }
+
+    /// Warn about comparing a uuid with a string other than a literal, when
+    /// enabled with [TypeOptions::warn_uuid_string_comparison]
+    pub(crate) fn check_uuid_comparison(
+        &mut self,
+        span: &impl Spanned,
+        (lhs, lhs_type): (&Expression<'a>, &FullType<'a>),
+        (rhs, rhs_type): (&Expression<'a>, &FullType<'a>),
+    ) {
+        if !self.options.warn_uuid_string_comparison {
+            return;
+        }
+        let is_string = |e: &Expression<'_>, t: &FullType<'_>| {
+            matches!(t.t, Type::Base(BaseType::String)) && !matches!(e, Expression::String(_))
+        };
+        if (lhs_type.t == Type::Uuid && is_string(rhs, rhs_type))
+            || (rhs_type.t == Type::Uuid && is_string(lhs, lhs_type))
+        {
+            self.warn("Comparison of uuid with string", span)
+                .frag(format!("Of type {}", lhs_type.t), lhs)
+                .frag(format!("Of type {}", rhs_type.t), rhs);
+        }
+    }
+
+    /// Warn about combining strings of different collations, which fails
+    /// with "Illegal mix of collations" when executed
+    pub(crate) fn check_collations(
+        &mut self,
+        message: &'static str,
+        span: &impl Spanned,
+        (lhs, lhs_type): (&impl Spanned, &FullType<'a>),
+        (rhs, rhs_type): (&impl Spanned, &FullType<'a>),
+    ) {
+        if let (Some(l), Some(r)) = (&lhs_type.collation, &rhs_type.collation) {
+            if !l.eq_ignore_ascii_case(r) {
+                self.warn(message, span)
+                    .frag(format!("Has collation {}", l), lhs)
+                    .frag(format!("Has collation {}", r), rhs);
+            }
+        }
+    }
+
+    /// Suggest `name` as the name of `expr` if it is an argument without a name.
+    /// Names already given to other arguments get a suffix, like `x_2`
+    pub(crate) fn suggest_arg_name(&mut self, expr: &Expression<'a>, name: &'a str) {
+        let idx = match expr {
+            Expression::Arg((idx, _)) | Expression::ListHack((idx, _)) => *idx,
+            _ => return,
+        };
+        let key = ArgumentKey::Index(idx);
+        let names = &self.info.argument_names;
+        if names.iter().any(|(k, _)| k == &key) {
+            return;
+        }
+        let taken = |n: &str| names.iter().any(|(_, v)| v == n);
+        let name = if taken(name) {
+            let mut i = 2;
+            while taken(&format!("{}_{}", name, i)) {
+                i += 1;
+            }
+            Cow::Owned(format!("{}_{}", name, i))
+        } else {
+            Cow::Borrowed(name)
+        };
+        self.info.argument_names.push((key, name));
+    }
+
+    /// The name of a table that must be in the default database, either
+    /// unqualified or qualified with the database given in the options
+    pub(crate) fn local_name<'c>(&mut self, name: &'c QualifiedName<'a>) -> &'c Identifier<'a> {
+        match name.prefix.as_slice() {
+            [(database, _)] if self.options.is_default_database(database.value) => &name.identifier,
+            _ => unqualified_name(self.issues, name),
+        }
+    }
+
+    pub(crate) fn get_schema(&mut self, name: &'a str) -> Option<&'b Schema<'a>> {
+        let options = self.options;
+        if let Some((_, schema)) = self
+            .with_schemas
+            .iter()
+            .find(|(n, _)| options.same_name(n, name))
+        {
             Some(schema)
         } else {
-            self.schemas.schemas.get(name)
+            let (id, schema) = find_table(&self.schemas.schemas, name, options)?;
+            self.use_table(id.value);
+            Some(schema)
         }
     }
 
+    /// Record that the statement depends on the table or view `name` in the schemas
+    pub(crate) fn use_table(&mut self, name: &'a str) {
+        self.info.tables.push(name);
+    }
+
     pub(crate) fn err(
         &mut self,
         message: impl Into<Cow<'static, str>>,
@@ -167,6 +428,23 @@ impl<'a, 'b> Typer<'a, 'b> {
         self.issues.err(message, span)
     }
 
+    /// Report that the type `got` did not match any of the types in `expected`
+    pub(crate) fn err_type_mismatch(
+        &mut self,
+        message: impl Into<Cow<'static, str>>,
+        span: &impl Spanned,
+        got: &Type<'a>,
+        expected: &[Type<'a>],
+    ) -> IssueHandle<'a, '_> {
+        let span = span.span();
+        self.info.type_mismatches.push(TypeMismatch {
+            span: span.clone(),
+            got: got.clone(),
+            expected: expected.to_vec(),
+        });
+        self.issues.err(message, &span)
+    }
+
     pub(crate) fn warn(
         &mut self,
         message: impl Into<Cow<'static, str>>,
@@ -220,3 +498,47 @@ pub(crate) fn unqualified_name<'b, 'c>(
     }
     &name.identifier
 }
+
+/// Name of the column referenced by `expr`, if it is an identifier
+pub(crate) fn column_name<'a>(expr: &Expression<'a>) -> Option<&'a str> {
+    match expr {
+        Expression::Identifier(parts) => match parts.last() {
+            Some(IdentifierPart::Name(n)) => Some(n.value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Is s a uuid, as 32 hexadecimal digits optionally grouped by dashes in
+/// groups of 8, 4, 4, 4 and 12 digits
+fn is_uuid(s: &str) -> bool {
+    let digits = |v: &str| v.bytes().all(|c| c.is_ascii_hexdigit());
+    if s.len() == 32 {
+        return digits(s);
+    }
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12]) && groups.iter().all(|g| digits(g))
+}
+
+/// Number of characters to insert, remove or change to turn a into b,
+/// ignoring case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let ca = ca.to_ascii_lowercase();
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}